@@ -0,0 +1,80 @@
+//! The `specdown` command line interface.
+
+use std::path::PathBuf;
+
+use crate::commands;
+use crate::config::Config;
+use clap::{CommandFactory, Parser, Subcommand};
+
+#[derive(Parser)]
+#[clap(version, about, long_about = None)]
+struct Cli {
+    /// Disables coloured output
+    #[clap(long)]
+    no_colour: bool,
+
+    /// Load settings from a specific config file instead of looking for
+    /// `specdown.toml` in the current directory and those above it
+    #[clap(long, value_name = "PATH")]
+    config: Option<PathBuf>,
+
+    /// Overlay the `[run]` settings with those of a `[profile.<NAME>]` table
+    /// in the config file. Defaults to the `SPECDOWN_PROFILE` environment
+    /// variable
+    #[clap(long, value_name = "NAME")]
+    profile: Option<String>,
+
+    #[clap(subcommand)]
+    command: Commands,
+}
+
+#[derive(Subcommand)]
+enum Commands {
+    /// Checks Markdown Specifications for problems without running them
+    Check(commands::check::Arguments),
+
+    /// Output completion for a shell of your choice
+    Completion(commands::completion::Arguments),
+
+    /// Runs a language server for Markdown Specifications over stdio
+    Lsp,
+
+    /// Runs a given Markdown Specification
+    Run(Box<commands::run::RunSettings>),
+
+    /// Outputs a version of the markdown with all specdown functions removed
+    Strip(commands::strip::Arguments),
+}
+
+/// Runs the `specdown` command line tool with the process arguments.
+pub fn run() {
+    let cli = Cli::parse();
+
+    let config = Config {
+        colour: !cli.no_colour,
+        file: cli.config,
+        profile: cli.profile.or_else(|| {
+            std::env::var("SPECDOWN_PROFILE")
+                .ok()
+                .filter(|profile| !profile.is_empty())
+        }),
+    };
+
+    match cli.command {
+        Commands::Check(args) => {
+            commands::check::execute(&config, &args);
+        }
+        Commands::Completion(args) => {
+            commands::completion::execute(&mut Cli::command(), &args);
+        }
+        Commands::Lsp => {
+            commands::lsp::execute();
+        }
+        Commands::Run(args) => {
+            commands::run::execute(&config, &args);
+        }
+        Commands::Strip(args) => {
+            commands::strip::execute(&args);
+        }
+    }
+}
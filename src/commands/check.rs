@@ -0,0 +1,107 @@
+use std::fs;
+use std::path::PathBuf;
+
+use clap::Args;
+use crossterm::style::Stylize;
+
+use crate::commands::run::front_matter::FrontMatter;
+use crate::commands::run::{config_file, file_discovery};
+use crate::config::Config;
+use crate::exit_codes::ExitCode;
+use crate::parsers;
+use crate::runner::Error;
+
+#[derive(Args)]
+pub struct Arguments {
+    /// The spec files to check. Defaults to the `files` in `specdown.toml`
+    #[clap()]
+    spec_files: Vec<PathBuf>,
+
+    /// Follow local Markdown links found in spec files and check every
+    /// linked file too, as `specdown run --follow-links` does
+    #[clap(long)]
+    follow_links: bool,
+}
+
+pub fn execute(config: &Config, args: &Arguments) {
+    let exit_code = match check_files(config, args) {
+        Ok(0) => ExitCode::Success,
+        Ok(_) => ExitCode::TestFailed,
+        Err(err) => {
+            println!("  \u{2717} {err}");
+            ExitCode::ErrorOccurred
+        }
+    };
+
+    std::process::exit(exit_code as i32)
+}
+
+/// Prints the problems in every spec file and returns how many were found.
+fn check_files(config: &Config, args: &Arguments) -> Result<usize, Error> {
+    let current_dir = std::env::current_dir().expect("Failed to get current workspace directory");
+    let file_settings = config_file::load_run_settings(
+        config.file.as_deref(),
+        config.profile.as_deref(),
+        &current_dir,
+    )?;
+
+    let spec_files = if args.spec_files.is_empty() {
+        file_settings.spec_files
+    } else {
+        args.spec_files.clone()
+    };
+    let follow_links = args.follow_links || file_settings.follow_links;
+    let spec_files =
+        file_discovery::find_spec_files(&spec_files, &file_settings.exclude, &current_dir)?;
+    let spec_files = file_discovery::build_file_list(&spec_files, &current_dir, follow_links)?;
+
+    let mut problem_count = 0;
+    for spec_file in &spec_files {
+        let contents = fs::read_to_string(current_dir.join(spec_file)).map_err(|err| {
+            Error::SpecFileUnreadable {
+                path: spec_file.display().to_string(),
+                message: err.to_string(),
+            }
+        })?;
+
+        let front_matter_problem =
+            FrontMatter::parse(&contents)
+                .err()
+                .map(|message| parsers::Problem {
+                    line: 1,
+                    span: None,
+                    message: format!("Invalid front matter: {message}"),
+                });
+        for problem in front_matter_problem
+            .into_iter()
+            .chain(parsers::check(&contents))
+        {
+            let location = format!("{}:{}:", spec_file.display(), problem.line);
+            if config.colour {
+                println!("{} {}", location.bold(), problem.message);
+            } else {
+                println!("{location} {}", problem.message);
+            }
+            problem_count += 1;
+        }
+    }
+
+    if problem_count > 0 {
+        println!();
+    }
+    println!(
+        "{} found in {}",
+        pluralise(problem_count, "problem"),
+        pluralise(spec_files.len(), "spec file")
+    );
+
+    Ok(problem_count)
+}
+
+fn pluralise(count: usize, noun: &str) -> String {
+    if count == 1 {
+        format!("{count} {noun}")
+    } else {
+        format!("{count} {noun}s")
+    }
+}
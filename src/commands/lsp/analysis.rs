@@ -0,0 +1,291 @@
+use lsp_types::{
+    CompletionItem, CompletionItemKind, Diagnostic, DiagnosticSeverity, Documentation,
+    MarkupContent, MarkupKind,
+};
+
+use super::text;
+use crate::parsers;
+
+/// Parse errors and other problems `specdown check` would report, pointing
+/// at the mistake when it can be found and at the code block's line when not.
+pub fn diagnostics(text: &str) -> Vec<Diagnostic> {
+    parsers::check(text)
+        .into_iter()
+        .map(|problem| Diagnostic {
+            range: match problem.span {
+                Some(span) => text::range(text, span),
+                None => text::line_range(text, problem.line),
+            },
+            severity: Some(DiagnosticSeverity::ERROR),
+            source: Some("specdown".to_string()),
+            message: problem.message,
+            ..Diagnostic::default()
+        })
+        .collect()
+}
+
+/// Function names when typing a function, or the arguments of the function
+/// being typed after its `(` or a `,`.
+pub fn completions(text: &str, offset: usize) -> Vec<CompletionItem> {
+    match context(text, offset) {
+        Some(Context::FunctionName) => parsers::FUNCTIONS
+            .iter()
+            .map(|name| CompletionItem {
+                label: (*name).to_string(),
+                kind: Some(CompletionItemKind::FUNCTION),
+                detail: Some(summary(name).to_string()),
+                documentation: Some(Documentation::MarkupContent(documentation(name))),
+                insert_text: Some(format!("{name}(")),
+                ..CompletionItem::default()
+            })
+            .collect(),
+        Some(Context::ArgumentName { function }) => parsers::argument_names(&function)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|name| CompletionItem {
+                label: name.to_string(),
+                kind: Some(CompletionItemKind::FIELD),
+                insert_text: Some(format!("{name}=")),
+                ..CompletionItem::default()
+            })
+            .collect(),
+        None => vec![],
+    }
+}
+
+/// The documentation of the function whose name is under `offset`.
+pub fn hover(text: &str, offset: usize) -> Option<MarkupContent> {
+    let start = function_start(text, offset)?;
+    let function = &text[start..];
+    let name_start = function.len() - function.trim_start().len();
+    let name_length = function[name_start..]
+        .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .unwrap_or(function.len() - name_start);
+    let name = &function[name_start..name_start + name_length];
+
+    let on_name = (start + name_start..=start + name_start + name_length).contains(&offset);
+    (on_name && parsers::FUNCTIONS.contains(&name)).then(|| documentation(name))
+}
+
+/// The line of the script named by the `script_name="..."` value under
+/// `offset`, counting from 1.
+pub fn definition(text: &str, offset: usize) -> Option<usize> {
+    let start = function_start(text, offset)?;
+    let before = &text[start..offset];
+    let quote = before.rfind('"')?;
+    let argument = before[..quote].trim_end().strip_suffix('=')?.trim_end();
+    if !argument.ends_with("script_name") {
+        return None;
+    }
+
+    let value = &text[start + quote + 1..];
+    let name = &value[..value.find('"')?];
+    parsers::script_definitions(text)
+        .into_iter()
+        .find(|(script_name, _)| script_name == name)
+        .map(|(_, line)| line)
+}
+
+/// What is being typed at the cursor.
+#[derive(Debug, Eq, PartialEq)]
+enum Context {
+    FunctionName,
+    ArgumentName { function: String },
+}
+
+fn context(text: &str, offset: usize) -> Option<Context> {
+    let start = function_start(text, offset)?;
+    let typed = &text[start..offset];
+    let Some(open) = typed.find('(') else {
+        return Some(Context::FunctionName);
+    };
+
+    // Track strings, lists and maps so a `,` or `=` inside a value isn't
+    // taken for one between arguments.
+    let mut in_string = false;
+    let mut escaped = false;
+    let mut depth = 0;
+    let mut expecting_name = true;
+    for c in typed[open + 1..].chars() {
+        if in_string {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match c {
+            '"' => in_string = true,
+            '[' | '{' => depth += 1,
+            ']' | '}' => depth -= 1,
+            ',' if depth == 0 => expecting_name = true,
+            '=' if depth == 0 => expecting_name = false,
+            ')' if depth == 0 => return None,
+            _ => {}
+        }
+    }
+
+    (expecting_name && !in_string).then(|| Context::ArgumentName {
+        function: typed[..open].trim().to_string(),
+    })
+}
+
+/// Finds where the specdown function around `offset` starts: after the first
+/// `,` in a fence's info string, or after `specdown:` in an annotation
+/// comment, which may span several lines.
+fn function_start(text: &str, offset: usize) -> Option<usize> {
+    let line_start = text[..offset].rfind('\n').map_or(0, |newline| newline + 1);
+    let line = &text[line_start..offset];
+    let fence = line.trim_start();
+    if fence.starts_with("```") || fence.starts_with("~~~") {
+        return line.find(',').map(|comma| line_start + comma + 1);
+    }
+
+    let comment_start = text[..offset].rfind("<!--")?;
+    let comment = &text[comment_start..offset];
+    if comment.contains("-->") {
+        return None;
+    }
+    comment
+        .find("specdown:")
+        .map(|marker| comment_start + marker + "specdown:".len())
+}
+
+fn documentation(function: &str) -> MarkupContent {
+    let arguments = parsers::argument_names(function)
+        .unwrap_or_default()
+        .join(", ");
+    MarkupContent {
+        kind: MarkupKind::Markdown,
+        value: format!(
+            "```\n{function}({arguments})\n```\n\n{}",
+            description(function)
+        ),
+    }
+}
+
+/// A one-line description of each function.
+fn summary(function: &str) -> &'static str {
+    description(function).split('\n').next().unwrap_or_default()
+}
+
+fn description(function: &str) -> &'static str {
+    match function {
+        "script" => "Runs the code block as a script.\n\nName it with `name` so a later `verify` block can check its output. `expected_exit_code` checks the exit code, `expected_output` (`any`, `none`, `stdout` or `stderr`) which streams may have output, and `interpreter` runs it with a program other than the shell.",
+        "session" => "Runs the code block as a script in a shell session shared by the whole spec file.\n\nIt takes the same arguments as `script`. A `console` block is a transcript of commands, each prefixed by `$ `, and their output.",
+        "verify" => "Checks the code block matches the output of a script.\n\n`script_name` is the script to check, the last one run when not given. `stream` is `output`, `stdout` or `stderr`, and `target_os` limits the check to one operating system. `mock_command` checks the calls made to a mocked command instead.",
+        "file" => "Creates a file at `path` with the code block as its contents.",
+        "background" => "Starts the code block as a script in the background, stopped when the spec file ends.\n\n`ready_when` waits for a condition, such as `file:server.pid`, before carrying on, for up to `timeout_secs` seconds.",
+        "response" => "Defines a mock response with the code block as its body.\n\nIt is paired by `name` with the request which consumes it. `status`, `headers`, `content_type` and `delay` set the rest of the response.",
+        "verify_tree" => "Checks the code block matches a listing of the files under `path`.\n\n`include` and `exclude` are glob patterns, and `details` adds each file's `size` or `mode`.",
+        "mock_command" => "Stubs the command `name` on `PATH`, writing the code block when it is called.\n\n`args` is a shell pattern the arguments must match, `stream` is `stdout` or `stderr` and `exit_code` is the code it exits with. Every call is recorded for `verify(mock_command=...)`.",
+        "skip" => "Skips the code block.",
+        _ => "",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use super::{completions, context, definition, diagnostics, hover, Context};
+
+    /// Splits `text` at the `|` marking the cursor.
+    fn cursor(text: &str) -> (String, usize) {
+        let offset = text.find('|').expect("text to have a cursor");
+        (text.replacen('|', "", 1), offset)
+    }
+
+    fn context_at(text: &str) -> Option<Context> {
+        let (text, offset) = cursor(text);
+        context(&text, offset)
+    }
+
+    #[test]
+    fn reports_problems_where_they_are() {
+        let text = "```shell,scirpt(name=\"greet\")\n```\n";
+
+        let diagnostics = diagnostics(text);
+
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].message, "Unknown function: scirpt");
+        assert_eq!(
+            (
+                diagnostics[0].range.start.character,
+                diagnostics[0].range.end.character
+            ),
+            (9, 15)
+        );
+    }
+
+    #[test]
+    fn completes_function_names_in_an_info_string() {
+        assert_eq!(context_at("```shell,scr|"), Some(Context::FunctionName));
+
+        let (text, offset) = cursor("```shell,|");
+        let labels: Vec<String> = completions(&text, offset)
+            .into_iter()
+            .map(|item| item.label)
+            .collect();
+        assert!(labels.contains(&"verify_tree".to_string()));
+    }
+
+    #[test]
+    fn completes_argument_names_after_an_open_paren_or_comma() {
+        let function = || {
+            Some(Context::ArgumentName {
+                function: "script".to_string(),
+            })
+        };
+
+        assert_eq!(context_at("```shell,script(|"), function());
+        assert_eq!(
+            context_at("```shell,script(name=\"a, b\", exp|"),
+            function()
+        );
+        assert_eq!(
+            context_at("<!-- specdown: script(\n  name=\"x\",\n  |"),
+            function()
+        );
+    }
+
+    #[test]
+    fn does_not_complete_values_or_outside_functions() {
+        assert_eq!(context_at("```shell,script(name=|"), None);
+        assert_eq!(context_at("```shell,script(name=\"a, |"), None);
+        assert_eq!(context_at("```shell|"), None);
+        assert_eq!(context_at("Some text, |"), None);
+        assert_eq!(context_at("<!-- specdown: script() -->\nText, |"), None);
+    }
+
+    #[test]
+    fn describes_the_function_under_the_cursor() {
+        let (text, offset) = cursor("```text,veri|fy(script_name=\"x\")\n```\n");
+
+        let documentation = hover(&text, offset).expect("hover to describe verify");
+
+        assert!(documentation
+            .value
+            .starts_with("```\nverify(script_name, stream, target_os, mock_command)\n```"));
+        assert_eq!(hover(&text, offset + 12), None);
+    }
+
+    #[test]
+    fn goes_from_a_verify_block_to_its_script() {
+        let (text, offset) = cursor(indoc! {r#"
+            # Title
+
+            ```shell,script(name="greet")
+            echo hello
+            ```
+
+            ```text,verify(script_name="gr|eet")
+            hello
+            ```
+        "#});
+
+        assert_eq!(definition(&text, offset), Some(3));
+    }
+}
@@ -0,0 +1,353 @@
+//! `specdown lsp`: a language server for spec files, speaking the Language
+//! Server Protocol over stdio.
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crossbeam_channel::Sender;
+use lsp_server::{Connection, ErrorCode, Message, Notification, Request, Response, ResponseError};
+use lsp_types::notification::{
+    DidChangeTextDocument, DidCloseTextDocument, DidOpenTextDocument,
+    Notification as NotificationTrait, PublishDiagnostics, ShowMessage,
+};
+use lsp_types::request::{
+    CodeActionRequest, Completion, ExecuteCommand, GotoDefinition, HoverRequest,
+    Request as RequestTrait,
+};
+use lsp_types::{
+    CodeAction, CodeActionOrCommand, CompletionOptions, ExecuteCommandOptions, Hover,
+    HoverContents, HoverProviderCapability, Location, MessageType, OneOf, PublishDiagnosticsParams,
+    ServerCapabilities, ShowMessageParams, TextDocumentSyncCapability, TextDocumentSyncKind, Url,
+};
+
+use crate::exit_codes::ExitCode;
+
+mod analysis;
+mod text;
+
+/// The command the code action runs the spec file with.
+const RUN_COMMAND: &str = "specdown.run";
+
+type Result<T> = std::result::Result<T, Box<dyn Error + Send + Sync>>;
+
+pub fn execute() {
+    let exit_code = match serve() {
+        Ok(()) => ExitCode::Success,
+        Err(err) => {
+            eprintln!("specdown lsp: {err}");
+            ExitCode::ErrorOccurred
+        }
+    };
+
+    std::process::exit(exit_code as i32)
+}
+
+fn serve() -> Result<()> {
+    let (connection, io_threads) = Connection::stdio();
+    let initialize_params = connection.initialize(serde_json::to_value(capabilities())?)?;
+    Server::new(workspace_root(initialize_params)).run(&connection)?;
+    // The writer thread stops once every sender is dropped.
+    drop(connection);
+    io_threads.join()?;
+    Ok(())
+}
+
+fn capabilities() -> ServerCapabilities {
+    ServerCapabilities {
+        text_document_sync: Some(TextDocumentSyncCapability::Kind(TextDocumentSyncKind::FULL)),
+        completion_provider: Some(CompletionOptions {
+            trigger_characters: Some(vec![",".to_string(), "(".to_string()]),
+            ..CompletionOptions::default()
+        }),
+        hover_provider: Some(HoverProviderCapability::Simple(true)),
+        definition_provider: Some(OneOf::Left(true)),
+        code_action_provider: Some(lsp_types::CodeActionProviderCapability::Simple(true)),
+        execute_command_provider: Some(ExecuteCommandOptions {
+            commands: vec![RUN_COMMAND.to_string()],
+            ..ExecuteCommandOptions::default()
+        }),
+        ..ServerCapabilities::default()
+    }
+}
+
+/// The root of the workspace the editor opened, if it gave one on a `file:`
+/// URL.
+// Older clients only send the deprecated `root_uri`.
+#[allow(deprecated)]
+fn workspace_root(initialize_params: serde_json::Value) -> Option<PathBuf> {
+    let params: lsp_types::InitializeParams = serde_json::from_value(initialize_params).ok()?;
+    let uri = match params.workspace_folders {
+        Some(folders) if !folders.is_empty() => folders.into_iter().next().map(|folder| folder.uri),
+        _ => params.root_uri,
+    }?;
+    uri.to_file_path().ok()
+}
+
+/// The open spec files, kept in sync with the editor.
+struct Server {
+    documents: HashMap<Url, String>,
+    /// The directory spec files are run from, when the editor gave one.
+    root: Option<PathBuf>,
+}
+
+impl Server {
+    fn new(root: Option<PathBuf>) -> Self {
+        Self {
+            documents: HashMap::new(),
+            root,
+        }
+    }
+
+    fn run(&mut self, connection: &Connection) -> Result<()> {
+        for message in &connection.receiver {
+            match message {
+                Message::Request(request) => {
+                    if connection.handle_shutdown(&request)? {
+                        return Ok(());
+                    }
+                    let response = self.respond(request, &connection.sender);
+                    connection.sender.send(Message::Response(response))?;
+                }
+                Message::Notification(notification) => {
+                    self.notify(notification, &connection.sender)?;
+                }
+                Message::Response(_) => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Answers `request`, with an error response if its method is unknown
+    /// or its params can't be read.
+    fn respond(&self, request: Request, sender: &Sender<Message>) -> Response {
+        let id = request.id.clone();
+        match self.result(request, sender) {
+            Ok(result) => Response::new_ok(id, result.unwrap_or(serde_json::Value::Null)),
+            Err(error) => Response {
+                id,
+                result: None,
+                error: Some(error),
+            },
+        }
+    }
+
+    fn result(
+        &self,
+        request: Request,
+        sender: &Sender<Message>,
+    ) -> std::result::Result<Option<serde_json::Value>, ResponseError> {
+        match request.method.as_str() {
+            Completion::METHOD => {
+                let params = request_params::<Completion>(request)?;
+                let position = params.text_document_position;
+                self.at(&position, |text, offset| {
+                    Some(analysis::completions(text, offset))
+                })
+                .map(lsp_types::CompletionResponse::Array)
+                .map(to_value)
+                .transpose()
+            }
+            HoverRequest::METHOD => {
+                let params = request_params::<HoverRequest>(request)?;
+                self.at(&params.text_document_position_params, analysis::hover)
+                    .map(|contents| Hover {
+                        contents: HoverContents::Markup(contents),
+                        range: None,
+                    })
+                    .map(to_value)
+                    .transpose()
+            }
+            GotoDefinition::METHOD => {
+                let params = request_params::<GotoDefinition>(request)?;
+                let position = params.text_document_position_params;
+                let uri = position.text_document.uri.clone();
+                self.at(&position, |text, offset| {
+                    analysis::definition(text, offset).map(|line| Location {
+                        uri: uri.clone(),
+                        range: text::line_range(text, line),
+                    })
+                })
+                .map(lsp_types::GotoDefinitionResponse::Scalar)
+                .map(to_value)
+                .transpose()
+            }
+            CodeActionRequest::METHOD => {
+                let params = request_params::<CodeActionRequest>(request)?;
+                to_value(vec![run_action(&params.text_document.uri)]).map(Some)
+            }
+            ExecuteCommand::METHOD => {
+                let params = request_params::<ExecuteCommand>(request)?;
+                if params.command == RUN_COMMAND {
+                    if let Some(uri) = params.arguments.first().and_then(|uri| uri.as_str()) {
+                        let uri = Url::parse(uri)
+                            .map_err(|err| response_error(ErrorCode::InvalidParams, &err))?;
+                        run_spec(uri, self.root.clone(), sender.clone());
+                    }
+                }
+                Ok(None)
+            }
+            method => Err(response_error(
+                ErrorCode::MethodNotFound,
+                &format!("Unknown method: {method}"),
+            )),
+        }
+    }
+
+    /// Calls `f` with the text of the document at `position`, and the byte
+    /// offset of the position in it.
+    fn at<T>(
+        &self,
+        position: &lsp_types::TextDocumentPositionParams,
+        f: impl FnOnce(&str, usize) -> Option<T>,
+    ) -> Option<T> {
+        let text = self.documents.get(&position.text_document.uri)?;
+        f(text, text::offset(text, position.position))
+    }
+
+    /// Handles `notification`. One whose params can't be read is logged and
+    /// ignored, as there's no response to report it in.
+    fn notify(&mut self, notification: Notification, sender: &Sender<Message>) -> Result<()> {
+        match notification.method.as_str() {
+            DidOpenTextDocument::METHOD => {
+                let Some(params) = notification_params::<DidOpenTextDocument>(notification) else {
+                    return Ok(());
+                };
+                let document = params.text_document;
+                self.update(document.uri, document.text, sender)
+            }
+            DidChangeTextDocument::METHOD => {
+                let Some(params) = notification_params::<DidChangeTextDocument>(notification)
+                else {
+                    return Ok(());
+                };
+                // Documents are synced in full, so the last change is the
+                // whole text.
+                match params.content_changes.into_iter().last() {
+                    Some(change) => self.update(params.text_document.uri, change.text, sender),
+                    None => Ok(()),
+                }
+            }
+            DidCloseTextDocument::METHOD => {
+                let Some(params) = notification_params::<DidCloseTextDocument>(notification) else {
+                    return Ok(());
+                };
+                self.documents.remove(&params.text_document.uri);
+                publish_diagnostics(params.text_document.uri, vec![], sender)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn update(&mut self, uri: Url, text: String, sender: &Sender<Message>) -> Result<()> {
+        let diagnostics = analysis::diagnostics(&text);
+        self.documents.insert(uri.clone(), text);
+        publish_diagnostics(uri, diagnostics, sender)
+    }
+}
+
+/// The params of a request for `R`, or an `InvalidParams` error.
+fn request_params<R: RequestTrait>(
+    request: Request,
+) -> std::result::Result<R::Params, ResponseError> {
+    serde_json::from_value(request.params)
+        .map_err(|err| response_error(ErrorCode::InvalidParams, &err))
+}
+
+/// The params of a notification for `N`, or `None` after logging why they
+/// can't be read.
+fn notification_params<N: NotificationTrait>(notification: Notification) -> Option<N::Params> {
+    serde_json::from_value(notification.params)
+        .map_err(|err| eprintln!("specdown lsp: ignoring invalid {}: {err}", N::METHOD))
+        .ok()
+}
+
+fn to_value(value: impl serde::Serialize) -> std::result::Result<serde_json::Value, ResponseError> {
+    serde_json::to_value(value).map_err(|err| response_error(ErrorCode::InternalError, &err))
+}
+
+fn response_error(code: ErrorCode, message: &dyn std::fmt::Display) -> ResponseError {
+    ResponseError {
+        code: code as i32,
+        message: message.to_string(),
+        data: None,
+    }
+}
+
+fn publish_diagnostics(
+    uri: Url,
+    diagnostics: Vec<lsp_types::Diagnostic>,
+    sender: &Sender<Message>,
+) -> Result<()> {
+    let params = PublishDiagnosticsParams {
+        uri,
+        diagnostics,
+        version: None,
+    };
+    sender.send(Message::Notification(Notification::new(
+        PublishDiagnostics::METHOD.to_string(),
+        params,
+    )))?;
+    Ok(())
+}
+
+fn run_action(uri: &Url) -> CodeActionOrCommand {
+    let title = "Run this spec with specdown".to_string();
+    CodeActionOrCommand::CodeAction(CodeAction {
+        title: title.clone(),
+        command: Some(lsp_types::Command {
+            title,
+            command: RUN_COMMAND.to_string(),
+            arguments: Some(vec![serde_json::Value::String(uri.to_string())]),
+        }),
+        ..CodeAction::default()
+    })
+}
+
+/// Runs the spec file at `uri` with `specdown run` in the background, from
+/// the workspace `root` or else the spec file's directory, so it finds the
+/// same `specdown.toml` as when run from a terminal. The summary line of its
+/// output is reported to the editor when it finishes.
+fn run_spec(uri: Url, root: Option<PathBuf>, sender: Sender<Message>) {
+    std::thread::spawn(move || {
+        let message = match (uri.to_file_path(), std::env::current_exe()) {
+            (Ok(path), Ok(specdown)) => Command::new(specdown)
+                .current_dir(
+                    root.as_deref()
+                        .or_else(|| path.parent())
+                        .unwrap_or_else(|| Path::new(".")),
+                )
+                .arg("--no-colour")
+                .arg("run")
+                .arg(&path)
+                .output()
+                .map_or_else(
+                    |err| (MessageType::ERROR, format!("Failed to run specdown: {err}")),
+                    |output| {
+                        let stdout = String::from_utf8_lossy(&output.stdout);
+                        let summary = stdout
+                            .lines()
+                            .rev()
+                            .find(|line| !line.trim().is_empty())
+                            .unwrap_or_default()
+                            .trim()
+                            .to_string();
+                        let message_type = if output.status.success() {
+                            MessageType::INFO
+                        } else {
+                            MessageType::ERROR
+                        };
+                        (message_type, format!("{}: {summary}", path.display()))
+                    },
+                ),
+            _ => (MessageType::ERROR, format!("Can't run {uri}")),
+        };
+
+        let (typ, message) = message;
+        let _ = sender.send(Message::Notification(Notification::new(
+            ShowMessage::METHOD.to_string(),
+            ShowMessageParams { typ, message },
+        )));
+    });
+}
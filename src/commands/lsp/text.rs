@@ -0,0 +1,102 @@
+use std::convert::TryFrom;
+use std::ops::Range;
+
+use lsp_types::Position;
+
+/// Converts `position`, whose character is counted in UTF-16 code units as
+/// LSP clients count them, into a byte offset in `text`. A position past the
+/// end of a line is the end of that line.
+pub fn offset(text: &str, position: Position) -> usize {
+    let line_start: usize = text
+        .split_inclusive('\n')
+        .take(position.line as usize)
+        .map(str::len)
+        .sum();
+    let line = text[line_start..].split('\n').next().unwrap_or_default();
+
+    let mut units = 0;
+    for (index, c) in line.char_indices() {
+        if units >= position.character as usize {
+            return line_start + index;
+        }
+        units += c.len_utf16();
+    }
+    line_start + line.len()
+}
+
+/// Converts a byte offset in `text` into an LSP position.
+pub fn position(text: &str, offset: usize) -> Position {
+    let before = &text[..offset];
+    let line_start = before.rfind('\n').map_or(0, |newline| newline + 1);
+    Position {
+        line: to_u32(before.matches('\n').count()),
+        character: to_u32(before[line_start..].encode_utf16().count()),
+    }
+}
+
+/// Converts a byte range in `text` into an LSP range.
+pub fn range(text: &str, range: Range<usize>) -> lsp_types::Range {
+    lsp_types::Range {
+        start: position(text, range.start),
+        end: position(text, range.end),
+    }
+}
+
+/// The range of the whole of `line`, counting from 1, without its newline.
+pub fn line_range(text: &str, line: usize) -> lsp_types::Range {
+    let start: usize = text
+        .split_inclusive('\n')
+        .take(line.saturating_sub(1))
+        .map(str::len)
+        .sum();
+    let length = text[start..]
+        .split('\n')
+        .next()
+        .unwrap_or_default()
+        .trim_end_matches('\r')
+        .len();
+    range(text, start..start + length)
+}
+
+fn to_u32(value: usize) -> u32 {
+    u32::try_from(value).unwrap_or(u32::MAX)
+}
+
+#[cfg(test)]
+mod tests {
+    use lsp_types::Position;
+
+    use super::{line_range, offset, position};
+
+    fn at(line: u32, character: u32) -> Position {
+        Position { line, character }
+    }
+
+    #[test]
+    fn converts_positions_to_offsets_and_back() {
+        let text = "first\nsecond\n";
+
+        assert_eq!(offset(text, at(1, 3)), 9);
+        assert_eq!(position(text, 9), at(1, 3));
+    }
+
+    #[test]
+    fn counts_characters_in_utf16_code_units() {
+        let text = "✓ 😀 x";
+
+        assert_eq!(offset(text, at(0, 5)), "✓ 😀 ".len());
+        assert_eq!(position(text, "✓ 😀 ".len()), at(0, 5));
+    }
+
+    #[test]
+    fn a_position_past_the_end_of_a_line_is_its_end() {
+        assert_eq!(offset("ab\ncd", at(0, 10)), 2);
+    }
+
+    #[test]
+    fn a_line_range_covers_the_line_without_its_newline() {
+        let range = line_range("one\r\ntwo\n", 2);
+
+        assert_eq!((range.start, range.end), (at(1, 0), at(1, 3)));
+    }
+}
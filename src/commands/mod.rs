@@ -1,3 +1,5 @@
+pub mod check;
 pub mod completion;
+pub mod lsp;
 pub mod run;
 pub mod strip;
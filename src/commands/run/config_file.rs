@@ -1,12 +1,13 @@
+use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
 
+use merge::Merge;
 use serde::Deserialize;
 
 use super::settings::RunSettings;
 use crate::runner::Error;
 
-/// The default name of the config file looked up in the current directory
-/// when `--config` is not given.
+/// The name of the config file looked for when `--config` is not given.
 const DEFAULT_FILE_NAME: &str = "specdown.toml";
 
 #[derive(Deserialize, Default)]
@@ -14,54 +15,338 @@ const DEFAULT_FILE_NAME: &str = "specdown.toml";
 struct ConfigFile {
     #[serde(default)]
     run: RunSettings,
+    /// Named `[profile.<name>]` tables, each overlaying `[run]` when
+    /// selected with `--profile` or `SPECDOWN_PROFILE`.
+    #[serde(default)]
+    profile: BTreeMap<String, RunSettings>,
 }
 
-/// Loads the `[run]` settings from a `specdown.toml` file.
+impl ConfigFile {
+    /// The `[run]` settings, overlaid with those of `profile` if one is
+    /// selected.
+    fn into_run_settings(mut self, profile: Option<&str>) -> Result<RunSettings, String> {
+        let Some(name) = profile else {
+            return Ok(self.run);
+        };
+
+        let Some(mut settings) = self.profile.remove(name) else {
+            let names: Vec<String> = self
+                .profile
+                .keys()
+                .map(|name| format!("`{name}`"))
+                .collect();
+            return Err(if names.is_empty() {
+                format!("no profile named `{name}`, as there is no [profile.{name}] table")
+            } else {
+                format!(
+                    "no profile named `{name}`, expected one of {}",
+                    names.join(", ")
+                )
+            });
+        };
+        settings.merge(self.run);
+        Ok(settings)
+    }
+}
+
+/// Loads the `[run]` settings from a `specdown.toml` file, overlaid with the
+/// `[profile.<name>]` table named by `profile`.
 ///
 /// If `explicit_path` is given (from `--config`), that file is loaded and it
-/// is an error if it doesn't exist or fails to parse. Otherwise `cwd` is
-/// searched for a `specdown.toml`; if it isn't there, the defaults
-/// (`RunSettings::default()`) are returned rather than an error.
-pub fn load_run_settings(explicit_path: Option<&Path>, cwd: &Path) -> Result<RunSettings, Error> {
-    let (path, is_explicit): (PathBuf, bool) = match explicit_path {
-        Some(p) => (p.to_path_buf(), true),
-        None => (cwd.join(DEFAULT_FILE_NAME), false),
+/// is an error if it doesn't exist or fails to parse. Otherwise the top-most
+/// `specdown.toml` is used, looking in `cwd` and each directory above it up
+/// to the root of the repository `cwd` is in (see [`discover`]). If there
+/// isn't one, the defaults (`RunSettings::default()`) are returned rather
+/// than an error, unless a profile was asked for. Any files below it apply
+/// only to the specs in their directories (see [`load_nested_settings`]).
+pub fn load_run_settings(
+    explicit_path: Option<&Path>,
+    profile: Option<&str>,
+    cwd: &Path,
+) -> Result<RunSettings, Error> {
+    let path = match explicit_path {
+        Some(path) => path.to_path_buf(),
+        None => match discover(cwd) {
+            Some(path) => path,
+            None if profile.is_some() => cwd.join(DEFAULT_FILE_NAME),
+            None => return Ok(RunSettings::default()),
+        },
     };
 
-    let contents = match std::fs::read_to_string(&path) {
-        Ok(contents) => contents,
-        Err(err) if err.kind() == std::io::ErrorKind::NotFound && !is_explicit => {
-            return Ok(RunSettings::default());
+    let mut settings = load_file(&path, profile)?;
+    if explicit_path.is_none() {
+        if let Some(dir) = path.parent() {
+            rebase(&mut settings, dir, cwd);
         }
-        Err(err) => {
-            return Err(Error::ConfigFileLoadFailed {
-                path,
-                message: err.to_string(),
-            })
+    }
+    Ok(settings)
+}
+
+/// Finds the top-level `specdown.toml` for `cwd`: the one furthest from it,
+/// looking in `cwd` and each directory above it up to the root of the
+/// repository it's in, the first directory found containing `.git`. Outside
+/// a repository only `cwd` is looked in, so a stray file elsewhere is never
+/// picked up.
+///
+/// Running from a directory with its own nested file still finds the one
+/// above it, so that file's settings only apply as a nested one.
+pub fn discover(cwd: &Path) -> Option<PathBuf> {
+    let dirs: Vec<&Path> = cwd.ancestors().collect();
+    let searched = dirs
+        .iter()
+        .position(|dir| dir.join(".git").exists())
+        .map_or(&dirs[..1], |root| &dirs[..=root]);
+    searched
+        .iter()
+        .rev()
+        .map(|dir| dir.join(DEFAULT_FILE_NAME))
+        .find(|path| path.is_file())
+}
+
+/// The settings a `specdown.toml` below the top-level one may set: those
+/// which change how a spec file's scripts are run.
+const NESTED_SETTINGS: &[&str] = &[
+    "shell_command",
+    "env",
+    "env_files",
+    "unset_env",
+    "add_path",
+    "interpreters",
+    "executor",
+];
+
+/// Loads the settings from the `specdown.toml` files in the directories
+/// between `spec_file` and `root`, nearest first. It is an error for these
+/// files to set anything but the [`NESTED_SETTINGS`]. `root`'s own file isn't
+/// included, and a spec file outside `root` has no nested settings.
+///
+/// A profile missing from one of these files is not an error, as only some
+/// of them may need to change what it does.
+pub fn load_nested_settings(
+    spec_file: &Path,
+    root: &Path,
+    profile: Option<&str>,
+) -> Result<Vec<RunSettings>, Error> {
+    let (Some(dir), Ok(root)) = (
+        spec_file
+            .canonicalize()
+            .ok()
+            .and_then(|path| path.parent().map(Path::to_path_buf)),
+        root.canonicalize(),
+    ) else {
+        return Ok(vec![]);
+    };
+
+    dir.ancestors()
+        .take_while(|dir| *dir != root && dir.starts_with(&root))
+        .filter(|dir| dir.join(DEFAULT_FILE_NAME).is_file())
+        .map(|dir| {
+            let path = dir.join(DEFAULT_FILE_NAME);
+            read_nested(&path)
+                .and_then(|config| {
+                    let profile = profile.filter(|name| config.profile.contains_key(*name));
+                    config.into_run_settings(profile)
+                })
+                .map(|settings| RunSettings {
+                    shell_command: settings.shell_command,
+                    env: settings.env,
+                    env_files: settings
+                        .env_files
+                        .iter()
+                        .map(|env_file| dir.join(env_file))
+                        .collect(),
+                    unset_env: settings.unset_env,
+                    add_path: absolute_paths(&settings.add_path, dir),
+                    interpreters: settings.interpreters,
+                    executor_config: settings.executor_config,
+                    ..RunSettings::default()
+                })
+                .map_err(|message| Error::ConfigFileLoadFailed { path, message })
+        })
+        .collect()
+}
+
+fn load_file(path: &Path, profile: Option<&str>) -> Result<RunSettings, Error> {
+    read(path)
+        .and_then(|config| config.into_run_settings(profile))
+        .map_err(|message| Error::ConfigFileLoadFailed {
+            path: path.to_path_buf(),
+            message,
+        })
+}
+
+fn read(path: &Path) -> Result<ConfigFile, String> {
+    let contents = std::fs::read_to_string(path).map_err(|err| err.to_string())?;
+    parse(&contents)
+}
+
+/// Reads a `specdown.toml` below the top-level one, which may only set the
+/// [`NESTED_SETTINGS`], in `[run]` or a profile.
+fn read_nested(path: &Path) -> Result<ConfigFile, String> {
+    let contents = std::fs::read_to_string(path).map_err(|err| err.to_string())?;
+    let table = contents
+        .parse::<toml::Table>()
+        .map_err(|err| err.to_string())?;
+    let run = table
+        .get("run")
+        .into_iter()
+        .map(|run| ("run".to_string(), run));
+    let profiles = table
+        .get("profile")
+        .and_then(toml::Value::as_table)
+        .into_iter()
+        .flatten()
+        .map(|(name, settings)| (format!("profile.{name}"), settings));
+    for (table_name, settings) in run.chain(profiles) {
+        let unsupported = settings
+            .as_table()
+            .into_iter()
+            .flat_map(toml::Table::keys)
+            .find(|key| !NESTED_SETTINGS.contains(&key.as_str()));
+        if let Some(key) = unsupported {
+            return Err(format!(
+                "`{key}` in [{table_name}] can only be set in the top-level specdown.toml, \
+                 as a nested one may only set {}",
+                NESTED_SETTINGS
+                    .iter()
+                    .map(|name| format!("`{name}`"))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ));
         }
+    }
+    parse(&contents)
+}
+
+fn parse(contents: &str) -> Result<ConfigFile, String> {
+    let mut config = toml::from_str::<ConfigFile>(contents).map_err(|err| err.to_string())?;
+    let var = |name: &str| std::env::var(name).ok();
+    interpolate_settings(&mut config.run, &var)?;
+    for settings in config.profile.values_mut() {
+        interpolate_settings(settings, &var)?;
+    }
+    Ok(config)
+}
+
+/// Interpolates environment variables into the settings naming paths, images
+/// and environment variables, so that a `specdown.toml` needn't hard-code
+/// what differs between machines. Commands aren't, as the shell running them
+/// expands variables itself.
+fn interpolate_settings(
+    settings: &mut RunSettings,
+    var: &dyn Fn(&str) -> Option<String>,
+) -> Result<(), String> {
+    let path = |path: &mut PathBuf| -> Result<(), String> {
+        *path = PathBuf::from(interpolate(&path.to_string_lossy(), var)?);
+        Ok(())
+    };
+    let string = |string: &mut String| -> Result<(), String> {
+        *string = interpolate(string, var)?;
+        Ok(())
+    };
+
+    settings.spec_files.iter_mut().try_for_each(path)?;
+    settings.exclude.iter_mut().try_for_each(string)?;
+    settings.workspace_dir.iter_mut().try_for_each(path)?;
+    settings.working_dir.iter_mut().try_for_each(path)?;
+    settings.env.iter_mut().try_for_each(string)?;
+    settings.env_files.iter_mut().try_for_each(path)?;
+    settings.add_path.iter_mut().try_for_each(string)?;
+    let executor = &mut settings.executor_config;
+    executor.container_image.iter_mut().try_for_each(string)?;
+    executor.container_volumes.iter_mut().try_for_each(string)
+}
+
+/// Replaces each `${VAR}` in `value` with the value of the environment
+/// variable `VAR`, and each `${VAR:-default}` with `default` if `VAR` is
+/// unset or empty, as a shell would. It is an error for `VAR` to be unset
+/// without a default, as the value would most likely be wrong.
+fn interpolate(value: &str, var: &dyn Fn(&str) -> Option<String>) -> Result<String, String> {
+    let mut interpolated = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(start) = rest.find("${") {
+        let Some(end) = rest[start..].find('}').map(|end| start + end) else {
+            break;
+        };
+        interpolated.push_str(&rest[..start]);
+        let (name, default) = match rest[start + 2..end].split_once(":-") {
+            Some((name, default)) => (name, Some(default)),
+            None => (&rest[start + 2..end], None),
+        };
+        match (var(name).filter(|value| !value.is_empty()), default) {
+            (Some(value), _) => interpolated.push_str(&value),
+            (None, Some(default)) => interpolated.push_str(default),
+            (None, None) if var(name).is_some() => {}
+            (None, None) => {
+                return Err(format!(
+                "environment variable `{name}` is not set, and `{value}` gives no default for it"
+            ))
+            }
+        }
+        rest = &rest[end + 1..];
+    }
+    interpolated.push_str(rest);
+    Ok(interpolated)
+}
+
+/// Makes the relative paths in settings loaded from a `specdown.toml` in
+/// `dir`, an ancestor of `cwd`, relative to `cwd` instead, so they mean the
+/// same whichever directory specdown is run from. `add_path` entries are
+/// made absolute, as scripts look them up from the directory they run in.
+fn rebase(settings: &mut RunSettings, dir: &Path, cwd: &Path) {
+    settings.add_path = absolute_paths(&settings.add_path, dir);
+
+    let Ok(below) = cwd.strip_prefix(dir) else {
+        return;
     };
+    let up: PathBuf = below.components().map(|_| "..").collect();
+    if up.as_os_str().is_empty() {
+        return;
+    }
 
-    toml::from_str::<ConfigFile>(&contents)
-        .map(|config| config.run)
-        .map_err(|err| Error::ConfigFileLoadFailed {
-            path,
-            message: err.to_string(),
+    let rebased = |path: &PathBuf| {
+        if path.is_relative() {
+            up.join(path)
+        } else {
+            path.clone()
+        }
+    };
+    settings.spec_files = settings.spec_files.iter().map(rebased).collect();
+    settings.workspace_dir = settings.workspace_dir.as_ref().map(rebased);
+    settings.env_files = settings.env_files.iter().map(rebased).collect();
+    settings.exclude = settings
+        .exclude
+        .iter()
+        .map(|pattern| {
+            rebased(&PathBuf::from(pattern))
+                .to_string_lossy()
+                .into_owned()
         })
+        .collect();
+}
+
+/// Joins each relative path in `paths` to `dir`.
+fn absolute_paths(paths: &[String], dir: &Path) -> Vec<String> {
+    paths
+        .iter()
+        .map(|path| dir.join(path).to_string_lossy().into_owned())
+        .collect()
 }
 
 #[cfg(test)]
 mod tests {
-    use super::load_run_settings;
+    use super::{discover, interpolate, load_nested_settings, load_run_settings};
     use crate::commands::run::settings::ExecutorKind;
     use crate::runner::Error;
     use std::fs;
+    use std::path::{Path, PathBuf};
 
     #[test]
     fn test_returns_defaults_when_no_file_exists_at_the_default_location() {
         let dir = tempfile::tempdir().expect("failed to create a temporary directory");
 
-        let settings =
-            load_run_settings(None, dir.path()).expect("expected default settings, got an error");
+        let settings = load_run_settings(None, None, dir.path())
+            .expect("expected default settings, got an error");
 
         assert!(settings.spec_files.is_empty());
         assert_eq!(None, settings.shell_command);
@@ -72,7 +357,7 @@ mod tests {
         let dir = tempfile::tempdir().expect("failed to create a temporary directory");
         let missing_path = dir.path().join("does-not-exist.toml");
 
-        let result = load_run_settings(Some(&missing_path), dir.path());
+        let result = load_run_settings(Some(&missing_path), None, dir.path());
 
         assert!(matches!(result, Err(Error::ConfigFileLoadFailed { .. })));
     }
@@ -98,7 +383,7 @@ mod tests {
         .expect("failed to write config file");
 
         let settings =
-            load_run_settings(None, dir.path()).expect("expected settings, got an error");
+            load_run_settings(None, None, dir.path()).expect("expected settings, got an error");
 
         assert_eq!(Some("sh -c".to_string()), settings.shell_command);
         assert_eq!(Some(4), settings.jobs);
@@ -127,11 +412,33 @@ mod tests {
         .expect("failed to write config file");
 
         let settings =
-            load_run_settings(None, dir.path()).expect("expected settings, got an error");
+            load_run_settings(None, None, dir.path()).expect("expected settings, got an error");
 
         assert_eq!(vec!["A=1".to_string(), "B=2".to_string()], settings.env);
     }
 
+    #[test]
+    fn test_loads_interpreters_from_the_interpreters_table() {
+        let dir = tempfile::tempdir().expect("failed to create a temporary directory");
+        fs::write(
+            dir.path().join("specdown.toml"),
+            r#"
+                [run.interpreters]
+                python = "python3 -c"
+                node = "node -e"
+            "#,
+        )
+        .expect("failed to write config file");
+
+        let settings =
+            load_run_settings(None, None, dir.path()).expect("expected settings, got an error");
+
+        assert_eq!(
+            vec!["node=node -e".to_string(), "python=python3 -c".to_string()],
+            settings.interpreters
+        );
+    }
+
     #[test]
     fn test_loads_settings_from_an_explicit_path() {
         let dir = tempfile::tempdir().expect("failed to create a temporary directory");
@@ -145,7 +452,7 @@ mod tests {
         )
         .expect("failed to write config file");
 
-        let settings = load_run_settings(Some(&config_path), dir.path())
+        let settings = load_run_settings(Some(&config_path), None, dir.path())
             .expect("expected settings, got an error");
 
         assert_eq!(Some("zsh -c".to_string()), settings.shell_command);
@@ -163,7 +470,7 @@ mod tests {
         )
         .expect("failed to write config file");
 
-        let result = load_run_settings(None, dir.path());
+        let result = load_run_settings(None, None, dir.path());
 
         assert!(matches!(result, Err(Error::ConfigFileLoadFailed { .. })));
     }
@@ -174,8 +481,303 @@ mod tests {
         fs::write(dir.path().join("specdown.toml"), "this is not valid toml")
             .expect("failed to write config file");
 
-        let result = load_run_settings(None, dir.path());
+        let result = load_run_settings(None, None, dir.path());
 
         assert!(matches!(result, Err(Error::ConfigFileLoadFailed { .. })));
     }
+
+    #[test]
+    fn test_overlays_the_run_table_with_the_selected_profile() {
+        let dir = tempfile::tempdir().expect("failed to create a temporary directory");
+        fs::write(
+            dir.path().join("specdown.toml"),
+            r#"
+                [run]
+                shell_command = "sh -c"
+                jobs = 2
+
+                [profile.ci]
+                jobs = 8
+
+                [profile.ci.env]
+                CI = "true"
+            "#,
+        )
+        .expect("failed to write config file");
+
+        let settings = load_run_settings(None, Some("ci"), dir.path())
+            .expect("expected settings, got an error");
+
+        assert_eq!(Some("sh -c".to_string()), settings.shell_command);
+        assert_eq!(Some(8), settings.jobs);
+        assert_eq!(vec!["CI=true".to_string()], settings.env);
+    }
+
+    #[test]
+    fn test_ignores_profiles_which_are_not_selected() {
+        let dir = tempfile::tempdir().expect("failed to create a temporary directory");
+        fs::write(
+            dir.path().join("specdown.toml"),
+            r"
+                [run]
+                jobs = 2
+
+                [profile.ci]
+                jobs = 8
+            ",
+        )
+        .expect("failed to write config file");
+
+        let settings =
+            load_run_settings(None, None, dir.path()).expect("expected settings, got an error");
+
+        assert_eq!(Some(2), settings.jobs);
+    }
+
+    #[test]
+    fn test_errors_when_the_selected_profile_does_not_exist() {
+        let dir = tempfile::tempdir().expect("failed to create a temporary directory");
+        fs::write(
+            dir.path().join("specdown.toml"),
+            r#"
+                [profile.ci]
+                jobs = 8
+
+                [profile.container.executor]
+                executor = "container"
+            "#,
+        )
+        .expect("failed to write config file");
+
+        let result = load_run_settings(None, Some("cj"), dir.path());
+
+        assert!(matches!(
+            result,
+            Err(Error::ConfigFileLoadFailed { message, .. })
+                if message == "no profile named `cj`, expected one of `ci`, `container`"
+        ));
+    }
+
+    #[test]
+    fn test_errors_when_a_profile_is_selected_without_a_config_file() {
+        let dir = tempfile::tempdir().expect("failed to create a temporary directory");
+
+        let result = load_run_settings(None, Some("ci"), dir.path());
+
+        assert!(matches!(result, Err(Error::ConfigFileLoadFailed { .. })));
+    }
+
+    #[test]
+    fn test_finds_a_config_file_above_the_current_directory_in_a_repository() {
+        let dir = tempfile::tempdir().expect("failed to create a temporary directory");
+        let cwd = dir.path().join("docs").join("api");
+        fs::create_dir_all(dir.path().join(".git")).expect("failed to create .git");
+        fs::create_dir_all(&cwd).expect("failed to create directories");
+        fs::write(
+            dir.path().join("specdown.toml"),
+            r#"
+                [run]
+                files = ["docs/index.md"]
+                exclude = ["docs/drafts/**"]
+                workspace_dir = "/tmp"
+                shell_command = "sh -c"
+                add_path = ["bin", "/opt/bin"]
+            "#,
+        )
+        .expect("failed to write config file");
+
+        let settings =
+            load_run_settings(None, None, &cwd).expect("expected settings, got an error");
+
+        assert_eq!(Some("sh -c".to_string()), settings.shell_command);
+        assert_eq!(
+            vec![Path::new("..").join("..").join("docs").join("index.md")],
+            settings.spec_files
+        );
+        assert_eq!(
+            vec![Path::new("..")
+                .join("..")
+                .join("docs")
+                .join("drafts")
+                .join("**")
+                .to_string_lossy()
+                .into_owned()],
+            settings.exclude
+        );
+        assert_eq!(Some(PathBuf::from("/tmp")), settings.workspace_dir);
+        assert_eq!(
+            vec![
+                dir.path().join("bin").to_string_lossy().into_owned(),
+                "/opt/bin".to_string()
+            ],
+            settings.add_path
+        );
+    }
+
+    #[test]
+    fn test_uses_the_top_most_config_file_in_a_repository() {
+        let dir = tempfile::tempdir().expect("failed to create a temporary directory");
+        let cwd = dir.path().join("docs");
+        fs::create_dir_all(dir.path().join(".git")).expect("failed to create .git");
+        fs::create_dir_all(&cwd).expect("failed to create directories");
+        fs::write(dir.path().join("specdown.toml"), "[run]\njobs = 2\n")
+            .expect("failed to write config file");
+        fs::write(
+            cwd.join("specdown.toml"),
+            "[run]\nshell_command = \"sh -c\"\n",
+        )
+        .expect("failed to write config file");
+
+        assert_eq!(Some(dir.path().join("specdown.toml")), discover(&cwd));
+    }
+
+    #[test]
+    fn test_does_not_look_above_the_current_directory_outside_a_repository() {
+        let dir = tempfile::tempdir().expect("failed to create a temporary directory");
+        let cwd = dir.path().join("docs");
+        fs::create_dir_all(&cwd).expect("failed to create directories");
+        fs::write(
+            dir.path().join("specdown.toml"),
+            "[run]\nshell_command = \"sh -c\"\n",
+        )
+        .expect("failed to write config file");
+
+        let settings =
+            load_run_settings(None, None, &cwd).expect("expected settings, got an error");
+
+        assert_eq!(None, settings.shell_command);
+    }
+
+    #[test]
+    fn test_loads_nested_config_files_nearest_first() {
+        let dir = tempfile::tempdir().expect("failed to create a temporary directory");
+        let api = dir.path().join("docs").join("api");
+        fs::create_dir_all(&api).expect("failed to create directories");
+        fs::write(
+            dir.path().join("specdown.toml"),
+            "[run]\nshell_command = \"bash -c\"\n",
+        )
+        .expect("failed to write config file");
+        fs::write(
+            dir.path().join("docs").join("specdown.toml"),
+            "[run]\nshell_command = \"sh -c\"\nadd_path = [\"bin\"]\n\n[run.env]\nAREA = \"docs\"\n",
+        )
+        .expect("failed to write config file");
+        fs::write(
+            api.join("specdown.toml"),
+            "[run]\nshell_command = \"zsh -c\"\n",
+        )
+        .expect("failed to write config file");
+        let spec_file = api.join("users.md");
+        fs::write(&spec_file, "# Users\n").expect("failed to write spec file");
+
+        let nested = load_nested_settings(&spec_file, dir.path(), None)
+            .expect("expected settings, got an error");
+
+        let shell_commands: Vec<Option<String>> = nested
+            .iter()
+            .map(|settings| settings.shell_command.clone())
+            .collect();
+        assert_eq!(
+            vec![Some("zsh -c".to_string()), Some("sh -c".to_string())],
+            shell_commands
+        );
+        assert_eq!(vec!["AREA=docs".to_string()], nested[1].env);
+        let docs = dir
+            .path()
+            .canonicalize()
+            .expect("failed to canonicalize")
+            .join("docs");
+        assert_eq!(
+            vec![docs.join("bin").to_string_lossy().into_owned()],
+            nested[1].add_path
+        );
+    }
+
+    #[test]
+    fn test_errors_when_a_nested_config_file_sets_an_unsupported_key() {
+        let dir = tempfile::tempdir().expect("failed to create a temporary directory");
+        let docs = dir.path().join("docs");
+        fs::create_dir_all(&docs).expect("failed to create directories");
+        fs::write(
+            docs.join("specdown.toml"),
+            "[run]\nshell_command = \"sh -c\"\n\n[profile.ci]\njobs = 4\n",
+        )
+        .expect("failed to write config file");
+        let spec_file = docs.join("users.md");
+        fs::write(&spec_file, "# Users\n").expect("failed to write spec file");
+
+        let result = load_nested_settings(&spec_file, dir.path(), None);
+
+        match result {
+            Err(Error::ConfigFileLoadFailed { message, .. }) => assert!(
+                message.starts_with(
+                    "`jobs` in [profile.ci] can only be set in the top-level specdown.toml"
+                ),
+                "unexpected message: {:?}",
+                message
+            ),
+            other => panic!("expected a load failure, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_has_no_nested_settings_for_a_spec_file_outside_the_root() {
+        let dir = tempfile::tempdir().expect("failed to create a temporary directory");
+        let root = dir.path().join("root");
+        fs::create_dir_all(&root).expect("failed to create directories");
+        fs::write(
+            dir.path().join("specdown.toml"),
+            "[run]\nshell_command = \"sh -c\"\n",
+        )
+        .expect("failed to write config file");
+        let spec_file = dir.path().join("outside.md");
+        fs::write(&spec_file, "# Outside\n").expect("failed to write spec file");
+
+        let nested =
+            load_nested_settings(&spec_file, &root, None).expect("expected settings, got an error");
+
+        assert!(nested.is_empty());
+    }
+
+    fn var(name: &str) -> Option<String> {
+        match name {
+            "HOME" => Some("/home/user".to_string()),
+            "EMPTY" => Some(String::new()),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn test_interpolates_environment_variables() {
+        assert_eq!(
+            Ok("/home/user/cache:/cache".to_string()),
+            interpolate("${HOME}/cache:/cache", &var)
+        );
+    }
+
+    #[test]
+    fn test_interpolates_a_default_when_a_variable_is_unset_or_empty() {
+        assert_eq!(
+            Ok("/tmp/a /tmp/b".to_string()),
+            interpolate("${MISSING:-/tmp/a} ${EMPTY:-/tmp/b}", &var)
+        );
+    }
+
+    #[test]
+    fn test_leaves_other_dollar_signs_alone() {
+        assert_eq!(Ok("$HOME ${".to_string()), interpolate("$HOME ${", &var));
+    }
+
+    #[test]
+    fn test_errors_when_a_variable_without_a_default_is_unset() {
+        assert_eq!(
+            Err(
+                "environment variable `MISSING` is not set, and `${MISSING}/x` gives no default for it"
+                    .to_string()
+            ),
+            interpolate("${MISSING}/x", &var)
+        );
+        assert_eq!(Ok("/x".to_string()), interpolate("${EMPTY}/x", &var));
+    }
 }
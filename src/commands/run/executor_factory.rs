@@ -1,5 +1,6 @@
 use std::path::Path;
 
+use super::hermetic;
 use crate::runner::shell_executor::ShellExecutor;
 use crate::runner::{Error, Executor};
 
@@ -26,6 +27,12 @@ pub struct ShellExecutorFactory {
     pub base_env: Vec<(String, String)>,
     pub unset_env: Vec<String>,
     pub paths: Vec<String>,
+    pub interpreters: Vec<(String, String)>,
+    /// Run scripts with a minimal environment (see [`hermetic::env`]),
+    /// with their `HOME`s in these, plus the variables in `pass_env`, rather
+    /// than specdown's own.
+    pub hermetic: Option<hermetic::Homes>,
+    pub pass_env: Vec<String>,
 }
 
 impl ExecutorFactory for ShellExecutorFactory {
@@ -35,10 +42,22 @@ impl ExecutorFactory for ShellExecutorFactory {
         extra_env: &[(String, String)],
         working_dir: &Path,
     ) -> Result<Box<dyn Executor>, Error> {
-        let mut env = self.base_env.clone();
+        let mut env = match &self.hermetic {
+            Some(homes) => hermetic::env(homes, &self.pass_env)?,
+            None => Vec::new(),
+        };
+        env.extend_from_slice(&self.base_env);
         env.extend_from_slice(extra_env);
-        ShellExecutor::new(&self.shell_cmd, &env, &self.unset_env, &self.paths)
-            .map(|e| Box::new(e.with_working_dir(working_dir.to_path_buf())) as Box<dyn Executor>)
+        ShellExecutor::new(&self.shell_cmd, &env, &self.unset_env, &self.paths).map(|e| {
+            let executor = e
+                .with_interpreters(&self.interpreters)
+                .with_working_dir(working_dir.to_path_buf());
+            Box::new(if self.hermetic.is_some() {
+                executor.with_clean_env()
+            } else {
+                executor
+            }) as Box<dyn Executor>
+        })
     }
 }
 
@@ -50,6 +69,7 @@ pub struct ContainerExecutorFactory {
     pub unset_env: Vec<String>,
     pub paths: Vec<String>,
     pub container_volumes: Vec<String>,
+    pub interpreters: Vec<(String, String)>,
 }
 
 #[cfg(feature = "container")]
@@ -71,6 +91,6 @@ impl ExecutorFactory for ContainerExecutorFactory {
             &self.container_volumes,
             label,
         )
-        .map(|e| Box::new(e) as Box<dyn Executor>)
+        .map(|e| Box::new(e.with_interpreters(&self.interpreters)) as Box<dyn Executor>)
     }
 }
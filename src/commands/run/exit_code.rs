@@ -6,7 +6,9 @@ pub fn from_events(events: &[RunEvent]) -> ExitCode {
 
     for event in events {
         match event {
-            RunEvent::SpecFileCompleted { success: false } if exit_code == ExitCode::Success => {
+            RunEvent::SpecFileCompleted { success: false, .. }
+                if exit_code == ExitCode::Success =>
+            {
                 exit_code = ExitCode::TestFailed;
             }
             RunEvent::ErrorOccurred(error) => {
@@ -1,10 +1,142 @@
 use std::collections::HashSet;
 use std::fs;
-use std::path::{Path, PathBuf};
+use std::path::{Component, Path, PathBuf};
+
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use ignore::WalkBuilder;
 
 use crate::parsers;
 use crate::runner::Error;
 
+/// Finds the spec files to run from those given on the command line or in
+/// `specdown.toml`, relative to `base_dir`.
+///
+/// A directory is replaced by every Markdown file under it, and a glob
+/// pattern such as `docs/**/*.md` by every file it matches, both in path
+/// order and skipping those matching an `exclude` pattern. Files ignored by
+/// a `.gitignore`, and hidden files, are never found. Any other path is
+/// taken to be a spec file and kept as it is, even if it is excluded, as it
+/// was asked for by name.
+///
+/// It is an error for a directory or pattern to find no spec files, as it
+/// is most likely a mistake.
+pub fn find_spec_files(
+    patterns: &[PathBuf],
+    exclude: &[String],
+    base_dir: &Path,
+) -> Result<Vec<PathBuf>, Error> {
+    let exclude = glob_set(exclude)?;
+    let mut seen: HashSet<PathBuf> = HashSet::new();
+    let mut spec_files = Vec::new();
+
+    for pattern in patterns {
+        let pattern = without_current_dir(pattern);
+        let found: Vec<PathBuf> = if is_glob(&pattern) {
+            let matcher = glob(&pattern.to_string_lossy())?.compile_matcher();
+            walk(&literal_prefix(&pattern), base_dir)
+                .into_iter()
+                .filter(|path| matcher.is_match(path))
+                .collect()
+        } else if base_dir.join(&pattern).is_dir() {
+            walk(&pattern, base_dir)
+                .into_iter()
+                .filter(|path| is_markdown(path))
+                .collect()
+        } else {
+            if seen.insert(pattern.clone()) {
+                spec_files.push(pattern);
+            }
+            continue;
+        };
+
+        let found: Vec<PathBuf> = found
+            .into_iter()
+            .filter(|path| !exclude.is_match(path))
+            .collect();
+        if found.is_empty() {
+            return Err(Error::NoSpecFilesFound {
+                pattern: pattern.display().to_string(),
+            });
+        }
+        spec_files.extend(found.into_iter().filter(|path| seen.insert(path.clone())));
+    }
+
+    Ok(spec_files)
+}
+
+fn is_glob(pattern: &Path) -> bool {
+    pattern.to_string_lossy().contains(['*', '?', '[', '{'])
+}
+
+fn is_markdown(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("md"))
+}
+
+/// `path` without any leading `./`, so `./docs` and `docs` find the same
+/// files.
+fn without_current_dir(path: &Path) -> PathBuf {
+    path.components()
+        .skip_while(|component| *component == Component::CurDir)
+        .collect()
+}
+
+/// The directories at the start of `pattern` before any glob syntax, which
+/// are all that need searching for files it could match.
+fn literal_prefix(pattern: &Path) -> PathBuf {
+    pattern
+        .components()
+        .take_while(|component| !is_glob(Path::new(component.as_os_str())))
+        .collect()
+}
+
+/// Every file under `dir`, relative to `base_dir`, in path order, leaving
+/// out hidden files and those ignored by a `.gitignore`.
+fn walk(dir: &Path, base_dir: &Path) -> Vec<PathBuf> {
+    let root = base_dir.join(dir);
+    let mut paths: Vec<PathBuf> = WalkBuilder::new(&root)
+        .require_git(false)
+        .build()
+        .filter_map(Result::ok)
+        .filter(|entry| {
+            entry
+                .file_type()
+                .is_some_and(|file_type| file_type.is_file())
+        })
+        .filter_map(|entry| {
+            entry
+                .path()
+                .strip_prefix(&root)
+                .ok()
+                .map(|relative| dir.join(relative))
+        })
+        .collect();
+    paths.sort();
+    paths
+}
+
+/// A glob where `*` does not cross `/`; `**` matches across directories.
+fn glob(pattern: &str) -> Result<globset::Glob, Error> {
+    GlobBuilder::new(pattern)
+        .literal_separator(true)
+        .build()
+        .map_err(|err| Error::InvalidGlob {
+            pattern: pattern.to_string(),
+            message: err.kind().to_string(),
+        })
+}
+
+fn glob_set(patterns: &[String]) -> Result<GlobSet, Error> {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        builder.add(glob(pattern)?);
+    }
+    builder.build().map_err(|err| Error::InvalidGlob {
+        pattern: patterns.join(", "),
+        message: err.to_string(),
+    })
+}
+
 /// Builds the complete, deduplicated list of spec files to run.
 ///
 /// When `follow_links` is `false`, returns `initial_files` unchanged (no
@@ -74,7 +206,11 @@ fn visit(
 
     let parent_dir = canonical.parent().unwrap_or(dir).to_path_buf();
 
-    for link in parsers::find_links(&contents)? {
+    let links = parsers::find_links(&contents).map_err(|error| Error::RunFailed {
+        path: file.display().to_string(),
+        error: Box::new(error),
+    })?;
+    for link in links {
         if let Some(link_path) = local_markdown_link(&link) {
             visit(&link_path, &parent_dir, canonical_base, visited, ordered)?;
         }
@@ -106,7 +242,8 @@ fn local_markdown_link(raw: &str) -> Option<PathBuf> {
 
 #[cfg(test)]
 mod tests {
-    use super::build_file_list;
+    use super::{build_file_list, find_spec_files};
+    use crate::runner::Error;
     use std::fs;
     use std::path::PathBuf;
 
@@ -236,4 +373,117 @@ mod tests {
             ]
         );
     }
+
+    fn paths(names: &[&str]) -> Vec<PathBuf> {
+        names.iter().map(PathBuf::from).collect()
+    }
+
+    fn docs_tree() -> tempfile::TempDir {
+        let dir = tempfile::tempdir().expect("failed to create temp dir");
+        for sub in ["docs/api", "docs/drafts"].iter().copied() {
+            fs::create_dir_all(dir.path().join(sub)).expect("failed to create fixture dir");
+        }
+        write(dir.path(), "docs/index.md", "");
+        write(dir.path(), "docs/notes.txt", "");
+        write(dir.path(), "docs/api/users.md", "");
+        write(dir.path(), "docs/drafts/plan.md", "");
+        dir
+    }
+
+    #[test]
+    fn keeps_files_named_explicitly() {
+        let dir = docs_tree();
+        let files = paths(&["docs/index.md", "missing.md"]);
+
+        let result =
+            find_spec_files(&files, &["**".to_string()], dir.path()).expect("should succeed");
+
+        assert_eq!(result, files);
+    }
+
+    #[test]
+    fn expands_a_directory_into_its_markdown_files_recursively() {
+        let dir = docs_tree();
+
+        let result = find_spec_files(&paths(&["./docs"]), &[], dir.path()).expect("should succeed");
+
+        assert_eq!(
+            result,
+            paths(&["docs/api/users.md", "docs/drafts/plan.md", "docs/index.md"])
+        );
+    }
+
+    #[test]
+    fn expands_a_glob_pattern() {
+        let dir = docs_tree();
+
+        let result = find_spec_files(&paths(&["docs/*.md", "docs/**/users.md"]), &[], dir.path())
+            .expect("should succeed");
+
+        assert_eq!(result, paths(&["docs/index.md", "docs/api/users.md"]));
+    }
+
+    #[test]
+    fn leaves_out_excluded_and_duplicate_files() {
+        let dir = docs_tree();
+
+        let result = find_spec_files(
+            &paths(&["docs/api", "docs/**/*.md"]),
+            &["docs/drafts/**".to_string()],
+            dir.path(),
+        )
+        .expect("should succeed");
+
+        assert_eq!(result, paths(&["docs/api/users.md", "docs/index.md"]));
+    }
+
+    #[test]
+    fn finds_a_file_named_explicitly_and_matched_by_a_pattern_once() {
+        let dir = docs_tree();
+
+        let result = find_spec_files(
+            &paths(&["docs/index.md", "docs/*.md", "./docs/index.md"]),
+            &[],
+            dir.path(),
+        )
+        .expect("should succeed");
+        assert_eq!(result, paths(&["docs/index.md"]));
+
+        let result = find_spec_files(&paths(&["docs/*.md", "docs/index.md"]), &[], dir.path())
+            .expect("should succeed");
+        assert_eq!(result, paths(&["docs/index.md"]));
+    }
+
+    #[test]
+    fn leaves_out_files_ignored_by_gitignore() {
+        let dir = docs_tree();
+        write(dir.path(), ".gitignore", "drafts/\n");
+
+        let result = find_spec_files(&paths(&["docs"]), &[], dir.path()).expect("should succeed");
+
+        assert_eq!(result, paths(&["docs/api/users.md", "docs/index.md"]));
+    }
+
+    #[test]
+    fn errors_when_a_pattern_finds_no_spec_files() {
+        let dir = docs_tree();
+
+        let result = find_spec_files(&paths(&["docs/**/*.rs"]), &[], dir.path());
+
+        assert!(matches!(
+            result,
+            Err(Error::NoSpecFilesFound { pattern }) if pattern == "docs/**/*.rs"
+        ));
+    }
+
+    #[test]
+    fn errors_on_an_invalid_glob_pattern() {
+        let dir = docs_tree();
+
+        let result = find_spec_files(&paths(&["docs/[a.md"]), &[], dir.path());
+
+        assert!(
+            matches!(result, Err(Error::InvalidGlob { pattern, .. }) if pattern == "docs/[a.md")
+        );
+    }
 }
@@ -1,6 +1,8 @@
 use std::fs;
 use std::path::{Path, PathBuf};
 
+use crate::runner::Error;
+
 pub struct FileReader {
     directory: PathBuf,
 }
@@ -10,8 +12,11 @@ impl FileReader {
         Self { directory }
     }
 
-    pub fn read_file(&self, spec_file: &Path) -> String {
-        fs::read_to_string(self.to_absolute(spec_file)).expect("failed to read spec file")
+    pub fn read_file(&self, spec_file: &Path) -> Result<String, Error> {
+        fs::read_to_string(self.to_absolute(spec_file)).map_err(|err| Error::SpecFileUnreadable {
+            path: spec_file.display().to_string(),
+            message: err.to_string(),
+        })
     }
 
     fn to_absolute(&self, path: &Path) -> PathBuf {
@@ -29,6 +34,7 @@ mod tests {
 
     mod to_absolute {
         use super::FileReader;
+        use crate::runner::Error;
         use std::fs::File;
         use std::io::Write;
         use std::path::Path;
@@ -43,7 +49,7 @@ mod tests {
 
             let reader = FileReader::new("/home".into());
             let content = reader.read_file(&full_path);
-            assert_eq!("example content", content);
+            assert_eq!(Ok("example content".to_string()), content);
         }
 
         #[test]
@@ -56,7 +62,19 @@ mod tests {
 
             let reader = FileReader::new(directory.path().to_path_buf());
             let content = reader.read_file(Path::new("example.txt"));
-            assert_eq!("example content", content);
+            assert_eq!(Ok("example content".to_string()), content);
+        }
+
+        #[test]
+        fn test_returns_an_error_when_the_file_can_not_be_read() {
+            let directory = tempfile::tempdir().expect("Failed to create a temporary directory");
+
+            let reader = FileReader::new(directory.path().to_path_buf());
+            let result = reader.read_file(Path::new("missing.md"));
+            assert!(matches!(
+                result,
+                Err(Error::SpecFileUnreadable { path, .. }) if path == "missing.md"
+            ));
         }
     }
 }
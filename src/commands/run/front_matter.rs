@@ -0,0 +1,144 @@
+//! Settings for a single spec file, given in front matter at its start.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+use super::settings::{ExecutorConfig, ExecutorKind, RunSettings};
+use crate::parsers::{self, FrontMatterFormat};
+use crate::runner::Error;
+
+/// The settings a spec file can give in its front matter, overriding those
+/// from `specdown.toml` for that file only. Those it shares with `[run]`
+/// mean the same there.
+#[derive(Deserialize, Debug, Default, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct FrontMatter {
+    pub shell_command: Option<String>,
+    pub env: BTreeMap<String, String>,
+    pub executor: Option<ExecutorKind>,
+    pub container_image: Option<String>,
+    pub working_dir: Option<PathBuf>,
+    /// Why the spec file shouldn't be run, if it shouldn't.
+    pub skip: Option<String>,
+    /// Tags for choosing which spec files to run with `--tag`.
+    pub tags: Vec<String>,
+}
+
+impl FrontMatter {
+    /// Reads the front matter `markdown` starts with, or the defaults if it
+    /// has none.
+    pub fn parse(markdown: &str) -> Result<Self, String> {
+        match parsers::front_matter(markdown) {
+            Some((_, text)) if text.trim().is_empty() => Ok(Self::default()),
+            Some((FrontMatterFormat::Yaml, text)) => {
+                serde_yaml_ng::from_str(&text).map_err(|err| err.to_string())
+            }
+            Some((FrontMatterFormat::Toml, text)) => {
+                toml::from_str(&text).map_err(|err| err.to_string())
+            }
+            None => Ok(Self::default()),
+        }
+    }
+
+    /// Reads the front matter of the spec file at `path`. A file which can't
+    /// be read has none, leaving the error to be reported when it's run.
+    pub fn read(path: &Path) -> Result<Self, Error> {
+        let Ok(markdown) = fs::read_to_string(path) else {
+            return Ok(Self::default());
+        };
+        Self::parse(&markdown).map_err(|message| Error::FrontMatterInvalid {
+            path: path.display().to_string(),
+            message,
+        })
+    }
+
+    /// The run settings it overrides, or `None` if it doesn't override any.
+    pub fn run_settings(&self) -> Option<RunSettings> {
+        let settings = RunSettings {
+            shell_command: self.shell_command.clone(),
+            env: self
+                .env
+                .iter()
+                .map(|(name, value)| format!("{name}={value}"))
+                .collect(),
+            executor_config: ExecutorConfig {
+                executor: self.executor,
+                container_image: self.container_image.clone(),
+                container_volumes: vec![],
+            },
+            working_dir: self.working_dir.clone(),
+            ..RunSettings::default()
+        };
+        let overrides = settings.shell_command.is_some()
+            || !settings.env.is_empty()
+            || settings.executor_config != ExecutorConfig::default()
+            || settings.working_dir.is_some();
+        overrides.then_some(settings)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use super::FrontMatter;
+    use crate::commands::run::settings::ExecutorKind;
+
+    #[test]
+    fn reads_yaml_front_matter() {
+        let front_matter = FrontMatter::parse(indoc! {r"
+            ---
+            shell_command: sh -c
+            env:
+              GREETING: Hello
+            executor: container
+            skip: Needs a database
+            tags: [slow, database]
+            ---
+
+            # Title
+        "})
+        .expect("front matter to parse");
+
+        assert_eq!(front_matter.shell_command, Some("sh -c".to_string()));
+        assert_eq!(front_matter.executor, Some(ExecutorKind::Container));
+        assert_eq!(front_matter.skip, Some("Needs a database".to_string()));
+        assert_eq!(front_matter.tags, vec!["slow", "database"]);
+
+        let settings = front_matter
+            .run_settings()
+            .expect("settings to be overridden");
+        assert_eq!(settings.env, vec!["GREETING=Hello"]);
+    }
+
+    #[test]
+    fn reads_toml_front_matter() {
+        let front_matter = FrontMatter::parse(indoc! {r#"
+            +++
+            working_dir = "sub"
+            +++
+            # Title
+        "#})
+        .expect("front matter to parse");
+
+        assert_eq!(front_matter.working_dir, Some("sub".into()));
+    }
+
+    #[test]
+    fn overrides_nothing_without_front_matter() {
+        let front_matter = FrontMatter::parse("# Title\n").expect("front matter to parse");
+
+        assert_eq!(front_matter, FrontMatter::default());
+        assert!(front_matter.run_settings().is_none());
+    }
+
+    #[test]
+    fn rejects_unknown_keys() {
+        let result = FrontMatter::parse("---\nshell_comand: sh -c\n---\n");
+
+        assert!(result.is_err_and(|message| message.contains("unknown field `shell_comand`")));
+    }
+}
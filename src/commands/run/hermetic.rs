@@ -0,0 +1,123 @@
+//! The minimal environment scripts are run with by `--hermetic`, so their
+//! output doesn't depend on the machine running them.
+
+use std::fs;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use tempfile::TempDir;
+
+use crate::runner::Error;
+
+/// The variables set to the same value on every machine.
+const FIXED_ENV: [(&str, &str); 4] = [
+    ("TZ", "UTC"),
+    ("LANG", "C.UTF-8"),
+    ("COLUMNS", "80"),
+    ("NO_COLOR", "1"),
+];
+
+/// A temporary directory holding the `HOME` directories made for scripts,
+/// so they're kept out of the project and the workspace. It's removed, with
+/// them, once the run finishes and it's dropped.
+pub struct Homes {
+    dir: TempDir,
+    created: AtomicUsize,
+}
+
+impl Homes {
+    pub fn create() -> Result<Self, Error> {
+        tempfile::Builder::new()
+            .prefix("specdown-home")
+            .tempdir()
+            .map(|dir| Self {
+                dir,
+                created: AtomicUsize::new(0),
+            })
+            .map_err(|err| Error::HomeUnwritable {
+                path: std::env::temp_dir().display().to_string(),
+                message: err.to_string(),
+            })
+    }
+
+    /// Creates another empty `HOME`.
+    fn next(&self) -> Result<PathBuf, Error> {
+        let home = self
+            .dir
+            .path()
+            .join(self.created.fetch_add(1, Ordering::Relaxed).to_string());
+        fs::create_dir(&home).map_err(|err| Error::HomeUnwritable {
+            path: home.display().to_string(),
+            message: err.to_string(),
+        })?;
+        Ok(home)
+    }
+}
+
+/// Builds the environment for a hermetic executor: a new, empty `HOME` from
+/// `homes`, the fixed variables, and those named in `pass_env` which are set
+/// in specdown's own environment. `PATH` is set by the executor.
+pub fn env(homes: &Homes, pass_env: &[String]) -> Result<Vec<(String, String)>, Error> {
+    let home = homes.next()?;
+
+    let fixed = FIXED_ENV
+        .iter()
+        .map(|(name, value)| ((*name).to_string(), (*value).to_string()));
+    let passed = pass_env
+        .iter()
+        .filter_map(|name| std::env::var(name).ok().map(|value| (name.clone(), value)));
+    Ok(
+        std::iter::once(("HOME".to_string(), home.display().to_string()))
+            .chain(fixed)
+            .chain(passed)
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{env, Homes};
+
+    #[test]
+    fn sets_a_new_home_and_the_fixed_variables() {
+        let homes = Homes::create().expect("failed to create homes");
+
+        let first = env(&homes, &[]).expect("should succeed");
+        let env = env(&homes, &[]).expect("should succeed");
+
+        let home = homes.dir.path().join("1");
+        assert!(home.is_dir());
+        assert_ne!(first[0], env[0], "each executor should get its own HOME");
+        assert_eq!(
+            env,
+            vec![
+                ("HOME".to_string(), home.display().to_string()),
+                ("TZ".to_string(), "UTC".to_string()),
+                ("LANG".to_string(), "C.UTF-8".to_string()),
+                ("COLUMNS".to_string(), "80".to_string()),
+                ("NO_COLOR".to_string(), "1".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn passes_on_the_allowed_variables_which_are_set() {
+        let homes = Homes::create().expect("failed to create homes");
+        std::env::set_var("HERMETIC_TEST_TOKEN", "abc");
+
+        let env = env(
+            &homes,
+            &[
+                "HERMETIC_TEST_TOKEN".to_string(),
+                "HERMETIC_TEST_UNSET".to_string(),
+            ],
+        )
+        .expect("should succeed");
+
+        assert_eq!(
+            env.last(),
+            Some(&("HERMETIC_TEST_TOKEN".to_string(), "abc".to_string()))
+        );
+        assert_eq!(env.len(), 6);
+    }
+}
@@ -0,0 +1,265 @@
+//! What happened the last time each spec file was run, kept in
+//! `.specdown/last-run.json` so later runs can make use of it.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::embedding::RunReport;
+use crate::results::basic_printer::BasicPrinter;
+use crate::results::Mask;
+
+/// The directory, relative to where specdown is run, that run state is kept
+/// in.
+const STATE_DIR: &str = ".specdown";
+
+/// The name of the file in [`STATE_DIR`] the last run is recorded in.
+const FILE_NAME: &str = "last-run.json";
+
+/// The recorded outcome of every spec file which has been run, keyed by its
+/// path as given to `specdown run`.
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct LastRun {
+    #[serde(default)]
+    pub spec_files: BTreeMap<String, SpecFileRecord>,
+}
+
+/// The recorded outcome of a single spec file.
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct SpecFileRecord {
+    /// Whether every function succeeded and no errors occurred.
+    pub success: bool,
+    /// How long the spec file took to run, in seconds.
+    pub duration: f64,
+    /// The outcome of each function run, in the order they were run.
+    #[serde(default)]
+    pub actions: Vec<ActionRecord>,
+}
+
+/// The recorded outcome of a single function.
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct ActionRecord {
+    /// What the function did, as it is printed.
+    pub title: String,
+    /// Whether the function succeeded.
+    pub success: bool,
+    /// How long the function took to run, in seconds.
+    pub duration: f64,
+}
+
+impl LastRun {
+    /// Loads the last run recorded in `dir`. A missing or unreadable file
+    /// is treated as there having been no previous run.
+    pub fn load(dir: &Path) -> Self {
+        fs::read_to_string(path(dir))
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default()
+    }
+
+    /// How long `spec_file` took the last time it was run, in seconds.
+    pub fn duration(&self, spec_file: &Path) -> Option<f64> {
+        self.spec_files
+            .get(&key(spec_file))
+            .map(|record| record.duration)
+    }
+
+    /// Whether `spec_file` failed the last time it was run.
+    pub fn failed(&self, spec_file: &Path) -> bool {
+        self.spec_files
+            .get(&key(spec_file))
+            .is_some_and(|record| !record.success)
+    }
+
+    /// The spec files which failed the last time they were run, for
+    /// `--last-failed`. If none of them did, they are all run again.
+    pub fn only_failed(&self, spec_files: Vec<PathBuf>) -> Vec<PathBuf> {
+        if spec_files.iter().any(|spec_file| self.failed(spec_file)) {
+            spec_files
+                .into_iter()
+                .filter(|spec_file| self.failed(spec_file))
+                .collect()
+        } else {
+            spec_files
+        }
+    }
+
+    /// The spec files which failed the last time they were run followed by
+    /// the rest, for `--failed-first`. Otherwise, the order they were given
+    /// in is kept.
+    pub fn failed_first(&self, spec_files: Vec<PathBuf>) -> Vec<PathBuf> {
+        let (failed, rest): (Vec<PathBuf>, Vec<PathBuf>) = spec_files
+            .into_iter()
+            .partition(|spec_file| self.failed(spec_file));
+        failed.into_iter().chain(rest).collect()
+    }
+
+    /// Records the spec files in `report`, keeping the records of any spec
+    /// files which weren't run this time, or were skipped.
+    pub fn update(&mut self, report: &RunReport, mask: &Mask) {
+        for spec_file in report
+            .spec_files
+            .iter()
+            .filter(|spec_file| spec_file.skipped.is_none())
+        {
+            self.spec_files.insert(
+                key(&spec_file.path),
+                SpecFileRecord {
+                    success: spec_file.success(),
+                    duration: spec_file.duration.as_secs_f64(),
+                    actions: spec_file
+                        .results
+                        .iter()
+                        .zip(&spec_file.durations)
+                        .map(|(result, duration)| ActionRecord {
+                            title: mask.apply(&BasicPrinter::action_title(result)),
+                            success: result.success(),
+                            duration: duration.as_secs_f64(),
+                        })
+                        .collect(),
+                },
+            );
+        }
+    }
+
+    /// Saves the run to `dir`, creating the state directory with a
+    /// `.gitignore` so it is never committed.
+    pub fn save(&self, dir: &Path) -> io::Result<()> {
+        let state_dir = dir.join(STATE_DIR);
+        fs::create_dir_all(&state_dir)?;
+        fs::write(state_dir.join(".gitignore"), "*\n")?;
+        let contents = serde_json::to_string_pretty(self).map_err(io::Error::other)?;
+        fs::write(path(dir), contents + "\n")
+    }
+}
+
+fn path(dir: &Path) -> PathBuf {
+    dir.join(STATE_DIR).join(FILE_NAME)
+}
+
+/// The key a spec file is recorded under, so `./a.md` and `a.md` are the
+/// same file.
+fn key(spec_file: &Path) -> String {
+    spec_file
+        .strip_prefix(".")
+        .unwrap_or(spec_file)
+        .display()
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::{Path, PathBuf};
+
+    use super::{ActionRecord, LastRun};
+    use crate::embedding::RunReport;
+    use crate::results::{ActionResult, CreateFileResult, Mask};
+    use crate::runner::RunEvent;
+    use crate::types::{CreateFileAction, FileContent, FilePath};
+    use std::time::Duration;
+
+    fn report(spec_file: &str, success: bool, duration: Duration) -> RunReport {
+        RunReport::from_events(vec![
+            RunEvent::SpecFileStarted(spec_file.into()),
+            RunEvent::SpecFileCompleted { success, duration },
+        ])
+    }
+
+    #[test]
+    fn is_empty_when_nothing_has_been_run() {
+        let dir = tempfile::tempdir().expect("failed to create temp dir");
+
+        assert_eq!(LastRun::load(dir.path()), LastRun::default());
+    }
+
+    #[test]
+    fn remembers_durations_between_runs() {
+        let dir = tempfile::tempdir().expect("failed to create temp dir");
+        let mut last_run = LastRun::load(dir.path());
+        last_run.update(
+            &report("./a.md", true, Duration::from_millis(1500)),
+            &Mask::default(),
+        );
+        last_run.save(dir.path()).expect("failed to save last run");
+
+        let mut last_run = LastRun::load(dir.path());
+        last_run.update(
+            &report("b.md", false, Duration::from_millis(250)),
+            &Mask::default(),
+        );
+
+        assert_eq!(last_run.duration(Path::new("a.md")), Some(1.5));
+        assert_eq!(last_run.duration(Path::new("./b.md")), Some(0.25));
+        assert_eq!(last_run.duration(Path::new("c.md")), None);
+    }
+
+    fn files(names: &[&str]) -> Vec<PathBuf> {
+        names.iter().map(PathBuf::from).collect()
+    }
+
+    #[test]
+    fn runs_only_the_spec_files_which_failed() {
+        let mut last_run = LastRun::default();
+        last_run.update(&report("a.md", true, Duration::ZERO), &Mask::default());
+        last_run.update(&report("b.md", false, Duration::ZERO), &Mask::default());
+        last_run.update(&report("d.md", false, Duration::ZERO), &Mask::default());
+
+        assert_eq!(
+            last_run.only_failed(files(&["a.md", "b.md", "c.md", "d.md"])),
+            files(&["b.md", "d.md"])
+        );
+        assert_eq!(
+            last_run.only_failed(files(&["a.md", "c.md"])),
+            files(&["a.md", "c.md"])
+        );
+    }
+
+    #[test]
+    fn runs_the_spec_files_which_failed_first() {
+        let mut last_run = LastRun::default();
+        last_run.update(&report("a.md", true, Duration::ZERO), &Mask::default());
+        last_run.update(&report("c.md", false, Duration::ZERO), &Mask::default());
+
+        assert_eq!(
+            last_run.failed_first(files(&["a.md", "b.md", "c.md"])),
+            files(&["c.md", "a.md", "b.md"])
+        );
+    }
+
+    #[test]
+    fn records_the_outcome_of_each_function_with_secrets_masked() {
+        let result = ActionResult::CreateFile(CreateFileResult {
+            action: CreateFileAction {
+                file_path: FilePath("s3cret.txt".to_string()),
+                file_content: FileContent(String::new()),
+            },
+        });
+        let mut last_run = LastRun::default();
+
+        let mask = Mask::new(vec!["s3cret".to_string()], vec![]).expect("valid mask");
+
+        last_run.update(
+            &RunReport::from_events(vec![
+                RunEvent::SpecFileStarted("a.md".into()),
+                RunEvent::TestCompleted(result, Duration::from_millis(500)),
+                RunEvent::SpecFileCompleted {
+                    success: true,
+                    duration: Duration::from_secs(1),
+                },
+            ]),
+            &mask,
+        );
+
+        assert_eq!(
+            last_run.spec_files["a.md"].actions,
+            vec![ActionRecord {
+                title: "creating file ***.txt".to_string(),
+                success: true,
+                duration: 0.5,
+            }]
+        );
+    }
+}
@@ -1,4 +1,5 @@
-use std::path::PathBuf;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 
 use merge::Merge;
 
@@ -6,70 +7,200 @@ use merge::Merge;
 use executor_factory::ContainerExecutorFactory;
 use executor_factory::{ExecutorFactory, ShellExecutorFactory};
 use file_reader::FileReader;
-use run_command::{RunCommand, RunMode};
+use front_matter::FrontMatter;
+use last_run::LastRun;
+use run_command::{RunCommand, RunMode, SpecFileOverride};
 use settings::ExecutorKind;
 pub use settings::RunSettings;
 
 use crate::config::Config;
+use crate::embedding::RunReport;
 use crate::exit_codes::ExitCode;
 use crate::results::basic_printer::BasicPrinter;
+use crate::results::Mask;
 use crate::runner::{Error, RunEvent};
 use crate::workspace::{ExistingDir, TemporaryDirectory, Workspace};
 
-mod config_file;
+pub(crate) mod config_file;
 mod executor_factory;
 mod exit_code;
-mod file_discovery;
-mod file_reader;
-mod run_command;
+pub(crate) mod file_discovery;
+pub(crate) mod file_reader;
+pub(crate) mod front_matter;
+mod hermetic;
+mod last_run;
+pub(crate) mod run_command;
 mod settings;
-mod specdown_env;
+mod shard;
+mod shuffle;
+pub(crate) mod specdown_env;
 
 /// The shell command used to invoke script blocks when neither the command
 /// line nor `specdown.toml` sets one.
-const DEFAULT_SHELL_COMMAND: &str = "bash -c";
+pub(crate) const DEFAULT_SHELL_COMMAND: &str = "bash -c";
 
 /// The number of parallel jobs used when neither the command line nor
 /// `specdown.toml` sets one.
 const DEFAULT_JOBS: u32 = 1;
 
 pub fn execute(config: &Config, args: &RunSettings) {
-    let printer = BasicPrinter::new(config.colour);
+    let settings = load_settings(config, args);
+    let mask = settings.as_ref().ok().map(build_mask).transpose();
+    let (settings, mask) = match mask {
+        Ok(mask) => (settings, mask.unwrap_or_default()),
+        Err(err) => (Err(err), Mask::default()),
+    };
+
+    let mut printer = BasicPrinter::new(config.colour).with_mask(mask.clone());
+    if let Some(count) = settings
+        .as_ref()
+        .ok()
+        .and_then(|settings| settings.durations)
+    {
+        printer = printer.with_durations(count);
+    }
     let printer_mutex =
         std::sync::Mutex::new(Box::new(printer) as Box<dyn crate::results::Printer>);
 
-    let events = create_run_command(config, args).map_or_else(
-        |err| {
-            let events = vec![RunEvent::ErrorOccurred(err)];
-            let mut guard = printer_mutex.lock().expect("printer mutex poisoned");
-            for event in &events {
-                guard.print(event);
-            }
-            events
-        },
-        |command| command.execute_with_printer(&printer_mutex),
-    );
+    let events = settings
+        .and_then(|settings| create_run_command(config, args, &settings))
+        .map_or_else(
+            |err| {
+                let events = vec![RunEvent::ErrorOccurred(err)];
+                let mut guard = printer_mutex.lock().expect("printer mutex poisoned");
+                for event in &events {
+                    guard.print(event);
+                }
+                events
+            },
+            |command| command.execute_with_printer(&printer_mutex),
+        );
 
     let exit_code = exit_code::from_events(&events);
+    record_last_run(events, &mask);
 
     std::process::exit(exit_code as i32)
 }
 
-fn create_run_command(config: &Config, cli_settings: &RunSettings) -> Result<RunCommand, Error> {
+/// Records how each spec file went in `.specdown/last-run.json`, for later
+/// runs to use, with the secrets hidden by `mask` masked. Failing to write
+/// it doesn't fail the run.
+fn record_last_run(events: Vec<RunEvent>, mask: &Mask) {
+    let report = RunReport::from_events(events);
+    if report.spec_files.is_empty() {
+        return;
+    }
+
+    let current_dir = std::env::current_dir().expect("Failed to get current workspace directory");
+    let mut last_run = LastRun::load(&current_dir);
+    last_run.update(&report, mask);
+    let _ = last_run.save(&current_dir);
+}
+
+/// Merges the settings given on the command line with those from
+/// `specdown.toml`, command-line values winning.
+fn load_settings(config: &Config, cli_settings: &RunSettings) -> Result<RunSettings, Error> {
     let current_dir = std::env::current_dir().expect("Failed to get current workspace directory");
 
     let mut args = cli_settings.clone();
-    let file_settings =
-        config_file::load_run_settings(config.config_path.as_deref(), &current_dir)?;
+    let file_settings = config_file::load_run_settings(
+        config.file.as_deref(),
+        config.profile.as_deref(),
+        &current_dir,
+    )?;
     args.merge(file_settings);
+    Ok(args)
+}
+
+/// Builds the mask hiding the values of the `mask_env` variables, as set for
+/// scripts or else in specdown's own environment, and matches of the `mask`
+/// patterns.
+fn build_mask(settings: &RunSettings) -> Result<Mask, Error> {
+    let mut env = read_env_files(&settings.env_files)?;
+    env.extend(parse_environment_variables(&settings.env));
+    let values = settings.mask_env.iter().filter_map(|name| {
+        env.iter()
+            .rev()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.clone())
+            .or_else(|| std::env::var(name).ok())
+    });
+    Mask::new(values, settings.mask.iter().cloned())
+}
+
+/// Reads the front matter of every spec file.
+fn read_front_matter(spec_files: &[PathBuf]) -> Result<HashMap<PathBuf, FrontMatter>, Error> {
+    spec_files
+        .iter()
+        .map(|spec_file| {
+            FrontMatter::read(spec_file).map(|front_matter| (spec_file.clone(), front_matter))
+        })
+        .collect()
+}
+
+/// Works out how the spec files with their own settings are run: those
+/// whose front matter says to skip them aren't, and the rest get executor
+/// factories built from their settings. These are layered with those on the
+/// command line first, then the front matter, then any `specdown.toml` files
+/// in their directories, nearest first, then the top-level `specdown.toml`.
+fn build_overrides(
+    config: &Config,
+    cli_settings: &RunSettings,
+    spec_files: &[PathBuf],
+    front_matter: &HashMap<PathBuf, FrontMatter>,
+    current_dir: &Path,
+) -> Result<HashMap<PathBuf, SpecFileOverride>, Error> {
+    let profile = config.profile.as_deref();
+    let root = match config.file.as_deref() {
+        Some(_) => None,
+        None => config_file::discover(current_dir),
+    }
+    .as_deref()
+    .and_then(Path::parent)
+    .map_or_else(|| current_dir.to_path_buf(), Path::to_path_buf);
+    let file_settings =
+        config_file::load_run_settings(config.file.as_deref(), profile, current_dir)?;
+
+    let mut overrides = HashMap::new();
+    for spec_file in spec_files {
+        let front_matter = front_matter.get(spec_file);
+        if let Some(reason) = front_matter.and_then(|front_matter| front_matter.skip.clone()) {
+            overrides.insert(spec_file.clone(), SpecFileOverride::Skip(reason));
+            continue;
+        }
+
+        let own_settings = front_matter.and_then(FrontMatter::run_settings);
+        let nested =
+            config_file::load_nested_settings(&current_dir.join(spec_file), &root, profile)?;
+        if own_settings.is_none() && nested.is_empty() {
+            continue;
+        }
+
+        let mut settings = cli_settings.clone();
+        for layer in own_settings.into_iter().chain(nested) {
+            settings.merge(layer);
+        }
+        settings.merge(file_settings.clone());
+        overrides.insert(
+            spec_file.clone(),
+            SpecFileOverride::Run {
+                factory: build_executor_factory(&settings)?,
+                working_dir: settings.working_dir,
+            },
+        );
+    }
+    Ok(overrides)
+}
+
+fn create_run_command(
+    config: &Config,
+    cli_settings: &RunSettings,
+    args: &RunSettings,
+) -> Result<RunCommand, Error> {
+    let current_dir = std::env::current_dir().expect("Failed to get current workspace directory");
 
     let temp_workspace_dir = args.temporary_workspace_dir;
     let workspace_init_command = args.workspace_init_command.clone();
-    let shell_cmd = args
-        .shell_command
-        .clone()
-        .unwrap_or_else(|| DEFAULT_SHELL_COMMAND.to_string());
-    let env = parse_environment_variables(&args.env);
 
     // Resolve jobs: 0 means "run all in parallel" — map to CPU count.
     let jobs = match args.jobs.unwrap_or(DEFAULT_JOBS) {
@@ -77,13 +208,44 @@ fn create_run_command(config: &Config, cli_settings: &RunSettings) -> Result<Run
         jobs => jobs as usize,
     };
 
-    let unset_env = args.unset_env.clone();
-    let paths = args.add_path.clone();
     let file_reader = FileReader::new(current_dir.clone());
     let workspace_per_spec = args.workspace_per_spec;
+    let session = args.session;
 
     let spec_files =
-        file_discovery::build_file_list(&args.spec_files, &current_dir, args.follow_links)?;
+        file_discovery::find_spec_files(&args.spec_files, &args.exclude, &current_dir)?;
+    let mut spec_files =
+        file_discovery::build_file_list(&spec_files, &current_dir, args.follow_links)?;
+    let front_matter = read_front_matter(&spec_files)?;
+    if !args.tags.is_empty() {
+        spec_files.retain(|spec_file| {
+            front_matter[spec_file]
+                .tags
+                .iter()
+                .any(|tag| args.tags.contains(tag))
+        });
+    }
+    if args.last_failed {
+        spec_files = LastRun::load(&current_dir).only_failed(spec_files);
+    } else if args.failed_first {
+        spec_files = LastRun::load(&current_dir).failed_first(spec_files);
+    }
+    if let Some(shard) = args.shard {
+        let timings = args.balance_shards.then(|| LastRun::load(&current_dir));
+        spec_files = shard.select(spec_files, timings.as_ref());
+    }
+
+    // `--failed-first` can't be given with `--shuffle` or `--seed`, but
+    // also takes priority over them when they're set in `specdown.toml`.
+    let seed = if args.failed_first {
+        None
+    } else {
+        args.seed
+            .or_else(|| args.shuffle.then(shuffle::random_seed))
+    };
+    if let Some(seed) = seed {
+        println!("Shuffling spec files with --seed {seed}\n");
+    }
 
     validate_workspace_dir_conflict(args.workspace_dir.as_ref(), temp_workspace_dir);
 
@@ -94,7 +256,14 @@ fn create_run_command(config: &Config, cli_settings: &RunSettings) -> Result<Run
         std::process::exit(ExitCode::ErrorOccurred as i32)
     }
 
-    let factory = build_executor_factory(&args, shell_cmd, env, unset_env, paths)?;
+    let factory = build_executor_factory(args)?;
+    let overrides = build_overrides(
+        config,
+        cli_settings,
+        &spec_files,
+        &front_matter,
+        &current_dir,
+    )?;
 
     if workspace_per_spec {
         return Ok(RunCommand {
@@ -107,6 +276,9 @@ fn create_run_command(config: &Config, cli_settings: &RunSettings) -> Result<Run
             workspace_init_command,
             file_reader,
             jobs,
+            session,
+            seed,
+            overrides,
         });
     }
 
@@ -126,36 +298,55 @@ fn create_run_command(config: &Config, cli_settings: &RunSettings) -> Result<Run
         run_mode: RunMode::SharedWorkspace {
             executor,
             working_dir: actual_working_dir,
+            workspace_dir: workspace.dir().clone(),
+            start_dir: current_dir,
         },
         workspace_init_command,
         file_reader,
         jobs,
+        session,
+        seed,
+        overrides,
     })
 }
 
 /// Builds the `ExecutorFactory` matching the configured executor kind
 /// (`--executor shell` or `--executor container`), shared by both the
-/// single-shared-workspace path and the `--workspace-per-spec` path.
+/// single-shared-workspace path and the `--workspace-per-spec` path, and by
+/// spec files with their own settings.
 ///
 /// `Result` is only "unnecessary" when the `container` feature is enabled
 /// (every arm becomes infallible); without it, the container arm returns
 /// `Err(ContainerFeatureNotEnabled)`, so the wrapping is genuinely needed
 /// depending on build configuration.
 #[allow(clippy::unnecessary_wraps)]
-fn build_executor_factory(
-    args: &RunSettings,
-    shell_cmd: String,
-    env: Vec<(String, String)>,
-    unset_env: Vec<String>,
-    paths: Vec<String>,
-) -> Result<Box<dyn ExecutorFactory>, Error> {
+fn build_executor_factory(args: &RunSettings) -> Result<Box<dyn ExecutorFactory>, Error> {
+    let shell_cmd = args
+        .shell_command
+        .clone()
+        .unwrap_or_else(|| DEFAULT_SHELL_COMMAND.to_string());
+    let mut env = read_env_files(&args.env_files)?;
+    env.extend(parse_environment_variables(&args.env));
+    let unset_env = args.unset_env.clone();
+    let paths = args.add_path.clone();
+    let interpreters = parse_environment_variables(&args.interpreters);
     match args.executor_config.executor.unwrap_or_default() {
         ExecutorKind::Shell => Ok(Box::new(ShellExecutorFactory {
             shell_cmd,
             base_env: env,
             unset_env,
             paths,
+            interpreters,
+            hermetic: if args.hermetic {
+                Some(hermetic::Homes::create()?)
+            } else {
+                None
+            },
+            pass_env: args.pass_env.clone(),
         })),
+        ExecutorKind::Container if args.hermetic || !args.pass_env.is_empty() => {
+            Err(Error::HermeticNotSupported)
+        }
         ExecutorKind::Container => {
             #[cfg(feature = "container")]
             {
@@ -171,6 +362,7 @@ fn build_executor_factory(
                     unset_env,
                     paths,
                     container_volumes: args.executor_config.container_volumes.clone(),
+                    interpreters,
                 }))
             }
             #[cfg(not(feature = "container"))]
@@ -222,6 +414,22 @@ fn create_workspace(
     }
 }
 
+/// Reads the variables set in each dotenv file in turn, so that a later
+/// file's value for a variable wins over an earlier one's.
+fn read_env_files(paths: &[PathBuf]) -> Result<Vec<(String, String)>, Error> {
+    let mut env = Vec::new();
+    for path in paths {
+        let unreadable = |err: dotenvy::Error| Error::EnvFileUnreadable {
+            path: path.display().to_string(),
+            message: err.to_string(),
+        };
+        for variable in dotenvy::from_path_iter(path).map_err(unreadable)? {
+            env.push(variable.map_err(unreadable)?);
+        }
+    }
+    Ok(env)
+}
+
 fn parse_environment_variables(strings: &[String]) -> Vec<(String, String)> {
     strings
         .iter()
@@ -239,7 +447,11 @@ fn parse_environment_variable(string: &str) -> (String, String) {
 
 #[cfg(test)]
 mod tests {
-    use super::workspace_per_spec_validation_error;
+    use super::{build_executor_factory, read_env_files, workspace_per_spec_validation_error};
+    use crate::commands::run::settings::{ExecutorKind, RunSettings};
+    use crate::runner::Error;
+    use merge::Merge;
+    use std::fs;
 
     #[test]
     fn errors_when_workspace_per_spec_is_set_without_temporary_workspace_dir() {
@@ -259,4 +471,73 @@ mod tests {
         assert_eq!(workspace_per_spec_validation_error(false, false), None);
         assert_eq!(workspace_per_spec_validation_error(false, true), None);
     }
+
+    #[test]
+    fn layers_add_env_unset_env_and_add_path_to_those_below() {
+        let strings = |values: &[&str]| values.iter().map(ToString::to_string).collect();
+        let layer = |env: &[&str], unset_env: &[&str], add_path: &[&str]| RunSettings {
+            env: strings(env),
+            unset_env: strings(unset_env),
+            add_path: strings(add_path),
+            ..RunSettings::default()
+        };
+        let config = layer(&["A=config", "B=config", "C=config"], &["X"], &["config"]);
+        let front_matter = layer(&["B=front matter", "C=front matter"], &["Y"], &["front"]);
+        let cli = layer(&["C=cli"], &["X"], &["cli"]);
+
+        let mut settings = cli;
+        settings.merge(front_matter);
+        settings.merge(config);
+
+        assert_eq!(settings.env, ["A=config", "B=front matter", "C=cli"]);
+        assert_eq!(settings.unset_env, ["X", "Y"]);
+        assert_eq!(settings.add_path, ["cli", "front", "config"]);
+    }
+
+    #[test]
+    fn rejects_hermetic_settings_with_the_container_executor() {
+        let mut settings = RunSettings::default();
+        settings.executor_config.executor = Some(ExecutorKind::Container);
+        settings.pass_env = vec!["TOKEN".to_string()];
+        assert!(matches!(
+            build_executor_factory(&settings),
+            Err(Error::HermeticNotSupported)
+        ));
+
+        settings.pass_env = Vec::new();
+        settings.hermetic = true;
+        assert!(matches!(
+            build_executor_factory(&settings),
+            Err(Error::HermeticNotSupported)
+        ));
+    }
+
+    #[test]
+    fn reads_env_files_in_order() {
+        let dir = tempfile::tempdir().expect("failed to create temp dir");
+        let first = dir.path().join(".env");
+        let second = dir.path().join(".env.test");
+        fs::write(&first, "# Defaults\nHOST=localhost\nPORT=80\n").expect("failed to write");
+        fs::write(&second, "export PORT=8080\n").expect("failed to write");
+
+        let env = read_env_files(&[first, second]).expect("should succeed");
+
+        assert_eq!(
+            env,
+            vec![
+                ("HOST".to_string(), "localhost".to_string()),
+                ("PORT".to_string(), "80".to_string()),
+                ("PORT".to_string(), "8080".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn errors_when_an_env_file_is_missing() {
+        let dir = tempfile::tempdir().expect("failed to create temp dir");
+
+        let result = read_env_files(&[dir.path().join(".env")]);
+
+        assert!(matches!(result, Err(Error::EnvFileUnreadable { .. })));
+    }
 }
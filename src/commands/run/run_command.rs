@@ -1,16 +1,19 @@
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use std::sync::Mutex;
+use std::time::Instant;
 
 use rayon::prelude::*;
 
 use crate::parsers;
 use crate::results::Printer;
 use crate::runner::{Error, Executor, RunEvent, Runner, State};
-use crate::types::ScriptCode;
+use crate::types::{Action, ScriptCode};
 use crate::workspace::{TemporaryDirectory, Workspace};
 
 use super::executor_factory::ExecutorFactory;
 use super::file_reader::FileReader;
+use super::shuffle;
 use super::specdown_env;
 
 /// How workspaces and executors are provided to `RunCommand`.
@@ -20,6 +23,10 @@ pub enum RunMode {
     SharedWorkspace {
         executor: Box<dyn Executor>,
         working_dir: PathBuf,
+        /// The workspace and the directory specdown was run from, for
+        /// building the executors of spec files with their own settings.
+        workspace_dir: PathBuf,
+        start_dir: PathBuf,
     },
     /// A fresh temporary workspace (and fresh executor instance) is created
     /// for every spec file; `workspace_init_command` is re-run each time.
@@ -41,6 +48,30 @@ pub struct RunCommand {
     /// A value of 0 has already been resolved to the CPU count by the CLI layer.
     /// When greater than 1, spec files are executed in parallel using rayon.
     pub jobs: usize,
+    /// Run every spec file in a persistent shell session (`--session`).
+    /// Spec files containing a `session()` block (including `console`
+    /// transcripts) always do.
+    pub session: bool,
+    /// Shuffle the spec files with this seed before running them
+    /// (`--shuffle` or `--seed`), rather than running them in the order
+    /// given.
+    pub seed: Option<u64>,
+    /// How spec files with their own settings, from a `specdown.toml` in
+    /// their directory or their front matter, are run differently to the
+    /// rest.
+    pub overrides: HashMap<PathBuf, SpecFileOverride>,
+}
+
+/// How a spec file with its own settings is run.
+pub enum SpecFileOverride {
+    /// It isn't run, for the given reason.
+    Skip(String),
+    /// Its scripts are run by an executor from `factory`, in `working_dir`
+    /// (relative to the workspace) if given, or the workspace itself.
+    Run {
+        factory: Box<dyn ExecutorFactory>,
+        working_dir: Option<PathBuf>,
+    },
 }
 
 impl RunCommand {
@@ -51,25 +82,54 @@ impl RunCommand {
     /// so output from different files never interleaves. A clear file header
     /// introduces each spec file's results. The events are also returned in
     /// original file order for exit-code computation.
+    ///
+    /// Once every spec file has run, the printer is told how long the whole
+    /// run took.
     pub fn execute_with_printer(&self, printer: &Mutex<Box<dyn Printer>>) -> Vec<RunEvent> {
+        let started = Instant::now();
+        let mut spec_files = self.spec_files.clone();
+        if let Some(seed) = self.seed {
+            shuffle::shuffle(&mut spec_files, seed);
+        }
+        let events = self.execute_spec_files(&spec_files, printer);
+        printer
+            .lock()
+            .expect("printer mutex poisoned")
+            .finish(started.elapsed());
+        events
+    }
+
+    fn execute_spec_files(
+        &self,
+        spec_files: &[PathBuf],
+        printer: &Mutex<Box<dyn Printer>>,
+    ) -> Vec<RunEvent> {
         match &self.run_mode {
             RunMode::SharedWorkspace {
                 executor,
                 working_dir,
+                workspace_dir,
+                start_dir,
             } => {
                 self.initialise_workspace(executor.as_ref());
 
+                let shared = SharedWorkspace {
+                    executor: executor.as_ref(),
+                    working_dir,
+                    workspace_dir,
+                    start_dir,
+                };
                 if self.jobs > 1 {
-                    self.execute_parallel_shared(printer, executor.as_ref(), working_dir)
+                    self.execute_parallel_shared(spec_files, printer, &shared)
                 } else {
-                    self.execute_sequential_shared(printer, executor.as_ref(), working_dir)
+                    self.execute_sequential_shared(spec_files, printer, &shared)
                 }
             }
             RunMode::PerSpecWorkspace { .. } => {
                 if self.jobs > 1 {
-                    self.execute_parallel_per_spec(printer)
+                    self.execute_parallel_per_spec(spec_files, printer)
                 } else {
-                    self.execute_sequential_per_spec(printer)
+                    self.execute_sequential_per_spec(spec_files, printer)
                 }
             }
         }
@@ -77,13 +137,13 @@ impl RunCommand {
 
     fn execute_sequential_shared(
         &self,
+        spec_files: &[PathBuf],
         printer: &Mutex<Box<dyn Printer>>,
-        executor: &dyn Executor,
-        working_dir: &Path,
+        shared: &SharedWorkspace<'_>,
     ) -> Vec<RunEvent> {
         let mut all_events = Vec::new();
-        for spec_file in &self.spec_files {
-            let events = self.run_spec_file_with_executor(spec_file, executor, working_dir);
+        for spec_file in spec_files {
+            let events = self.run_spec_file_shared(spec_file, shared, false);
             let mut guard = printer.lock().expect("printer mutex poisoned");
             for event in &events {
                 guard.print(event);
@@ -96,9 +156,9 @@ impl RunCommand {
 
     fn execute_parallel_shared(
         &self,
+        spec_files: &[PathBuf],
         printer: &Mutex<Box<dyn Printer>>,
-        executor: &dyn Executor,
-        working_dir: &Path,
+        shared: &SharedWorkspace<'_>,
     ) -> Vec<RunEvent> {
         let pool = rayon::ThreadPoolBuilder::new()
             .num_threads(self.jobs)
@@ -106,18 +166,13 @@ impl RunCommand {
             .expect("Failed to create thread pool for parallel execution");
 
         let results: Vec<Vec<RunEvent>> = pool.install(|| {
-            self.spec_files
+            spec_files
                 .par_iter()
                 .map(|spec_file| {
-                    // Clone the executor for each spec file so that
-                    // stateful executors (e.g. ContainerExecutor) get
-                    // their own isolated container/instance. The spec file
-                    // path is passed as a label so the container executor
-                    // can incorporate a file-hash into the container name.
-                    let cloned_executor =
-                        executor.clone_box(spec_file.to_str().unwrap_or("unknown"));
-                    let events =
-                        self.run_spec_file_with_executor(spec_file, &*cloned_executor, working_dir);
+                    // Each spec file gets its own executor (see
+                    // `run_spec_file_shared`), so stateful executors such as
+                    // `ContainerExecutor` get their own isolated instance.
+                    let events = self.run_spec_file_shared(spec_file, shared, true);
                     // Lock the printer so output from this spec file is printed
                     // atomically and never interleaves with output from another.
                     let mut guard = printer.lock().expect("printer mutex poisoned");
@@ -142,9 +197,13 @@ impl RunCommand {
         }
     }
 
-    fn execute_sequential_per_spec(&self, printer: &Mutex<Box<dyn Printer>>) -> Vec<RunEvent> {
+    fn execute_sequential_per_spec(
+        &self,
+        spec_files: &[PathBuf],
+        printer: &Mutex<Box<dyn Printer>>,
+    ) -> Vec<RunEvent> {
         let mut all_events = Vec::new();
-        for spec_file in &self.spec_files {
+        for spec_file in spec_files {
             let events = self.run_spec_file_per_spec(spec_file);
             let mut guard = printer.lock().expect("printer mutex poisoned");
             for event in &events {
@@ -156,14 +215,18 @@ impl RunCommand {
         all_events
     }
 
-    fn execute_parallel_per_spec(&self, printer: &Mutex<Box<dyn Printer>>) -> Vec<RunEvent> {
+    fn execute_parallel_per_spec(
+        &self,
+        spec_files: &[PathBuf],
+        printer: &Mutex<Box<dyn Printer>>,
+    ) -> Vec<RunEvent> {
         let pool = rayon::ThreadPoolBuilder::new()
             .num_threads(self.jobs)
             .build()
             .expect("Failed to create thread pool for parallel execution");
 
         let results: Vec<Vec<RunEvent>> = pool.install(|| {
-            self.spec_files
+            spec_files
                 .par_iter()
                 .map(|spec_file| {
                     // Each spec file builds its own fresh workspace and
@@ -200,6 +263,14 @@ impl RunCommand {
             unreachable!("build_spec_workspace requires RunMode::PerSpecWorkspace")
         };
 
+        let (factory, working_dir_suffix) = match self.overrides.get(spec_file) {
+            Some(SpecFileOverride::Run {
+                factory,
+                working_dir,
+            }) => (factory, working_dir),
+            _ => (factory, working_dir_suffix),
+        };
+
         let mut workspace = TemporaryDirectory::create();
         workspace.initialize();
 
@@ -219,19 +290,64 @@ impl RunCommand {
     /// workspace and executor, re-runs `workspace_init_command` in it, then
     /// runs the spec file's own actions.
     fn run_spec_file_per_spec(&self, spec_file: &Path) -> Vec<RunEvent> {
+        if let Some(SpecFileOverride::Skip(reason)) = self.overrides.get(spec_file) {
+            return skipped(spec_file, reason);
+        }
+
+        let started = Instant::now();
         match self.build_spec_workspace(spec_file) {
             Ok((executor, working_dir)) => {
                 self.initialise_workspace(executor.as_ref());
                 self.run_spec_file_with_executor(spec_file, executor.as_ref(), &working_dir)
             }
-            Err(err) => vec![
-                RunEvent::SpecFileStarted(spec_file.to_path_buf()),
-                RunEvent::ErrorOccurred(err),
-                RunEvent::SpecFileCompleted { success: false },
-            ],
+            Err(err) => failed_to_start(spec_file, err, started),
         }
     }
 
+    /// Runs a single spec file in the shared workspace. A spec file with its
+    /// own settings gets an executor built from them. When `isolate` is set,
+    /// as it is when running in parallel, every other spec file gets a clone
+    /// of the shared executor, labelled with its path so the container
+    /// executor can incorporate a file-hash into the container name.
+    fn run_spec_file_shared(
+        &self,
+        spec_file: &Path,
+        shared: &SharedWorkspace<'_>,
+        isolate: bool,
+    ) -> Vec<RunEvent> {
+        let started = Instant::now();
+        let label = spec_file.to_str().unwrap_or("unknown");
+        let (own_executor, working_dir) = match self.overrides.get(spec_file) {
+            Some(SpecFileOverride::Skip(reason)) => return skipped(spec_file, reason),
+            Some(SpecFileOverride::Run {
+                factory,
+                working_dir,
+            }) => {
+                let working_dir = working_dir.as_ref().map_or_else(
+                    || shared.workspace_dir.to_path_buf(),
+                    |dir| shared.workspace_dir.join(dir),
+                );
+                let extra_env =
+                    specdown_env::build(shared.start_dir, shared.workspace_dir, &working_dir);
+                match factory.build(label, &extra_env, &working_dir) {
+                    Ok(executor) => (Some(executor), working_dir),
+                    Err(err) => return failed_to_start(spec_file, err, started),
+                }
+            }
+            None if isolate => (
+                Some(shared.executor.clone_box(label)),
+                shared.working_dir.to_path_buf(),
+            ),
+            None => (None, shared.working_dir.to_path_buf()),
+        };
+
+        self.run_spec_file_with_executor(
+            spec_file,
+            own_executor.as_deref().unwrap_or(shared.executor),
+            &working_dir,
+        )
+    }
+
     /// Run a single spec file using the given executor and working directory.
     ///
     /// This is used by parallel execution to pass a cloned executor
@@ -244,17 +360,32 @@ impl RunCommand {
         working_dir: &Path,
     ) -> Vec<RunEvent> {
         let mut state = State::new();
-        let mut runner = Runner::create(executor, working_dir, &mut state);
+        let started = Instant::now();
 
         let start_events = vec![RunEvent::SpecFileStarted(spec_file.to_path_buf())];
-        let contents = self.file_reader.read_file(spec_file);
-        let run_events = parsers::parse(&contents)
-            .map_err(Error::RunFailed)
-            .map(|action_list| runner.run(&action_list))
+        let run_events = self
+            .file_reader
+            .read_file(spec_file)
+            .and_then(|contents| {
+                parsers::parse(&contents).map_err(|error| Error::RunFailed {
+                    path: spec_file.display().to_string(),
+                    error: Box::new(error),
+                })
+            })
+            .map(|action_list| {
+                let session = self.session
+                    || action_list.iter().any(|action| {
+                        matches!(action, Action::Session(_) | Action::ConsoleCommand(_))
+                    });
+                Runner::create(executor, working_dir, &mut state)
+                    .with_session(session)
+                    .run(&action_list)
+            })
             .or_else::<Error, _>(|err| Ok(vec![RunEvent::ErrorOccurred(err)]))
             .unwrap();
         let end_events = vec![RunEvent::SpecFileCompleted {
             success: state.is_success(),
+            duration: started.elapsed(),
         }];
 
         start_events
@@ -265,6 +396,34 @@ impl RunCommand {
     }
 }
 
+/// The parts of `RunMode::SharedWorkspace` a spec file is run with.
+struct SharedWorkspace<'a> {
+    executor: &'a dyn Executor,
+    working_dir: &'a Path,
+    workspace_dir: &'a Path,
+    start_dir: &'a Path,
+}
+
+/// The events for a spec file whose front matter says to skip it.
+fn skipped(spec_file: &Path, reason: &str) -> Vec<RunEvent> {
+    vec![RunEvent::SpecFileSkipped {
+        path: spec_file.to_path_buf(),
+        reason: reason.to_string(),
+    }]
+}
+
+/// The events for a spec file whose executor couldn't be built.
+fn failed_to_start(spec_file: &Path, err: Error, started: Instant) -> Vec<RunEvent> {
+    vec![
+        RunEvent::SpecFileStarted(spec_file.to_path_buf()),
+        RunEvent::ErrorOccurred(err),
+        RunEvent::SpecFileCompleted {
+            success: false,
+            duration: started.elapsed(),
+        },
+    ]
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -305,12 +464,15 @@ mod tests {
                 RunEvent::SpecFileStarted(path) => {
                     let _ = writeln!(guard, "START: {}", path.display());
                 }
-                RunEvent::SpecFileCompleted { success } => {
+                RunEvent::SpecFileCompleted { success, .. } => {
                     let _ = writeln!(guard, "END: success={success}");
                 }
-                RunEvent::TestCompleted(result) => {
+                RunEvent::TestCompleted(result, _) => {
                     let _ = writeln!(guard, "TEST: success={}", result.success());
                 }
+                RunEvent::SpecFileSkipped { path, reason } => {
+                    let _ = writeln!(guard, "SKIP: {}: {reason}", path.display());
+                }
                 RunEvent::ErrorOccurred(error) => {
                     let _ = writeln!(guard, "ERROR: {error}");
                 }
@@ -378,11 +540,16 @@ mod tests {
             spec_files,
             run_mode: RunMode::SharedWorkspace {
                 executor,
+                workspace_dir: working_dir.clone(),
+                start_dir: working_dir.clone(),
                 working_dir,
             },
             workspace_init_command: None,
             file_reader,
             jobs,
+            session: false,
+            seed: None,
+            overrides: HashMap::new(),
         }
     }
 
@@ -429,6 +596,39 @@ mod tests {
         );
     }
 
+    #[test]
+    fn shuffles_spec_files_the_same_way_for_the_same_seed() {
+        let dir = tempdir().expect("Failed to create temp dir");
+        let names = ["a.md", "b.md", "c.md", "d.md", "e.md"];
+        let spec_files: Vec<PathBuf> = names
+            .iter()
+            .map(|name| write_spec_file(dir.path(), name, SIMPLE_SPEC))
+            .collect();
+
+        let run_order = |seed| {
+            let mut cmd = make_run_command(
+                spec_files.clone(),
+                Box::new(CountingExecutor::new()),
+                dir.path().to_path_buf(),
+                FileReader::new(dir.path().to_path_buf()),
+                1,
+            );
+            cmd.seed = seed;
+            cmd.execute_with_printer(&null_printer())
+                .into_iter()
+                .filter_map(|event| match event {
+                    RunEvent::SpecFileStarted(path) => Some(path),
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(run_order(None), spec_files);
+        let shuffled = run_order(Some(3));
+        assert_ne!(shuffled, spec_files);
+        assert_eq!(run_order(Some(3)), shuffled);
+    }
+
     #[test]
     fn parallel_execution_preserves_file_order() {
         let dir = tempdir().expect("Failed to create temp dir");
@@ -487,6 +687,35 @@ mod tests {
         );
     }
 
+    #[test]
+    fn session_block_errors_when_the_executor_does_not_support_sessions() {
+        let dir = tempdir().expect("Failed to create temp dir");
+        let spec = write_spec_file(
+            dir.path(),
+            "session.md",
+            "# Session\n\n```shell,session()\ncd /tmp\n```\n",
+        );
+
+        let file_reader = FileReader::new(dir.path().to_path_buf());
+        let cmd = make_run_command(
+            vec![spec],
+            Box::new(CountingExecutor::new()),
+            dir.path().to_path_buf(),
+            file_reader,
+            1,
+        );
+        let printer = null_printer();
+        let events = cmd.execute_with_printer(&printer);
+
+        assert!(
+            events
+                .iter()
+                .any(|e| matches!(e, RunEvent::ErrorOccurred(Error::SessionNotSupported))),
+            "Expected a SessionNotSupported error, got {} events",
+            events.len()
+        );
+    }
+
     #[test]
     fn parallel_execution_with_single_spec_file() {
         let dir = tempdir().expect("Failed to create temp dir");
@@ -744,6 +973,9 @@ mod tests {
             workspace_init_command,
             file_reader,
             jobs,
+            session: false,
+            seed: None,
+            overrides: HashMap::new(),
         }
     }
 
@@ -910,6 +1142,9 @@ mod tests {
             base_env: Vec::new(),
             unset_env: Vec::new(),
             paths: Vec::new(),
+            interpreters: Vec::new(),
+            hermetic: None,
+            pass_env: Vec::new(),
         });
 
         let cmd = make_per_spec_run_command(
@@ -929,4 +1164,35 @@ mod tests {
             "spec B should not see the file created by spec A's own workspace"
         );
     }
+
+    #[test]
+    fn runs_spec_files_with_their_own_settings_with_an_executor_built_from_them() {
+        let dir = tempdir().expect("Failed to create temp dir");
+        let spec1 = write_spec_file(dir.path(), "spec1.md", SIMPLE_SPEC);
+        let spec2 = write_spec_file(dir.path(), "spec2.md", SIMPLE_SPEC);
+        let file_reader = FileReader::new(dir.path().to_path_buf());
+        let (factory, calls, execute_count) = RecordingExecutorFactory::new();
+
+        let mut cmd = make_run_command(
+            vec![spec1, spec2.clone()],
+            Box::new(CountingExecutor::new()),
+            dir.path().to_path_buf(),
+            file_reader,
+            1,
+        );
+        cmd.overrides.insert(
+            spec2,
+            SpecFileOverride::Run {
+                factory: Box::new(factory),
+                working_dir: None,
+            },
+        );
+        let printer = null_printer();
+        let _events = cmd.execute_with_printer(&printer);
+
+        let recorded = calls.lock().expect("mutex poisoned");
+        assert_eq!(recorded.len(), 1);
+        assert!(recorded[0].0.ends_with("spec2.md"));
+        assert_eq!(execute_count.load(Ordering::SeqCst), 1);
+    }
 }
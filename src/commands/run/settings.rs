@@ -4,12 +4,15 @@ use serde::{Deserialize, Deserializer};
 use std::collections::BTreeMap;
 use std::path::PathBuf;
 
-/// Deserializes the `[run.env]` table (a `KEY = "value"` dictionary) into
-/// the `"KEY=VALUE"` string form used internally and by the `--env` CLI flag.
+use super::shard::Shard;
+
+/// Deserializes a `KEY = "value"` table (such as `[run.env]` or
+/// `[run.interpreters]`) into the `"KEY=VALUE"` string form used internally
+/// and by the matching repeatable CLI flag (`--env`, `--interpreter`).
 ///
 /// A `BTreeMap` is used so the resulting order is deterministic (sorted by
 /// key) regardless of the order keys appear in the TOML file.
-fn deserialize_env_map<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
+fn deserialize_key_value_map<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
 where
     D: Deserializer<'de>,
 {
@@ -17,6 +20,30 @@ where
     Ok(map.into_iter().map(|(k, v)| format!("{k}={v}")).collect())
 }
 
+/// Merges `KEY=VALUE` settings key by key: those in `lower` are kept unless
+/// `higher` sets the same key. Lower ones come first, as later values win
+/// when the variables are set.
+fn merge_env(higher: &mut Vec<String>, lower: Vec<String>) {
+    let key = |entry: &String| entry.split('=').next().map(ToString::to_string);
+    let keys: Vec<_> = higher.iter().map(key).collect();
+    let mut merged: Vec<String> = lower
+        .into_iter()
+        .filter(|entry| !keys.contains(&key(entry)))
+        .collect();
+    merged.append(higher);
+    *higher = merged;
+}
+
+/// Adds the values in `lower` which aren't already in `higher` after those
+/// in `higher`, so a directory added by a higher layer comes first on `PATH`.
+fn merge_union(higher: &mut Vec<String>, lower: Vec<String>) {
+    for value in lower {
+        if !higher.contains(&value) {
+            higher.push(value);
+        }
+    }
+}
+
 /// The executor backend to use for running script blocks.
 #[derive(Debug, Clone, Copy, Eq, PartialEq, Default, ValueEnum, Deserialize)]
 #[serde(rename_all = "lowercase")]
@@ -72,15 +99,25 @@ pub struct ExecutorConfig {
 /// parsed directly from the command line (via `clap::Args`), parsed from the
 /// `[run]` table of a `specdown.toml` file (via `serde::Deserialize`), and
 /// merged between the two (via `merge::Merge`, command-line values winning).
+/// `env`, `unset_env` and `add_path` add to the layers below rather than
+/// replacing them, with `env` overridden key by key.
 /// Adding a new setting only requires adding one field here.
+#[allow(clippy::struct_excessive_bools)]
 #[derive(Args, Deserialize, Merge, Debug, Clone, Default)]
 #[serde(default, deny_unknown_fields)]
 pub struct RunSettings {
-    /// The spec files to run
+    /// The spec files to run. A directory runs every Markdown file under it,
+    /// and a glob pattern such as 'docs/**/*.md' every file it matches
     #[serde(rename = "files")]
     #[merge(strategy = merge::vec::overwrite_empty)]
     pub spec_files: Vec<PathBuf>,
 
+    /// Leave out the spec files matching this glob pattern when expanding
+    /// directories and patterns (repeatable)
+    #[clap(long, value_name = "GLOB")]
+    #[merge(strategy = merge::vec::overwrite_empty)]
+    pub exclude: Vec<String>,
+
     /// Set the workspace directory
     #[clap(long)]
     #[merge(strategy = merge::option::overwrite_none)]
@@ -113,20 +150,67 @@ pub struct RunSettings {
     /// Set an environment variable (format: 'VAR_NAME=value')
     // todo: Add validator
     #[clap(long)]
-    #[serde(deserialize_with = "deserialize_env_map")]
-    #[merge(strategy = merge::vec::overwrite_empty)]
+    #[serde(deserialize_with = "deserialize_key_value_map")]
+    #[merge(strategy = merge_env)]
     pub env: Vec<String>,
 
+    /// Load environment variables from a dotenv file (repeatable). Those set
+    /// with `--env` take precedence
+    #[clap(long = "env-file", value_name = "PATH")]
+    #[merge(strategy = merge::vec::overwrite_empty)]
+    pub env_files: Vec<PathBuf>,
+
     /// Unset an environment variable
     #[clap(long)]
-    #[merge(strategy = merge::vec::overwrite_empty)]
+    #[merge(strategy = merge_union)]
     pub unset_env: Vec<String>,
 
     /// Adds the given directory to PATH
     #[clap(long)]
-    #[merge(strategy = merge::vec::overwrite_empty)]
+    #[merge(strategy = merge_union)]
     pub add_path: Vec<String>,
 
+    #[allow(clippy::doc_markdown)]
+    /// Run scripts with a minimal environment, rather than specdown's own:
+    /// PATH, a HOME in the workspace, TZ=UTC, LANG=C.UTF-8, COLUMNS=80,
+    /// NO_COLOR=1 and the variables given with `--pass-env` and `--env`
+    #[clap(long)]
+    #[merge(strategy = merge::bool::overwrite_false)]
+    pub hermetic: bool,
+
+    /// Pass this environment variable on to scripts run with `--hermetic`
+    /// (repeatable)
+    #[clap(long, value_name = "VAR")]
+    #[merge(strategy = merge::vec::overwrite_empty)]
+    pub pass_env: Vec<String>,
+
+    /// Print '***' instead of the value of this environment variable
+    /// wherever it appears in script output, diffs and recorded results
+    /// (repeatable)
+    #[clap(long, value_name = "VAR")]
+    #[merge(strategy = merge::vec::overwrite_empty)]
+    pub mask_env: Vec<String>,
+
+    /// Print '***' instead of anything matching this regular expression
+    /// wherever it appears in script output, diffs and recorded results
+    /// (repeatable)
+    #[clap(long, value_name = "REGEX")]
+    #[merge(strategy = merge::vec::overwrite_empty)]
+    pub mask: Vec<String>,
+
+    /// Run script blocks in the given language with a different interpreter
+    /// (format: 'LANGUAGE=COMMAND', e.g. 'python=python3 -c')
+    #[clap(long = "interpreter", value_name = "LANGUAGE=COMMAND")]
+    #[serde(deserialize_with = "deserialize_key_value_map")]
+    #[merge(strategy = merge::vec::overwrite_empty)]
+    pub interpreters: Vec<String>,
+
+    /// Run each spec file's script blocks in one persistent shell, so that
+    /// `cd`, `export`, functions and aliases carry over between blocks
+    #[clap(long)]
+    #[merge(strategy = merge::bool::overwrite_false)]
+    pub session: bool,
+
     /// Number of parallel jobs to run (0 = all CPUs, default = 1 for backward compatibility)
     #[clap(short = 'j', long = "jobs", allow_hyphen_values = true, value_parser = clap::value_parser!(u32).range(0..))]
     #[merge(strategy = merge::option::overwrite_none)]
@@ -142,8 +226,8 @@ pub struct RunSettings {
     /// link cycles are handled safely and each file only runs once.
     ///
     /// This can also be enabled via a `specdown.toml` config file
-    /// (`follow_links = true`) in the current directory; either the flag or
-    /// the config file being set enables the behaviour.
+    /// (`follow_links = true`); either the flag or the config file being set
+    /// enables the behaviour.
     #[clap(long)]
     #[merge(strategy = merge::bool::overwrite_false)]
     pub follow_links: bool,
@@ -158,4 +242,56 @@ pub struct RunSettings {
     #[clap(long)]
     #[merge(strategy = merge::bool::overwrite_false)]
     pub workspace_per_spec: bool,
+
+    /// Show how long each spec file took, and list the N slowest functions
+    /// once every spec file has run
+    #[clap(long, value_name = "N")]
+    #[merge(strategy = merge::option::overwrite_none)]
+    pub durations: Option<usize>,
+
+    /// Only run the spec files which failed the last time they were run, as
+    /// recorded in `.specdown/last-run.json`, or every spec file if none did
+    #[clap(long, conflicts_with = "failed_first")]
+    #[serde(skip)]
+    #[merge(strategy = merge::bool::overwrite_false)]
+    pub last_failed: bool,
+
+    /// Run the spec files which failed the last time they were run before
+    /// the others
+    #[clap(long, conflicts_with_all = ["shuffle", "seed"])]
+    #[serde(skip)]
+    #[merge(strategy = merge::bool::overwrite_false)]
+    pub failed_first: bool,
+
+    /// Run the spec files in a random order, printing the seed used so that
+    /// the order can be reproduced with `--seed`
+    #[clap(long)]
+    #[merge(strategy = merge::bool::overwrite_false)]
+    pub shuffle: bool,
+
+    /// Run the spec files in the random order given by a seed printed by
+    /// `--shuffle`
+    #[clap(long, value_name = "N")]
+    #[merge(strategy = merge::option::overwrite_none)]
+    pub seed: Option<u64>,
+
+    /// Only run shard K of N, splitting the spec files between N machines
+    /// so that each runs a different part of the suite
+    #[clap(long, value_name = "K/N")]
+    #[serde(skip)]
+    #[merge(strategy = merge::option::overwrite_none)]
+    pub shard: Option<Shard>,
+
+    /// Split the spec files between shards by how long each took the last
+    /// time it was run, as recorded in `.specdown/last-run.json`, so every
+    /// shard takes about as long
+    #[clap(long, requires = "shard")]
+    #[merge(strategy = merge::bool::overwrite_false)]
+    pub balance_shards: bool,
+
+    /// Only run the spec files with this tag in their front matter
+    /// (repeatable)
+    #[clap(long = "tag", value_name = "TAG")]
+    #[merge(strategy = merge::vec::overwrite_empty)]
+    pub tags: Vec<String>,
 }
@@ -0,0 +1,154 @@
+//! Splitting the spec files to run between several machines with
+//! `--shard K/N`.
+
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use super::last_run::LastRun;
+
+/// One of `count` groups of spec files, numbered from 1.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct Shard {
+    index: usize,
+    count: usize,
+}
+
+impl FromStr for Shard {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("'{s}' is not a shard of the form K/N, where 1 <= K <= N");
+        let (index, count) = s.split_once('/').ok_or_else(invalid)?;
+        let index: usize = index.trim().parse().map_err(|_| invalid())?;
+        let count: usize = count.trim().parse().map_err(|_| invalid())?;
+        if index == 0 || index > count {
+            return Err(invalid());
+        }
+        Ok(Self { index, count })
+    }
+}
+
+impl Shard {
+    /// The spec files in this shard, in the order they were given.
+    ///
+    /// Without `timings`, files are dealt out in turn: the first file to
+    /// shard 1, the second to shard 2, and so on. With them, each file is
+    /// given to the shard with the least total duration so far, slowest file
+    /// first, so every shard takes about as long. Files without a recorded
+    /// duration are assumed to take the average time.
+    ///
+    /// Either way, every machine given the same spec files (and the same
+    /// timings) agrees on which shard each file is in.
+    pub fn select(self, spec_files: Vec<PathBuf>, timings: Option<&LastRun>) -> Vec<PathBuf> {
+        let shards = match timings {
+            Some(timings) => self.balanced(&spec_files, timings),
+            None => (0..spec_files.len()).map(|i| i % self.count).collect(),
+        };
+
+        spec_files
+            .into_iter()
+            .zip(shards)
+            .filter(|(_, shard)| *shard == self.index - 1)
+            .map(|(spec_file, _)| spec_file)
+            .collect()
+    }
+
+    /// The shard, counting from 0, each spec file is given to when balancing
+    /// by duration.
+    fn balanced(self, spec_files: &[PathBuf], timings: &LastRun) -> Vec<usize> {
+        let durations: Vec<Option<f64>> = spec_files
+            .iter()
+            .map(|spec_file| timings.duration(spec_file))
+            .collect();
+        let known: Vec<f64> = durations.iter().flatten().copied().collect();
+        #[allow(clippy::cast_precision_loss)]
+        let average = if known.is_empty() {
+            1.0
+        } else {
+            known.iter().sum::<f64>() / known.len() as f64
+        };
+        let durations: Vec<f64> = durations
+            .into_iter()
+            .map(|duration| duration.unwrap_or(average))
+            .collect();
+
+        // Slowest first; ties keep the order the files were given in.
+        let mut order: Vec<usize> = (0..spec_files.len()).collect();
+        order.sort_by(|a, b| durations[*b].total_cmp(&durations[*a]).then(a.cmp(b)));
+
+        let mut totals = vec![0.0_f64; self.count];
+        let mut shards = vec![0; spec_files.len()];
+        for file in order {
+            let shard = (0..self.count)
+                .min_by(|a, b| totals[*a].total_cmp(&totals[*b]).then(a.cmp(b)))
+                .unwrap_or_default();
+            totals[shard] += durations[file];
+            shards[file] = shard;
+        }
+        shards
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::Shard;
+    use crate::commands::run::last_run::{LastRun, SpecFileRecord};
+
+    fn files(names: &[&str]) -> Vec<PathBuf> {
+        names.iter().map(PathBuf::from).collect()
+    }
+
+    fn shard(s: &str) -> Shard {
+        s.parse().expect("shard to parse")
+    }
+
+    #[test]
+    fn parses_index_and_count() {
+        assert_eq!(shard("2/3"), Shard { index: 2, count: 3 });
+        assert!("0/3".parse::<Shard>().is_err());
+        assert!("4/3".parse::<Shard>().is_err());
+        assert!("3".parse::<Shard>().is_err());
+        assert!("a/b".parse::<Shard>().is_err());
+    }
+
+    #[test]
+    fn deals_files_out_in_turn() {
+        let spec_files = files(&["a.md", "b.md", "c.md", "d.md", "e.md"]);
+
+        assert_eq!(
+            shard("1/2").select(spec_files.clone(), None),
+            files(&["a.md", "c.md", "e.md"])
+        );
+        assert_eq!(
+            shard("2/2").select(spec_files, None),
+            files(&["b.md", "d.md"])
+        );
+    }
+
+    #[test]
+    fn balances_files_by_their_last_duration() {
+        let mut timings = LastRun::default();
+        for (name, duration) in [("a.md", 1.0), ("b.md", 8.0), ("c.md", 3.0), ("d.md", 4.0)] {
+            timings.spec_files.insert(
+                name.to_string(),
+                SpecFileRecord {
+                    duration,
+                    ..SpecFileRecord::default()
+                },
+            );
+        }
+        let spec_files = files(&["a.md", "b.md", "c.md", "d.md", "e.md"]);
+
+        // e.md has no timing, so is assumed to take the average of 4s.
+        assert_eq!(
+            shard("1/2").select(spec_files.clone(), Some(&timings)),
+            files(&["b.md", "c.md"])
+        );
+        assert_eq!(
+            shard("2/2").select(spec_files, Some(&timings)),
+            files(&["a.md", "d.md", "e.md"])
+        );
+    }
+}
@@ -0,0 +1,67 @@
+//! Running spec files in a random, but reproducible, order with `--shuffle`
+//! and `--seed`.
+
+use std::convert::TryFrom;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Shuffles `items` with a Fisher-Yates shuffle driven by `SplitMix64`. It
+/// is implemented here rather than taken from a crate so that a seed gives
+/// the same order on every platform and in every version of specdown.
+pub fn shuffle<T>(items: &mut [T], seed: u64) {
+    let mut state = seed;
+    for i in (1..items.len()).rev() {
+        let bound = u64::try_from(i + 1).unwrap_or(u64::MAX);
+        let j = usize::try_from(next(&mut state) % bound).unwrap_or_default();
+        items.swap(i, j);
+    }
+}
+
+/// A seed for when `--shuffle` is given without `--seed`. It is kept short
+/// so it is easy to copy from the output.
+pub fn random_seed() -> u64 {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_nanos());
+    let mut state = u64::try_from(nanos % u128::from(u64::MAX)).unwrap_or_default()
+        ^ u64::from(std::process::id());
+    next(&mut state) % 1_000_000
+}
+
+/// The next number from a `SplitMix64` generator.
+fn next(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::shuffle;
+
+    fn shuffled(seed: u64) -> Vec<u32> {
+        let mut items: Vec<u32> = (1..=8).collect();
+        shuffle(&mut items, seed);
+        items
+    }
+
+    #[test]
+    fn gives_the_same_order_for_the_same_seed() {
+        assert_eq!(shuffled(42), shuffled(42));
+        assert_eq!(shuffled(42), vec![4, 2, 7, 3, 5, 1, 8, 6]);
+    }
+
+    #[test]
+    fn gives_different_orders_for_different_seeds() {
+        assert_ne!(shuffled(1), shuffled(2));
+    }
+
+    #[test]
+    fn keeps_every_item() {
+        let mut items = shuffled(7);
+        items.sort_unstable();
+
+        assert_eq!(items, (1..=8).collect::<Vec<u32>>());
+    }
+}
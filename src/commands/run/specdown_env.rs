@@ -1,5 +1,8 @@
 use std::path::Path;
 
+/// The variable holding the workspace directory.
+pub const WORKSPACE_DIR: &str = "SPECDOWN_WORKSPACE_DIR";
+
 /// Builds the `SPECDOWN_*` environment variables passed to spec scripts.
 ///
 /// * `start_dir` - the directory `specdown` was invoked from.
@@ -10,10 +13,7 @@ use std::path::Path;
 pub fn build(start_dir: &Path, workspace_dir: &Path, working_dir: &Path) -> Vec<(String, String)> {
     vec![
         ("SPECDOWN_START_DIR".to_string(), path_to_string(start_dir)),
-        (
-            "SPECDOWN_WORKSPACE_DIR".to_string(),
-            path_to_string(workspace_dir),
-        ),
+        (WORKSPACE_DIR.to_string(), path_to_string(workspace_dir)),
         (
             "SPECDOWN_WORKING_DIR".to_string(),
             path_to_string(working_dir),
@@ -8,10 +8,15 @@ pub struct Arguments {
     /// The spec file to strip specdown functions from
     #[clap()]
     spec_file: PathBuf,
+
+    /// Keep the front matter holding the spec file's settings, rather than
+    /// removing it
+    #[clap(long)]
+    keep_front_matter: bool,
 }
 
 pub fn execute(args: &Arguments) {
     let contents = fs::read_to_string(&args.spec_file).expect("failed to read spec file");
-    let stripped = parsers::strip(&contents);
+    let stripped = parsers::strip(&contents, args.keep_front_matter);
     println!("{stripped}");
 }
@@ -2,5 +2,6 @@ use std::path::PathBuf;
 
 pub struct Config {
     pub colour: bool,
-    pub config_path: Option<PathBuf>,
+    pub file: Option<PathBuf>,
+    pub profile: Option<String>,
 }
@@ -0,0 +1,300 @@
+//! Running spec files from other Rust programs.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::commands::run::file_reader::FileReader;
+use crate::commands::run::run_command::{RunCommand, RunMode};
+use crate::commands::run::{specdown_env, DEFAULT_SHELL_COMMAND};
+use crate::results::{ActionResult, Printer};
+use crate::runner::shell_executor::ShellExecutor;
+use crate::runner::{Error, Executor, RunEvent};
+use crate::types::ScriptCode;
+
+/// Runs spec files, configured with a builder.
+///
+/// By default scripts are run with `bash -c` in the current directory and
+/// nothing is printed; the results are returned as a [`RunReport`].
+///
+/// ```no_run
+/// use specdown::{BasicPrinter, Specdown};
+///
+/// let report = Specdown::new()
+///     .printer(BasicPrinter::new(true))
+///     .run(["README.md"])
+///     .expect("specs to run");
+///
+/// assert!(report.success());
+/// ```
+pub struct Specdown {
+    executor: Option<Box<dyn Executor>>,
+    printer: Option<Box<dyn Printer>>,
+    working_dir: Option<PathBuf>,
+    workspace_init_command: Option<String>,
+    jobs: usize,
+    session: bool,
+    seed: Option<u64>,
+}
+
+impl Specdown {
+    /// Creates a builder with the default settings.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            executor: None,
+            printer: None,
+            working_dir: None,
+            workspace_init_command: None,
+            jobs: 1,
+            session: false,
+            seed: None,
+        }
+    }
+
+    /// Sets the executor scripts are run with, instead of `bash -c`.
+    ///
+    /// A custom executor is responsible for running scripts in the working
+    /// directory; the builder's working directory is only used for files
+    /// created by `file()` blocks and listed by `verify_tree()` blocks.
+    #[must_use]
+    pub fn executor(mut self, executor: impl Executor + 'static) -> Self {
+        self.executor = Some(Box::new(executor));
+        self
+    }
+
+    /// Sets the printer results are reported to as each spec file finishes.
+    #[must_use]
+    pub fn printer(mut self, printer: impl Printer + 'static) -> Self {
+        self.printer = Some(Box::new(printer));
+        self
+    }
+
+    /// Sets the directory scripts are run in. Defaults to the current
+    /// directory.
+    #[must_use]
+    pub fn working_dir(mut self, working_dir: impl Into<PathBuf>) -> Self {
+        self.working_dir = Some(working_dir.into());
+        self
+    }
+
+    /// Sets a command to run in the working directory before any spec file.
+    #[must_use]
+    pub fn workspace_init_command(mut self, command: impl Into<String>) -> Self {
+        self.workspace_init_command = Some(command.into());
+        self
+    }
+
+    /// Sets the number of spec files run in parallel. Running in parallel
+    /// requires an executor which implements [`Executor::clone_box`].
+    #[must_use]
+    pub fn jobs(mut self, jobs: usize) -> Self {
+        self.jobs = jobs.max(1);
+        self
+    }
+
+    /// Runs every spec file in a persistent shell session, as `--session`
+    /// does.
+    #[must_use]
+    pub fn session(mut self, session: bool) -> Self {
+        self.session = session;
+        self
+    }
+
+    /// Runs the spec files in an order shuffled with `seed`, as `--seed`
+    /// does, rather than the order they were given in.
+    #[must_use]
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Runs the given spec files in order, unless a [`seed`](Self::seed) is
+    /// set, and returns their results.
+    ///
+    /// Relative spec file paths are resolved from the current directory.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the default shell executor can not be created, or
+    /// the [workspace init command](Self::workspace_init_command) can not be
+    /// run. Errors which occur while running a spec file are reported in its
+    /// [`SpecFileReport`] instead.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the current directory can not be read.
+    pub fn run<I, P>(self, spec_files: I) -> Result<RunReport, Error>
+    where
+        I: IntoIterator<Item = P>,
+        P: AsRef<Path>,
+    {
+        let current_dir =
+            std::env::current_dir().expect("Failed to get current workspace directory");
+        let working_dir = self.working_dir.unwrap_or_else(|| current_dir.clone());
+
+        let executor = if let Some(executor) = self.executor {
+            executor
+        } else {
+            let env = specdown_env::build(&current_dir, &working_dir, &working_dir);
+            Box::new(
+                ShellExecutor::new::<PathBuf>(DEFAULT_SHELL_COMMAND, &env, &[], &[])?
+                    .with_working_dir(working_dir.clone()),
+            )
+        };
+
+        if let Some(command) = self.workspace_init_command {
+            executor.execute(&ScriptCode(command))?;
+        }
+
+        let command = RunCommand {
+            spec_files: spec_files
+                .into_iter()
+                .map(|path| path.as_ref().to_path_buf())
+                .collect(),
+            run_mode: RunMode::SharedWorkspace {
+                executor,
+                workspace_dir: working_dir.clone(),
+                start_dir: current_dir.clone(),
+                working_dir,
+            },
+            workspace_init_command: None,
+            file_reader: FileReader::new(current_dir),
+            jobs: self.jobs,
+            session: self.session,
+            seed: self.seed,
+            overrides: HashMap::new(),
+        };
+
+        let printer = Mutex::new(
+            self.printer
+                .unwrap_or_else(|| Box::new(SilentPrinter) as Box<dyn Printer>),
+        );
+
+        Ok(RunReport::from_events(
+            command.execute_with_printer(&printer),
+        ))
+    }
+}
+
+impl Default for Specdown {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for Specdown {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Specdown")
+            .field("working_dir", &self.working_dir)
+            .field("workspace_init_command", &self.workspace_init_command)
+            .field("jobs", &self.jobs)
+            .field("session", &self.session)
+            .field("seed", &self.seed)
+            .finish_non_exhaustive()
+    }
+}
+
+/// The results of [`Specdown::run`], one report per spec file.
+#[derive(Clone, Debug, Default)]
+pub struct RunReport {
+    /// The report for each spec file, in the order the files were given.
+    pub spec_files: Vec<SpecFileReport>,
+}
+
+impl RunReport {
+    /// Groups a run's events by the spec file they belong to.
+    #[must_use]
+    pub fn from_events(events: Vec<RunEvent>) -> Self {
+        let mut spec_files: Vec<SpecFileReport> = Vec::new();
+
+        for event in events {
+            match event {
+                RunEvent::SpecFileStarted(path) => spec_files.push(SpecFileReport {
+                    path,
+                    results: Vec::new(),
+                    durations: Vec::new(),
+                    errors: Vec::new(),
+                    duration: Duration::ZERO,
+                    skipped: None,
+                    success: true,
+                }),
+                RunEvent::SpecFileSkipped { path, reason } => spec_files.push(SpecFileReport {
+                    path,
+                    results: Vec::new(),
+                    durations: Vec::new(),
+                    errors: Vec::new(),
+                    duration: Duration::ZERO,
+                    skipped: Some(reason),
+                    success: true,
+                }),
+                RunEvent::TestCompleted(result, duration) => {
+                    if let Some(report) = spec_files.last_mut() {
+                        report.results.push(result);
+                        report.durations.push(duration);
+                    }
+                }
+                RunEvent::ErrorOccurred(error) => {
+                    if let Some(report) = spec_files.last_mut() {
+                        report.errors.push(error);
+                    }
+                }
+                RunEvent::SpecFileCompleted { success, duration } => {
+                    if let Some(report) = spec_files.last_mut() {
+                        report.success = success;
+                        report.duration = duration;
+                    }
+                }
+            }
+        }
+
+        Self { spec_files }
+    }
+
+    /// Whether every spec file ran without any failures or errors.
+    #[must_use]
+    pub fn success(&self) -> bool {
+        self.spec_files.iter().all(SpecFileReport::success)
+    }
+}
+
+/// The results of running a single spec file.
+#[derive(Clone, Debug)]
+pub struct SpecFileReport {
+    /// The spec file's path, as it was given to [`Specdown::run`].
+    pub path: PathBuf,
+    /// The result of each function run, in the order they were run.
+    pub results: Vec<ActionResult>,
+    /// How long each function took to run, in the same order as `results`.
+    pub durations: Vec<Duration>,
+    /// Errors which stopped the spec file, or a function in it, from running.
+    pub errors: Vec<Error>,
+    /// How long the whole spec file took to run.
+    pub duration: Duration,
+    /// Why the spec file wasn't run, if its front matter said to skip it.
+    pub skipped: Option<String>,
+    success: bool,
+}
+
+impl SpecFileReport {
+    /// Whether every function in the spec file succeeded and no errors
+    /// occurred.
+    #[must_use]
+    pub fn success(&self) -> bool {
+        self.success && self.errors.is_empty()
+    }
+
+    /// The results of the functions which failed.
+    pub fn failures(&self) -> impl Iterator<Item = &ActionResult> {
+        self.results.iter().filter(|result| !result.success())
+    }
+}
+
+/// The printer used when none is configured.
+struct SilentPrinter;
+
+impl Printer for SilentPrinter {
+    fn print(&mut self, _event: &RunEvent) {}
+}
@@ -0,0 +1,102 @@
+//! Running spec files as `cargo test` tests.
+//!
+//! Each spec file becomes a test named after its path, so the usual test
+//! filtering, `--nocapture`, `--test-threads` and reporting all apply. Add a
+//! test target without the default harness to `Cargo.toml`:
+//!
+//! ```toml
+//! [[test]]
+//! name = "docs"
+//! harness = false
+//! ```
+//!
+//! and list the spec files in `tests/docs.rs`:
+//!
+//! ```ignore
+//! specdown::harness!("README.md", "docs/getting_started.md");
+//! ```
+//!
+//! Tests run in parallel, so every spec file is run in its own temporary
+//! workspace directory. Use [`run`] to configure how spec files are run.
+
+use std::io::IsTerminal;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use libtest_mimic::{Arguments, Failed, Trial};
+
+use crate::results::basic_printer::BasicPrinter;
+use crate::Specdown;
+
+/// Defines a `main` function which runs each of the given spec files as a
+/// test, with the default [`Specdown`] settings.
+#[macro_export]
+macro_rules! harness {
+    ($($spec_file:expr),+ $(,)?) => {
+        fn main() {
+            $crate::harness::run(&[$($spec_file),+], |workspace| {
+                $crate::Specdown::new().working_dir(workspace)
+            });
+        }
+    };
+}
+
+/// Runs each spec file as a test and exits with the result.
+///
+/// `configure` is called once per spec file with a fresh temporary
+/// workspace directory, and returns the [`Specdown`] used to run it. The
+/// printer is always replaced: output is captured and shown when the test
+/// fails, or printed as it happens with `--nocapture`.
+pub fn run<P, F>(spec_files: &[P], configure: F) -> !
+where
+    P: AsRef<Path>,
+    F: Fn(&Path) -> Specdown + Send + Sync + 'static,
+{
+    let args = Arguments::from_args();
+    let configure = Arc::new(configure);
+
+    let trials = spec_files
+        .iter()
+        .map(|spec_file| {
+            let spec_file = spec_file.as_ref().to_path_buf();
+            let configure = Arc::clone(&configure);
+            let nocapture = args.nocapture;
+            Trial::test(spec_file.display().to_string(), move || {
+                run_spec_file(&spec_file, configure.as_ref(), nocapture)
+            })
+        })
+        .collect();
+
+    libtest_mimic::run(&args, trials).exit()
+}
+
+fn run_spec_file(
+    spec_file: &Path,
+    configure: &dyn Fn(&Path) -> Specdown,
+    nocapture: bool,
+) -> Result<(), Failed> {
+    let workspace = tempfile::tempdir()?;
+    let output = Arc::new(Mutex::new(String::new()));
+
+    let printer = if nocapture {
+        BasicPrinter::new(std::io::stdout().is_terminal())
+    } else {
+        let output = Arc::clone(&output);
+        BasicPrinter::with_display(false, move |line| {
+            let mut output = output.lock().expect("output mutex poisoned");
+            output.push_str(line);
+            output.push('\n');
+        })
+    };
+
+    let report = configure(workspace.path())
+        .printer(printer)
+        .run([spec_file])?;
+
+    if report.success() {
+        Ok(())
+    } else {
+        let output = output.lock().expect("output mutex poisoned");
+        Err(Failed::from(output.trim_end()))
+    }
+}
@@ -0,0 +1,61 @@
+//! A tool to test markdown files and drive development from documentation
+//!
+//! As well as the `specdown` command line tool, the crate can be used to run
+//! spec files from other Rust programs with [`Specdown`]. Custom
+//! [`Executor`]s and [`Printer`]s can be plugged in to change how scripts
+//! are run and how results are reported.
+//!
+//! For finer control, [`parse`] turns a spec file's Markdown into
+//! [`Action`]s, and a [`Runner`] runs them, recording their results in a
+//! [`State`].
+
+#![warn(
+    rust_2018_idioms,
+    unused,
+    rust_2021_compatibility,
+    nonstandard_style,
+    future_incompatible,
+    missing_copy_implementations,
+    missing_debug_implementations,
+    missing_docs
+)]
+
+mod ansi;
+mod cli;
+mod commands;
+mod config;
+mod embedding;
+mod exit_codes;
+#[cfg(feature = "harness")]
+pub mod harness;
+mod parsers;
+mod results;
+mod runner;
+mod types;
+mod workspace;
+
+pub use embedding::{RunReport, SpecFileReport, Specdown};
+pub use parsers::{parse, Error as ParseError, FunctionError, MarkdownError};
+pub use results::basic_printer::BasicPrinter;
+pub use results::{
+    ActionError, ActionResult, BackgroundExitStatus, BackgroundStartResult, BackgroundStopResult,
+    ConsoleCommandResult, CreateFileResult, Mask, MockCommandResult, Printer, ResponseResult,
+    ResponseStatus, ScriptResult, VerifyMockCommandResult, VerifyResult, VerifyTreeResult,
+};
+pub use runner::{
+    BackgroundHandle, Error, Executor, Output, RunEvent, Runner, ScriptOutput, State,
+};
+pub use types::{
+    Action, BackgroundAction, ConsoleCommandAction, CreateFileAction, DelayMillis,
+    DelayMillisError, ExitCode, FileContent, FilePath, Interpreter, MockCommandAction, MockName,
+    OutputExpectation, ReadyWhen, ResponseAction, ResponseBody, ResponseHeader, ScriptAction,
+    ScriptCode, ScriptName, Source, StatusCode, StatusCodeError, Stream, TargetOs, TreeDetails,
+    VerifyAction, VerifyMockCommandAction, VerifyTreeAction, VerifyValue,
+    DEFAULT_READY_WHEN_TIMEOUT_SECS, MAX_DELAY_MILLIS,
+};
+
+/// Runs the `specdown` command line tool with the arguments the process was
+/// started with, as the `specdown` binary does.
+pub fn run_cli() {
+    cli::run();
+}
@@ -1,18 +1,25 @@
 use crate::parsers::code_block_type::{
-    BackgroundCodeBlock, CodeBlockType, ScriptCodeBlock, VerifyCodeBlock,
+    BackgroundCodeBlock, CodeBlockType, MockCommandCodeBlock, ScriptCodeBlock, VerifyCodeBlock,
+    VerifyTreeCodeBlock,
 };
 use crate::types::{
-    Action, BackgroundAction, CreateFileAction, FileContent, ResponseAction, ResponseBody,
-    ResponseCodeBlock, ResponseHeader, ScriptAction, ScriptCode, TargetOs, VerifyAction,
-    VerifyValue,
+    Action, BackgroundAction, CreateFileAction, FileContent, Interpreter, MockCommandAction,
+    ResponseAction, ResponseBody, ResponseCodeBlock, ResponseHeader, ScriptAction, ScriptCode,
+    TargetOs, VerifyAction, VerifyMockCommandAction, VerifyTreeAction, VerifyValue,
 };
 use std::env::consts::OS;
 
-pub fn create_action(code_block_type: &CodeBlockType, literal: String) -> Option<Action> {
+pub fn create_action(
+    code_block_type: &CodeBlockType,
+    language: &str,
+    literal: String,
+) -> Option<Action> {
     match code_block_type {
-        CodeBlockType::Script(script_code_block) => {
-            Some(Action::Script(to_script_action(script_code_block, literal)))
-        }
+        CodeBlockType::Script(script_code_block) => Some(Action::Script(to_script_action(
+            script_code_block,
+            language,
+            literal,
+        ))),
         CodeBlockType::Verify(verify_code_block) => {
             to_verify_action(verify_code_block, literal).map(Action::Verify)
         }
@@ -27,22 +34,86 @@ pub fn create_action(code_block_type: &CodeBlockType, literal: String) -> Option
             response_code_block,
             literal,
         ))),
+        CodeBlockType::VerifyTree(verify_tree_code_block) => Some(Action::VerifyTree(
+            to_verify_tree_action(verify_tree_code_block, literal),
+        )),
+        CodeBlockType::MockCommand(mock_command_code_block) => Some(Action::MockCommand(
+            to_mock_command_action(mock_command_code_block, literal),
+        )),
+        CodeBlockType::VerifyMockCommand(name) => {
+            Some(Action::VerifyMockCommand(VerifyMockCommandAction {
+                name: name.clone(),
+                expected_value: VerifyValue(literal),
+            }))
+        }
+        CodeBlockType::Session(script_code_block) => Some(Action::Session(to_script_action(
+            script_code_block,
+            language,
+            literal,
+        ))),
         CodeBlockType::Skip() => None,
     }
 }
 
-fn to_script_action(code_block: &ScriptCodeBlock, literal: String) -> ScriptAction {
+fn to_verify_tree_action(code_block: &VerifyTreeCodeBlock, literal: String) -> VerifyTreeAction {
+    let VerifyTreeCodeBlock {
+        path,
+        include,
+        exclude,
+        details,
+    } = code_block;
+
+    VerifyTreeAction {
+        path: path.clone(),
+        include: include.clone(),
+        exclude: exclude.clone(),
+        details: *details,
+        expected_value: VerifyValue(literal),
+    }
+}
+
+fn to_mock_command_action(code_block: &MockCommandCodeBlock, literal: String) -> MockCommandAction {
+    let MockCommandCodeBlock {
+        name,
+        args,
+        stream,
+        exit_code,
+    } = code_block;
+
+    MockCommandAction {
+        name: name.clone(),
+        args: args.clone(),
+        stream: *stream,
+        exit_code: *exit_code,
+        output: literal,
+    }
+}
+
+fn to_script_action(code_block: &ScriptCodeBlock, language: &str, literal: String) -> ScriptAction {
     let ScriptCodeBlock {
         script_name,
         expected_exit_code,
         expected_output,
+        interpreter,
     } = code_block;
 
     ScriptAction {
         script_name: script_name.clone(),
         script_code: ScriptCode(literal),
         expected_exit_code: *expected_exit_code,
-        expected_output: expected_output.clone(),
+        expected_output: *expected_output,
+        interpreter: to_interpreter(language, interpreter.as_deref()),
+    }
+}
+
+/// An explicit `interpreter` argument always wins; otherwise the code block
+/// language selects the interpreter (resolved against `[run.interpreters]`
+/// by the executor).
+fn to_interpreter(language: &str, interpreter: Option<&str>) -> Interpreter {
+    match interpreter {
+        Some(command) => Interpreter::Command(command.to_string()),
+        None if language.trim().is_empty() => Interpreter::Shell,
+        None => Interpreter::Language(language.trim().to_string()),
     }
 }
 
@@ -71,10 +142,7 @@ fn to_response_action(code_block: &ResponseCodeBlock, literal: String) -> Respon
         body: _,
     } = code_block;
 
-    let mut decoded_headers = headers
-        .as_deref()
-        .map(decode_response_headers)
-        .unwrap_or_default();
+    let mut decoded_headers = headers.clone();
 
     if let Some(ct) = content_type {
         let has_content_type = decoded_headers.iter().any(|h| h.name == "content-type");
@@ -103,20 +171,6 @@ fn to_response_action(code_block: &ResponseCodeBlock, literal: String) -> Respon
     }
 }
 
-fn decode_response_headers(raw: &str) -> Vec<ResponseHeader> {
-    raw.split(';')
-        .map(str::trim)
-        .filter(|entry| !entry.is_empty())
-        .filter_map(|entry| {
-            let (name, value) = entry.split_once(':')?;
-            Some(ResponseHeader {
-                name: name.trim().to_lowercase(),
-                value: value.trim().to_string(),
-            })
-        })
-        .collect()
-}
-
 fn to_verify_action(
     VerifyCodeBlock { source, target_os }: &VerifyCodeBlock,
     literal: String,
@@ -155,9 +209,9 @@ mod tests {
     use crate::parsers::code_block_type::VerifyCodeBlock;
     use crate::types::BackgroundAction;
     use crate::types::{
-        CreateFileAction, FilePath, MockName, OutputExpectation, ReadyWhen, ResponseAction,
-        ResponseBody, ResponseHeader, ScriptAction, ScriptName, Source, Stream, TargetOs,
-        VerifyAction,
+        CreateFileAction, FilePath, Interpreter, MockName, OutputExpectation, ReadyWhen,
+        ResponseAction, ResponseBody, ResponseHeader, ScriptAction, ScriptName, Source, Stream,
+        TargetOs, VerifyAction,
     };
 
     #[test]
@@ -168,7 +222,9 @@ mod tests {
                     script_name: Some(ScriptName("script-name".to_string())),
                     expected_exit_code: None,
                     expected_output: OutputExpectation::Any,
+                    interpreter: None,
                 }),
+                "shell",
                 "code".to_string(),
             ),
             Some(Action::Script(ScriptAction {
@@ -176,6 +232,30 @@ mod tests {
                 script_code: ScriptCode("code".to_string()),
                 expected_exit_code: None,
                 expected_output: OutputExpectation::Any,
+                interpreter: Interpreter::Language("shell".to_string()),
+            }))
+        );
+    }
+
+    #[test]
+    fn create_action_for_session() {
+        assert_eq!(
+            create_action(
+                &CodeBlockType::Session(ScriptCodeBlock {
+                    script_name: Some(ScriptName("script-name".to_string())),
+                    expected_exit_code: None,
+                    expected_output: OutputExpectation::Any,
+                    interpreter: None,
+                }),
+                "shell",
+                "code".to_string(),
+            ),
+            Some(Action::Session(ScriptAction {
+                script_name: Some(ScriptName("script-name".to_string())),
+                script_code: ScriptCode("code".to_string()),
+                expected_exit_code: None,
+                expected_output: OutputExpectation::Any,
+                interpreter: Interpreter::Language("shell".to_string()),
             }))
         );
     }
@@ -191,6 +271,7 @@ mod tests {
                     },
                     target_os: None,
                 }),
+                "shell",
                 "value".to_string(),
             ),
             Some(Action::Verify(VerifyAction {
@@ -214,6 +295,7 @@ mod tests {
                     },
                     target_os: Some(TargetOs("fake-os".to_string())),
                 }),
+                "shell",
                 "value".to_string(),
             ),
             None
@@ -231,6 +313,7 @@ mod tests {
                     },
                     target_os: Some(TargetOs("!fake-os".to_string())),
                 }),
+                "shell",
                 "value".to_string(),
             ),
             Some(Action::Verify(VerifyAction {
@@ -248,6 +331,7 @@ mod tests {
         assert_eq!(
             create_action(
                 &CodeBlockType::CreateFile(FilePath("file.txt".to_string())),
+                "shell",
                 "content".to_string(),
             ),
             Some(Action::CreateFile(CreateFileAction {
@@ -266,6 +350,7 @@ mod tests {
                     ready_when: None,
                     timeout_secs: None,
                 }),
+                "shell",
                 "code".to_string(),
             ),
             Some(Action::Background(BackgroundAction {
@@ -286,6 +371,7 @@ mod tests {
                     ready_when: None,
                     timeout_secs: None,
                 }),
+                "shell",
                 "code".to_string(),
             ),
             Some(Action::Background(BackgroundAction {
@@ -306,6 +392,7 @@ mod tests {
                     ready_when: Some(ReadyWhen::FileExists(FilePath("/tmp/ready".to_string()))),
                     timeout_secs: None,
                 }),
+                "shell",
                 "code".to_string(),
             ),
             Some(Action::Background(BackgroundAction {
@@ -326,6 +413,7 @@ mod tests {
                     ready_when: Some(ReadyWhen::PortOpen(8080)),
                     timeout_secs: Some(5),
                 }),
+                "shell",
                 "code".to_string(),
             ),
             Some(Action::Background(BackgroundAction {
@@ -337,10 +425,70 @@ mod tests {
         );
     }
 
+    #[test]
+    fn create_action_for_verify_tree() {
+        use crate::parsers::code_block_type::VerifyTreeCodeBlock;
+        use crate::types::{TreeDetails, VerifyTreeAction};
+
+        assert_eq!(
+            create_action(
+                &CodeBlockType::VerifyTree(VerifyTreeCodeBlock {
+                    path: FilePath("out".to_string()),
+                    include: vec!["*.txt".to_string()],
+                    exclude: vec![],
+                    details: TreeDetails {
+                        size: true,
+                        mode: false,
+                        hash: false,
+                    },
+                }),
+                "shell",
+                "a.txt  size=1\n".to_string(),
+            ),
+            Some(Action::VerifyTree(VerifyTreeAction {
+                path: FilePath("out".to_string()),
+                include: vec!["*.txt".to_string()],
+                exclude: vec![],
+                details: TreeDetails {
+                    size: true,
+                    mode: false,
+                    hash: false,
+                },
+                expected_value: VerifyValue("a.txt  size=1\n".to_string()),
+            }))
+        );
+    }
+
+    #[test]
+    fn create_action_for_mock_command() {
+        use crate::parsers::code_block_type::MockCommandCodeBlock;
+        use crate::types::{ExitCode, MockCommandAction};
+
+        assert_eq!(
+            create_action(
+                &CodeBlockType::MockCommand(MockCommandCodeBlock {
+                    name: "git".to_string(),
+                    args: Some("status *".to_string()),
+                    stream: Stream::StdErr,
+                    exit_code: ExitCode(128),
+                }),
+                "text",
+                "fatal: not a git repository\n".to_string(),
+            ),
+            Some(Action::MockCommand(MockCommandAction {
+                name: "git".to_string(),
+                args: Some("status *".to_string()),
+                stream: Stream::StdErr,
+                exit_code: ExitCode(128),
+                output: "fatal: not a git repository\n".to_string(),
+            }))
+        );
+    }
+
     #[test]
     fn create_action_for_skip() {
         assert_eq!(
-            create_action(&CodeBlockType::Skip(), "content".to_string()),
+            create_action(&CodeBlockType::Skip(), "shell", "content".to_string()),
             None
         );
     }
@@ -354,11 +502,12 @@ mod tests {
                 &CodeBlockType::Response(ResponseCodeBlock {
                     name: MockName("my-mock".to_string()),
                     status: StatusCode(200),
-                    headers: None,
+                    headers: vec![],
                     content_type: None,
                     delay: DelayMillis(0),
                     body: ResponseBody::Empty,
                 }),
+                "shell",
                 "{\"hello\": \"world\"}".to_string(),
             ),
             Some(Action::Response(ResponseAction {
@@ -380,11 +529,12 @@ mod tests {
                 &CodeBlockType::Response(ResponseCodeBlock {
                     name: MockName("no-body".to_string()),
                     status: StatusCode(204),
-                    headers: None,
+                    headers: vec![],
                     content_type: None,
                     delay: DelayMillis(0),
                     body: ResponseBody::Empty,
                 }),
+                "shell",
                 String::new(),
             ),
             Some(Action::Response(ResponseAction {
@@ -406,11 +556,12 @@ mod tests {
                 &CodeBlockType::Response(ResponseCodeBlock {
                     name: MockName("json-mock".to_string()),
                     status: StatusCode(200),
-                    headers: None,
+                    headers: vec![],
                     content_type: Some("application/json".to_string()),
                     delay: DelayMillis(0),
                     body: ResponseBody::Empty,
                 }),
+                "shell",
                 "{}".to_string(),
             ),
             Some(Action::Response(ResponseAction {
@@ -435,11 +586,21 @@ mod tests {
                 &CodeBlockType::Response(ResponseCodeBlock {
                     name: MockName("hdr-mock".to_string()),
                     status: StatusCode(200),
-                    headers: Some("X-Custom:value; Content-Type:text/plain".to_string()),
+                    headers: vec![
+                        ResponseHeader {
+                            name: "x-custom".to_string(),
+                            value: "value".to_string(),
+                        },
+                        ResponseHeader {
+                            name: "content-type".to_string(),
+                            value: "text/plain".to_string(),
+                        },
+                    ],
                     content_type: Some("application/json".to_string()),
                     delay: DelayMillis(100),
                     body: ResponseBody::Empty,
                 }),
+                "shell",
                 "hello".to_string(),
             ),
             Some(Action::Response(ResponseAction {
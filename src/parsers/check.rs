@@ -0,0 +1,344 @@
+use std::collections::HashMap;
+use std::ops::Range;
+
+use super::diagnostic::Label;
+use super::{in_code_block, markdown, to_actions};
+use crate::types::{Action, ScriptName};
+
+/// A problem found in a spec file without running it.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Problem {
+    /// The line the problem was found on, counting from 1.
+    pub line: usize,
+    /// The bytes of the Markdown the problem is about, when it can be pinned
+    /// to part of a line, such as a misspelt argument.
+    pub span: Option<Range<usize>>,
+    /// A description of the problem.
+    pub message: String,
+}
+
+impl Problem {
+    fn new(line: usize, message: impl Into<String>) -> Self {
+        Self {
+            line,
+            span: None,
+            message: message.into(),
+        }
+    }
+}
+
+/// Checks every specdown function in `markdown` for mistakes which would
+/// only show up when the spec file is run, or not at all: functions or
+/// arguments which can't be parsed, arguments a function doesn't accept,
+/// `verify` blocks checking a script which hasn't run yet, duplicate script
+/// names and `response` blocks which nothing requests.
+///
+/// Problems are returned in line order.
+#[must_use]
+pub fn check(markdown: &str) -> Vec<Problem> {
+    let elements = match markdown::parse(markdown) {
+        Ok(elements) => elements,
+        Err(err) => return vec![Problem::new(err.line(), err.to_string())],
+    };
+
+    let mut problems = Vec::new();
+    let mut actions = Vec::new();
+
+    for element in &elements {
+        let markdown::Element::FencedCodeBlock { info, line, .. } = element;
+        if !info.contains(',') {
+            continue;
+        }
+
+        match to_actions(element) {
+            Ok(element_actions) => {
+                actions.extend(element_actions.into_iter().map(|action| (*line, action)));
+            }
+            Err(err) => {
+                let error = in_code_block(element, err);
+                problems.push(Problem {
+                    line: *line,
+                    span: error
+                        .label()
+                        .and_then(|label| locate(markdown, *line, info, &label)),
+                    message: error.to_string(),
+                });
+            }
+        }
+    }
+
+    problems.extend(check_actions(&actions));
+    problems.sort_by_key(|problem| problem.line);
+    problems
+}
+
+/// Finds the bytes of `markdown` a label in the info string of the code
+/// block on `line` covers. The function after the info string's first `,`
+/// is written the same in the Markdown, either on the fence's line or in the
+/// annotation comment before it.
+fn locate(markdown: &str, line: usize, info: &str, label: &Label) -> Option<Range<usize>> {
+    let function_start = info.find(',')? + 1;
+    let function = &info[function_start..];
+    let line_start: usize = markdown
+        .split_inclusive('\n')
+        .take(line - 1)
+        .map(str::len)
+        .sum();
+
+    let search_from = if markdown[line_start..].lines().next()?.contains(function) {
+        line_start
+    } else {
+        markdown[..line_start].rfind("<!--")?
+    };
+    let function_offset = search_from + markdown[search_from..].find(function)?;
+    let start = function_offset + label.start.checked_sub(function_start)?;
+    Some(start..start + label.length)
+}
+
+/// Checks the order of a spec file's actions, tracking the scripts defined
+/// so far the way the runner does.
+fn check_actions(actions: &[(usize, Action)]) -> Vec<Problem> {
+    let mut problems = Vec::new();
+    let mut script_lines: HashMap<&str, usize> = HashMap::new();
+    let mut any_script = false;
+
+    for (line, action) in actions {
+        match action {
+            Action::Script(script) | Action::Session(script) => {
+                any_script = true;
+                if let Some(ScriptName(name)) = &script.script_name {
+                    if let Some(first_line) = script_lines.get(name.as_str()) {
+                        problems.push(Problem::new(
+                            *line,
+                            format!(
+                                "Script name '{name}' is already used by the script on line {first_line}"
+                            ),
+                        ));
+                    } else {
+                        script_lines.insert(name, *line);
+                    }
+                }
+            }
+            Action::Verify(verify) => match &verify.source.name {
+                Some(ScriptName(name)) if !script_lines.contains_key(name.as_str()) => {
+                    problems.push(Problem::new(
+                        *line,
+                        format!("Script '{name}' is not defined before this verify block"),
+                    ));
+                }
+                None if !any_script => problems.push(Problem::new(
+                    *line,
+                    "There is no script before this verify block to verify",
+                )),
+                _ => {}
+            },
+            Action::Response(response) => problems.push(Problem::new(
+                *line,
+                format!(
+                    "Response '{}' has no request block consuming it",
+                    response.name.0
+                ),
+            )),
+            _ => {}
+        }
+    }
+
+    problems
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use super::{check, Problem};
+
+    fn problem(line: usize, message: &str) -> Problem {
+        Problem::new(line, message)
+    }
+
+    /// The problems found, without where on their line they are.
+    fn check_lines(markdown: &str) -> Vec<Problem> {
+        check(markdown)
+            .into_iter()
+            .map(|problem| Problem {
+                span: None,
+                ..problem
+            })
+            .collect()
+    }
+
+    #[test]
+    fn a_valid_spec_has_no_problems() {
+        let markdown = indoc! {r#"
+            ```shell,script(name="greet")
+            echo hello
+            ```
+
+            ```text,verify(script_name="greet")
+            hello
+            ```
+
+            ```text,verify()
+            hello
+            ```
+        "#};
+
+        assert_eq!(check(markdown), vec![]);
+    }
+
+    #[test]
+    fn reports_functions_which_can_not_be_parsed() {
+        let markdown = indoc! {r#"
+            # Title
+
+            ```shell,scirpt(name="greet")
+            echo hello
+            ```
+
+            ```shell,script(expected_exit_code="zero")
+            echo hello
+            ```
+        "#};
+
+        assert_eq!(
+            check_lines(markdown),
+            vec![
+                problem(3, "Unknown function: scirpt"),
+                problem(
+                    7,
+                    "Function script requires argument expected_exit_code to be a integer, got string"
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_arguments_a_function_does_not_accept_with_a_suggestion() {
+        let markdown = indoc! {r#"
+            ```shell,script(name="greet", expect_exit_code=1)
+            exit 1
+            ```
+        "#};
+
+        assert_eq!(
+            check_lines(markdown),
+            vec![problem(
+                1,
+                "Unknown argument `expect_exit_code` for function script, did you mean `expected_exit_code`?"
+            )]
+        );
+    }
+
+    #[test]
+    fn reports_verify_blocks_checking_scripts_defined_later_or_not_at_all() {
+        let markdown = indoc! {r#"
+            ```text,verify()
+            hello
+            ```
+
+            ```text,verify(script_name="greet")
+            hello
+            ```
+
+            ```shell,script(name="greet")
+            echo hello
+            ```
+
+            ```text,verify(script_name="gret")
+            hello
+            ```
+        "#};
+
+        assert_eq!(
+            check(markdown),
+            vec![
+                problem(1, "There is no script before this verify block to verify"),
+                problem(5, "Script 'greet' is not defined before this verify block"),
+                problem(13, "Script 'gret' is not defined before this verify block"),
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_duplicate_script_names() {
+        let markdown = indoc! {r#"
+            ```shell,script(name="greet")
+            echo hello
+            ```
+
+            ```shell,script(name="greet")
+            echo goodbye
+            ```
+        "#};
+
+        assert_eq!(
+            check(markdown),
+            vec![problem(
+                5,
+                "Script name 'greet' is already used by the script on line 1"
+            )]
+        );
+    }
+
+    #[test]
+    fn reports_response_blocks_with_no_consumer() {
+        let markdown = indoc! {r#"
+            ```json,response(name="users")
+            []
+            ```
+        "#};
+
+        assert_eq!(
+            check(markdown),
+            vec![problem(
+                1,
+                "Response 'users' has no request block consuming it"
+            )]
+        );
+    }
+
+    #[test]
+    fn reports_annotations_without_a_code_block() {
+        let markdown = "# Title\n\n<!-- specdown: script() -->\n\nText\n";
+
+        assert_eq!(
+            check(markdown),
+            vec![problem(
+                3,
+                "The annotation '<!-- specdown: script() -->' must be directly followed by a fenced code block"
+            )]
+        );
+    }
+
+    #[test]
+    fn locates_a_mistake_in_the_info_string() {
+        let markdown = "# Title\n\n```shell,script(name=\"greet\", expect_exit_code=1)\n```\n";
+
+        let span = check(markdown)[0]
+            .span
+            .clone()
+            .expect("problem to have a span");
+
+        assert_eq!(&markdown[span], "expect_exit_code");
+    }
+
+    #[test]
+    fn locates_a_mistake_in_an_annotation_comment() {
+        let markdown = indoc! {r#"
+            <!-- specdown: script(
+              name="greet",
+              expected_exit_code="zero",
+            ) -->
+            ```shell
+            echo hello
+            ```
+        "#};
+
+        let span = check(markdown)[0]
+            .span
+            .clone()
+            .expect("problem to have a span");
+
+        assert_eq!(&markdown[span], "\"zero\"");
+    }
+}
@@ -39,6 +39,24 @@ mod tests {
                             script_name: Some(ScriptName("example-script".to_string())),
                             expected_exit_code: None,
                             expected_output: OutputExpectation::Any,
+                            interpreter: None,
+                        }),
+                    })
+                );
+            }
+
+            #[test]
+            fn succeeds_when_function_is_session() {
+                let result = parse("shell,session(name=\"example-session\")");
+                assert_eq!(
+                    result,
+                    Ok(CodeBlockInfo {
+                        language: "shell".to_string(),
+                        extra: CodeBlockType::Session(ScriptCodeBlock {
+                            script_name: Some(ScriptName("example-session".to_string())),
+                            expected_exit_code: None,
+                            expected_output: OutputExpectation::Any,
+                            interpreter: None,
                         }),
                     })
                 );
@@ -55,6 +73,7 @@ mod tests {
                             script_name: None,
                             expected_exit_code: None,
                             expected_output: OutputExpectation::Any,
+                            interpreter: None,
                         }),
                     })
                 );
@@ -71,6 +90,7 @@ mod tests {
                             script_name: Some(ScriptName("example-script".to_string())),
                             expected_exit_code: Some(ExitCode(2)),
                             expected_output: OutputExpectation::Any,
+                            interpreter: None,
                         }),
                     })
                 );
@@ -87,6 +107,7 @@ mod tests {
                             script_name: Some(ScriptName("example-script".to_string())),
                             expected_exit_code: None,
                             expected_output: OutputExpectation::Any,
+                            interpreter: None,
                         }),
                     })
                 );
@@ -103,6 +124,7 @@ mod tests {
                             script_name: Some(ScriptName("example-script".to_string())),
                             expected_exit_code: None,
                             expected_output: OutputExpectation::StdOut,
+                            interpreter: None,
                         }),
                     })
                 );
@@ -282,7 +304,7 @@ mod tests {
             fn returns_an_error_when_there_is_no_comma_in_the_info_string() {
                 let result = parse("specdown");
                 assert!(result.is_err());
-                assert!(matches!(result, Err(Error::ParserFailed(_))));
+                assert!(matches!(result, Err(Error::ParserFailed { .. })));
             }
         }
 
@@ -321,6 +343,8 @@ mod tests {
 
         mod background {
             use crate::parsers::code_block_type::BackgroundCodeBlock;
+            use crate::parsers::error::Error;
+            use crate::parsers::function_string_parser;
             use crate::types::{FilePath, ReadyWhen, ScriptName};
 
             use super::{parse, CodeBlockInfo, CodeBlockType};
@@ -447,6 +471,22 @@ mod tests {
                     })
                 );
             }
+
+            #[test]
+            fn fails_when_timeout_secs_is_negative() {
+                let result = parse("shell,background(name=\"server\",timeout_secs=-1)");
+                assert_eq!(
+                    result,
+                    Err(Error::FunctionStringParser(
+                        function_string_parser::Error::IncorrectArgumentType {
+                            function: "background".to_string(),
+                            argument: "timeout_secs".to_string(),
+                            expected: "non-negative integer".to_string(),
+                            got: "negative integer".to_string(),
+                        }
+                    ))
+                );
+            }
         }
     }
 }
@@ -1,9 +1,11 @@
 use crate::parsers::error::{Error, Result};
+use crate::parsers::function_schema;
 use crate::parsers::function_string_parser;
 use crate::parsers::function_string_parser::Function;
 use crate::types::{
     DelayMillis, ExitCode, FilePath, MockName, OutputExpectation, ReadyWhen, ResponseBody,
-    ResponseCodeBlock, ScriptCode, ScriptName, Source, StatusCode, Stream, TargetOs,
+    ResponseCodeBlock, ResponseHeader, ScriptCode, ScriptName, Source, StatusCode, Stream,
+    TargetOs, TreeDetails,
 };
 use nom::combinator::map_res;
 use nom::{IResult, Parser};
@@ -13,6 +15,7 @@ pub struct ScriptCodeBlock {
     pub script_name: Option<ScriptName>,
     pub expected_exit_code: Option<ExitCode>,
     pub expected_output: OutputExpectation,
+    pub interpreter: Option<String>,
 }
 
 #[derive(Debug, Eq, PartialEq)]
@@ -28,6 +31,22 @@ pub struct BackgroundCodeBlock {
     pub timeout_secs: Option<u32>,
 }
 
+#[derive(Debug, Eq, PartialEq)]
+pub struct VerifyTreeCodeBlock {
+    pub path: FilePath,
+    pub include: Vec<String>,
+    pub exclude: Vec<String>,
+    pub details: TreeDetails,
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub struct MockCommandCodeBlock {
+    pub name: String,
+    pub args: Option<String>,
+    pub stream: Stream,
+    pub exit_code: ExitCode,
+}
+
 #[derive(Debug, Eq, PartialEq)]
 pub enum CodeBlockType {
     Script(ScriptCodeBlock),
@@ -35,6 +54,10 @@ pub enum CodeBlockType {
     CreateFile(FilePath),
     Background(BackgroundCodeBlock),
     Response(ResponseCodeBlock),
+    VerifyTree(VerifyTreeCodeBlock),
+    MockCommand(MockCommandCodeBlock),
+    VerifyMockCommand(String),
+    Session(ScriptCodeBlock),
     Skip(),
 }
 
@@ -43,18 +66,23 @@ pub fn parse(input: &str) -> IResult<&str, CodeBlockType, Error> {
 }
 
 fn from_function(f: Function) -> Result<CodeBlockType> {
+    function_schema::validate(&f)?;
     match &f.name[..] {
-        "script" => script_to_code_block_type(&f),
+        "script" => to_script_code_block(&f).map(CodeBlockType::Script),
         "verify" => verify_to_code_block_type(&f),
         "file" => file_to_code_block_type(&f),
         "background" => background_to_code_block_type(&f),
         "response" => response_to_code_block_type(&f),
+        "verify_tree" => verify_tree_to_code_block_type(&f),
+        "mock_command" => mock_command_to_code_block_type(&f),
+        "session" => to_script_code_block(&f).map(CodeBlockType::Session),
         "skip" => Ok(skip_to_code_block_type(&f)),
         _ => Err(Error::UnknownFunction(f.name)),
     }
 }
 
-fn script_to_code_block_type(f: &Function) -> Result<CodeBlockType> {
+/// Parses the arguments shared by `script` and `session` blocks.
+fn to_script_code_block(f: &Function) -> Result<ScriptCodeBlock> {
     let name = if f.has_argument("name") {
         Some(ScriptName(f.get_string_argument("name")?))
     } else {
@@ -69,11 +97,17 @@ fn script_to_code_block_type(f: &Function) -> Result<CodeBlockType> {
         .get_token_argument("expected_output")
         .or_else(|_| Ok("any".to_string()))
         .and_then(|s| to_expected_output(&s))?;
-    Ok(CodeBlockType::Script(ScriptCodeBlock {
+    let interpreter = if f.has_argument("interpreter") {
+        Some(f.get_string_argument("interpreter")?)
+    } else {
+        None
+    };
+    Ok(ScriptCodeBlock {
         script_name: name,
         expected_exit_code,
         expected_output,
-    }))
+        interpreter,
+    })
 }
 
 fn to_expected_output(s: &str) -> Result<OutputExpectation> {
@@ -108,20 +142,7 @@ fn background_to_code_block_type(f: &Function) -> Result<CodeBlockType> {
         None
     };
     let timeout_secs = if f.has_argument("timeout_secs") {
-        let value = f.get_integer_argument("timeout_secs")?;
-        if value < 0 {
-            return Err(Error::InvalidArgumentValue {
-                function: "background".to_string(),
-                argument: "timeout_secs".to_string(),
-                expected: "a non-negative integer".to_string(),
-                got: value.to_string(),
-            });
-        }
-        // The parser returns i32; readiness timeouts fit comfortably in a u32
-        // and the public API uses u32. We validated non-negativity above, so
-        // the cast is safe.
-        #[allow(clippy::cast_sign_loss)]
-        Some(value as u32)
+        Some(f.get_non_negative_integer_argument("timeout_secs")?)
     } else {
         None
     };
@@ -147,11 +168,7 @@ fn response_to_code_block_type(f: &Function) -> Result<CodeBlockType> {
         StatusCode::default()
     };
 
-    let headers = if f.has_argument("headers") {
-        Some(f.get_string_argument("headers")?)
-    } else {
-        None
-    };
+    let headers = get_headers_argument(f)?;
 
     let content_type = if f.has_argument("content_type") {
         Some(f.get_string_argument("content_type")?)
@@ -187,6 +204,101 @@ fn response_to_code_block_type(f: &Function) -> Result<CodeBlockType> {
     }))
 }
 
+/// Reads the optional `headers` argument, either a map or a `;`-separated
+/// string of `name: value` pairs. Names are lower-cased.
+fn get_headers_argument(f: &Function) -> Result<Vec<ResponseHeader>> {
+    if !f.has_argument("headers") {
+        return Ok(Vec::new());
+    }
+    let headers = match f.get_string_argument("headers") {
+        Ok(raw) => split_list(&raw)
+            .filter_map(|entry| {
+                let (name, value) = entry.split_once(':')?;
+                Some((name.to_string(), value.to_string()))
+            })
+            .collect(),
+        Err(_) => f.get_map_argument("headers")?,
+    };
+    Ok(headers
+        .into_iter()
+        .map(|(name, value)| ResponseHeader {
+            name: name.trim().to_lowercase(),
+            value: value.trim().to_string(),
+        })
+        .collect())
+}
+
+fn verify_tree_to_code_block_type(f: &Function) -> Result<CodeBlockType> {
+    let path = FilePath(f.get_string_argument("path")?);
+    let include = get_glob_list_argument(f, "include")?;
+    let exclude = get_glob_list_argument(f, "exclude")?;
+    let details = parse_tree_details(get_list_argument(f, "details")?)?;
+    Ok(CodeBlockType::VerifyTree(VerifyTreeCodeBlock {
+        path,
+        include,
+        exclude,
+        details,
+    }))
+}
+
+/// Reads an optional list argument, which can also be written as a
+/// `;`-separated string (the same separator `response(headers=...)` uses).
+fn get_list_argument(f: &Function, argument: &str) -> Result<Vec<String>> {
+    if !f.has_argument(argument) {
+        return Ok(Vec::new());
+    }
+    match f.get_string_argument(argument) {
+        Ok(value) => Ok(split_list(&value).collect()),
+        Err(_) => Ok(f.get_list_argument(argument)?),
+    }
+}
+
+/// Reads an optional list of glob patterns, rejecting any pattern that isn't
+/// a valid glob so mistakes are reported at parse time rather than mid-run.
+fn get_glob_list_argument(f: &Function, argument: &str) -> Result<Vec<String>> {
+    get_list_argument(f, argument)?
+        .into_iter()
+        .map(|pattern| {
+            globset::Glob::new(&pattern)
+                .map(|_| pattern.clone())
+                .map_err(|err| Error::InvalidArgumentValue {
+                    function: f.name.clone(),
+                    argument: argument.to_string(),
+                    expected: "a list of valid glob patterns".to_string(),
+                    got: format!("{pattern} ({})", err.kind()),
+                })
+        })
+        .collect()
+}
+
+fn parse_tree_details(values: Vec<String>) -> Result<TreeDetails> {
+    let mut details = TreeDetails::default();
+    for detail in values {
+        match detail.as_str() {
+            "size" => details.size = true,
+            "mode" => details.mode = true,
+            "hash" => details.hash = true,
+            _ => {
+                return Err(Error::InvalidArgumentValue {
+                    function: "verify_tree".to_string(),
+                    argument: "details".to_string(),
+                    expected: "a list of size, mode or hash".to_string(),
+                    got: detail,
+                })
+            }
+        }
+    }
+    Ok(details)
+}
+
+fn split_list(value: &str) -> impl Iterator<Item = String> + '_ {
+    value
+        .split(';')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(ToString::to_string)
+}
+
 /// Parse a `ready_when` condition string into a [`ReadyWhen`] variant.
 ///
 /// Supported forms:
@@ -215,6 +327,9 @@ const fn skip_to_code_block_type(_f: &Function) -> CodeBlockType {
 }
 
 fn verify_to_code_block_type(f: &Function) -> Result<CodeBlockType> {
+    if f.has_argument("mock_command") {
+        return verify_mock_command_to_code_block_type(f);
+    }
     let name = if f.has_argument("script_name") {
         Some(ScriptName(f.get_string_argument("script_name")?))
     } else {
@@ -242,6 +357,70 @@ fn verify_to_code_block_type(f: &Function) -> Result<CodeBlockType> {
     }))
 }
 
+/// A `verify` block checking the calls made to a mocked command, which has
+/// no script or stream to check.
+fn verify_mock_command_to_code_block_type(f: &Function) -> Result<CodeBlockType> {
+    let name = get_command_name_argument(f, "mock_command")?;
+    if ["script_name", "stream", "target_os"]
+        .iter()
+        .any(|argument| f.has_argument(argument))
+    {
+        return Err(Error::InvalidArgumentValue {
+            function: f.name.clone(),
+            argument: "mock_command".to_string(),
+            expected: "given without script_name, stream or target_os".to_string(),
+            got: name,
+        });
+    }
+    Ok(CodeBlockType::VerifyMockCommand(name))
+}
+
+fn mock_command_to_code_block_type(f: &Function) -> Result<CodeBlockType> {
+    let name = get_command_name_argument(f, "name")?;
+    let args = if f.has_argument("args") {
+        Some(f.get_string_argument("args")?)
+    } else {
+        None
+    };
+    let stream_name = if f.has_argument("stream") {
+        f.get_token_argument("stream")?
+    } else {
+        "stdout".to_string()
+    };
+    let stream = to_stream(&stream_name).ok_or_else(|| Error::InvalidArgumentValue {
+        function: f.name.clone(),
+        argument: "stream".to_string(),
+        got: stream_name.clone(),
+        expected: "stdout or stderr".to_string(),
+    })?;
+    let exit_code = if f.has_argument("exit_code") {
+        ExitCode(f.get_integer_argument("exit_code")?)
+    } else {
+        ExitCode(0)
+    };
+    Ok(CodeBlockType::MockCommand(MockCommandCodeBlock {
+        name,
+        args,
+        stream,
+        exit_code,
+    }))
+}
+
+/// Reads the name of a command to mock, which becomes the name of the stub
+/// file, so can't be empty or contain a path separator.
+fn get_command_name_argument(f: &Function, argument: &str) -> Result<String> {
+    let name = f.get_string_argument(argument)?;
+    if name.is_empty() || name.contains(['/', '\\']) || name == "." || name == ".." {
+        return Err(Error::InvalidArgumentValue {
+            function: f.name.clone(),
+            argument: argument.to_string(),
+            expected: "a command name without a path".to_string(),
+            got: name,
+        });
+    }
+    Ok(name)
+}
+
 fn to_stream(stream_name: &str) -> Option<Stream> {
     match stream_name {
         "stdout" => Some(Stream::StdOut),
@@ -291,6 +470,191 @@ mod tests {
         );
     }
 
+    #[test]
+    fn verify_tree_splits_include_and_exclude_lists() {
+        let f = function_string_parser::parse::<nom::error::Error<&str>>(
+            "verify_tree(path=\"out\", include=\"**/*.rs; *.toml\", exclude=\"target/**\")",
+        )
+        .unwrap()
+        .1;
+
+        assert_eq!(
+            from_function(f),
+            Ok(CodeBlockType::VerifyTree(VerifyTreeCodeBlock {
+                path: FilePath("out".to_string()),
+                include: vec!["**/*.rs".to_string(), "*.toml".to_string()],
+                exclude: vec!["target/**".to_string()],
+                details: TreeDetails::default(),
+            }))
+        );
+    }
+
+    #[test]
+    fn verify_tree_rejects_an_invalid_glob() {
+        let f = function_string_parser::parse::<nom::error::Error<&str>>(
+            "verify_tree(path=\"out\", include=\"src/[\")",
+        )
+        .unwrap()
+        .1;
+
+        assert!(matches!(
+            from_function(f),
+            Err(Error::InvalidArgumentValue { argument, .. }) if argument == "include"
+        ));
+    }
+
+    #[test]
+    fn parse_tree_details_accepts_every_detail() {
+        assert_eq!(
+            parse_tree_details(vec![
+                "size".to_string(),
+                "mode".to_string(),
+                "hash".to_string()
+            ]),
+            Ok(TreeDetails {
+                size: true,
+                mode: true,
+                hash: true,
+            })
+        );
+    }
+
+    #[test]
+    fn parse_tree_details_rejects_an_unknown_detail() {
+        assert!(parse_tree_details(vec!["size".to_string(), "owner".to_string()]).is_err());
+    }
+
+    #[test]
+    fn verify_tree_accepts_lists_as_well_as_separated_strings() {
+        let f = function_string_parser::parse::<nom::error::Error<&str>>(
+            "verify_tree(path=\"out\", include=[\"*.rs\", \"*.md\"], details=\"size;hash\")",
+        )
+        .unwrap()
+        .1;
+
+        assert_eq!(
+            from_function(f),
+            Ok(CodeBlockType::VerifyTree(VerifyTreeCodeBlock {
+                path: FilePath("out".to_string()),
+                include: vec!["*.rs".to_string(), "*.md".to_string()],
+                exclude: vec![],
+                details: TreeDetails {
+                    size: true,
+                    mode: false,
+                    hash: true,
+                },
+            }))
+        );
+    }
+
+    #[test]
+    fn mock_command_defaults_to_stdout_and_exit_code_0() {
+        let f =
+            function_string_parser::parse::<nom::error::Error<&str>>("mock_command(name=\"git\")")
+                .unwrap()
+                .1;
+
+        assert_eq!(
+            from_function(f),
+            Ok(CodeBlockType::MockCommand(MockCommandCodeBlock {
+                name: "git".to_string(),
+                args: None,
+                stream: Stream::StdOut,
+                exit_code: ExitCode(0),
+            }))
+        );
+    }
+
+    #[test]
+    fn mock_command_rejects_a_name_with_a_path() {
+        let f = function_string_parser::parse::<nom::error::Error<&str>>(
+            "mock_command(name=\"/usr/bin/git\")",
+        )
+        .unwrap()
+        .1;
+
+        assert!(matches!(
+            from_function(f),
+            Err(Error::InvalidArgumentValue { argument, .. }) if argument == "name"
+        ));
+    }
+
+    #[test]
+    fn verify_with_mock_command_checks_its_calls() {
+        let f = function_string_parser::parse::<nom::error::Error<&str>>(
+            "verify(mock_command=\"git\")",
+        )
+        .unwrap()
+        .1;
+
+        assert_eq!(
+            from_function(f),
+            Ok(CodeBlockType::VerifyMockCommand("git".to_string()))
+        );
+    }
+
+    #[test]
+    fn verify_with_mock_command_rejects_a_script_name() {
+        let f = function_string_parser::parse::<nom::error::Error<&str>>(
+            "verify(mock_command=\"git\", script_name=\"commit\")",
+        )
+        .unwrap()
+        .1;
+
+        assert!(matches!(
+            from_function(f),
+            Err(Error::InvalidArgumentValue { argument, .. }) if argument == "mock_command"
+        ));
+    }
+
+    #[test]
+    fn response_headers_can_be_a_map_or_a_separated_string() {
+        let headers = |input: &str| {
+            let f = function_string_parser::parse::<nom::error::Error<&str>>(input)
+                .unwrap()
+                .1;
+            match from_function(f) {
+                Ok(CodeBlockType::Response(response)) => response.headers,
+                other => panic!("expected a response, got {:?}", other),
+            }
+        };
+        let expected = vec![
+            ResponseHeader {
+                name: "x-custom".to_string(),
+                value: "value".to_string(),
+            },
+            ResponseHeader {
+                name: "content-type".to_string(),
+                value: "text/plain".to_string(),
+            },
+        ];
+
+        assert_eq!(
+            headers(
+                "response(name=\"a\", headers={\"X-Custom\": \"value\", \"Content-Type\": \"text/plain\"})"
+            ),
+            expected
+        );
+        assert_eq!(
+            headers("response(name=\"a\", headers=\"X-Custom: value; Content-Type: text/plain\")"),
+            expected
+        );
+    }
+
+    #[test]
+    fn response_headers_must_have_string_values() {
+        let f = function_string_parser::parse::<nom::error::Error<&str>>(
+            "response(name=\"a\", headers={retries: 3})",
+        )
+        .unwrap()
+        .1;
+
+        assert_eq!(
+            from_function(f).map_err(|err| err.to_string()),
+            Err("Function response requires argument headers to be a map of strings, got map containing integer".to_string())
+        );
+    }
+
     #[test]
     fn ready_when_timeout_constant_is_30_secs() {
         assert_eq!(crate::types::DEFAULT_READY_WHEN_TIMEOUT_SECS, 30);
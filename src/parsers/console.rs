@@ -0,0 +1,168 @@
+use crate::parsers::error::{Error, Result};
+use crate::types::{ConsoleCommandAction, ExitCode, ScriptCode, VerifyValue};
+
+const PROMPT: &str = "$ ";
+const CONTINUATION: &str = "> ";
+
+/// Splits a `console` transcript into its commands.
+///
+/// A line starting with `$ ` begins a command, and following lines starting
+/// with `> ` continue it. Every other line, up to the next command, is the
+/// command's expected output. If the last output line is `[exit N]`, the
+/// command is expected to exit with `N` rather than 0.
+pub fn parse(transcript: &str) -> Result<Vec<ConsoleCommandAction>> {
+    let mut commands: Vec<(Vec<&str>, Vec<&str>)> = Vec::new();
+
+    for line in transcript.lines() {
+        if let Some(command) = strip_prompt(line) {
+            commands.push((vec![command], Vec::new()));
+            continue;
+        }
+
+        match commands.last_mut() {
+            Some((command, output)) => match line.strip_prefix(CONTINUATION) {
+                Some(continued) if output.is_empty() => command.push(continued),
+                _ => output.push(line),
+            },
+            None if line.trim().is_empty() => {}
+            None => return Err(Error::ConsoleOutputBeforeCommand(line.to_string())),
+        }
+    }
+
+    Ok(commands
+        .into_iter()
+        .map(|(command, output)| to_action(&command, output))
+        .collect())
+}
+
+fn strip_prompt(line: &str) -> Option<&str> {
+    if line == PROMPT.trim_end() {
+        Some("")
+    } else {
+        line.strip_prefix(PROMPT)
+    }
+}
+
+fn to_action(command: &[&str], mut output: Vec<&str>) -> ConsoleCommandAction {
+    let expected_exit_code = output
+        .last()
+        .and_then(|line| parse_exit_annotation(line))
+        .map_or(ExitCode(0), |code| {
+            output.pop();
+            ExitCode(code)
+        });
+
+    ConsoleCommandAction {
+        command: ScriptCode(command.join("\n")),
+        expected_output: VerifyValue(output.iter().flat_map(|line| [line, "\n"]).collect()),
+        expected_exit_code,
+    }
+}
+
+fn parse_exit_annotation(line: &str) -> Option<i32> {
+    line.trim()
+        .strip_prefix("[exit ")
+        .and_then(|rest| rest.strip_suffix(']'))
+        .and_then(|code| code.trim().parse().ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse;
+    use crate::parsers::error::Error;
+    use crate::types::{ConsoleCommandAction, ExitCode, ScriptCode, VerifyValue};
+    use indoc::indoc;
+
+    fn command(command: &str, output: &str, exit_code: i32) -> ConsoleCommandAction {
+        ConsoleCommandAction {
+            command: ScriptCode(command.to_string()),
+            expected_output: VerifyValue(output.to_string()),
+            expected_exit_code: ExitCode(exit_code),
+        }
+    }
+
+    #[test]
+    fn splits_commands_and_their_output() {
+        let transcript = indoc! {"
+            $ echo hello
+            hello
+            $ ls
+            a.txt
+            b.txt
+        "};
+
+        assert_eq!(
+            parse(transcript),
+            Ok(vec![
+                command("echo hello", "hello\n", 0),
+                command("ls", "a.txt\nb.txt\n", 0),
+            ])
+        );
+    }
+
+    #[test]
+    fn reads_the_exit_code_annotation() {
+        let transcript = indoc! {"
+            $ grep missing file.txt
+            [exit 1]
+            $ false
+            [exit 1]
+            $ true
+        "};
+
+        assert_eq!(
+            parse(transcript),
+            Ok(vec![
+                command("grep missing file.txt", "", 1),
+                command("false", "", 1),
+                command("true", "", 0),
+            ])
+        );
+    }
+
+    #[test]
+    fn joins_continuation_lines_to_the_command() {
+        let transcript = indoc! {"
+            $ for i in 1 2; do
+            >   echo $i
+            > done
+            1
+            2
+        "};
+
+        assert_eq!(
+            parse(transcript),
+            Ok(vec![command(
+                "for i in 1 2; do\n  echo $i\ndone",
+                "1\n2\n",
+                0
+            )])
+        );
+    }
+
+    #[test]
+    fn keeps_output_lines_starting_with_a_continuation_prompt() {
+        let transcript = indoc! {"
+            $ echo out; echo '> quoted'
+            out
+            > quoted
+        "};
+
+        assert_eq!(
+            parse(transcript),
+            Ok(vec![command(
+                "echo out; echo '> quoted'",
+                "out\n> quoted\n",
+                0
+            )])
+        );
+    }
+
+    #[test]
+    fn fails_when_output_comes_before_the_first_command() {
+        assert_eq!(
+            parse("hello\n$ echo hello\n"),
+            Err(Error::ConsoleOutputBeforeCommand("hello".to_string()))
+        );
+    }
+}
@@ -0,0 +1,307 @@
+use super::error::Error;
+use super::function_schema;
+use super::function_string_parser;
+
+/// The part of an info string a diagnostic underlines, as a byte range, and
+/// the hint written after it.
+#[derive(Debug, Eq, PartialEq)]
+pub struct Label {
+    pub start: usize,
+    pub length: usize,
+    pub hint: String,
+}
+
+impl Error {
+    /// Formats the error as a rustc-style diagnostic for the spec file at
+    /// `path`. An error in a code block's function gives the line and column
+    /// of the block's fence, then its info string with a caret under the
+    /// failing part and a hint:
+    ///
+    /// ```text
+    /// Unknown function: scirpt
+    ///  --> spec.md:3:1
+    ///   |
+    /// 3 | shell,scirpt(name="greet")
+    ///   |       ^^^^^^ unknown function `scirpt`, did you mean `script`?
+    /// ```
+    #[must_use]
+    pub fn render(&self, path: &str) -> String {
+        match self {
+            Self::InCodeBlock {
+                line,
+                column,
+                info,
+                error,
+            } => render_in_code_block(path, *line, *column, info, error),
+            Self::MarkdownParser(error) => {
+                let gutter = gutter(error.line());
+                format!("{self}\n{gutter}--> {path}:{}", error.line())
+            }
+            _ => self.to_string(),
+        }
+    }
+
+    /// The part of the code block's info string an [`Error::InCodeBlock`]
+    /// is about, if it can be found.
+    pub(crate) fn label(&self) -> Option<Label> {
+        match self {
+            Self::InCodeBlock { info, error, .. } => label(info, error),
+            _ => None,
+        }
+    }
+}
+
+fn render_in_code_block(
+    path: &str,
+    line: usize,
+    column: usize,
+    info: &str,
+    error: &Error,
+) -> String {
+    let gutter = gutter(line);
+    let mut lines = vec![
+        error.to_string(),
+        format!("{gutter}--> {path}:{line}:{column}"),
+    ];
+
+    if let Some(label) = label(info, error) {
+        // The info string of an annotation can span several lines, which are
+        // shown as one. Each replaced character is a single byte, so the
+        // label's byte range still applies.
+        let source = info.replace(['\n', '\r', '\t'], " ");
+        let end = (label.start + label.length).min(source.len());
+        let indent = source[..label.start].chars().count();
+        let width = source[label.start..end].chars().count().max(1);
+
+        lines.push(format!("{gutter} |"));
+        lines.push(format!("{line} | {source}"));
+        lines.push(format!(
+            "{gutter} | {}{} {}",
+            " ".repeat(indent),
+            "^".repeat(width),
+            label.hint
+        ));
+    }
+
+    lines.join("\n")
+}
+
+/// The space to the left of a diagnostic's `|` column, as wide as the line
+/// number shown in it.
+fn gutter(line: usize) -> String {
+    " ".repeat(line.to_string().len())
+}
+
+/// Finds the part of `info` which `error` is about.
+fn label(info: &str, error: &Error) -> Option<Label> {
+    let function_start = info.find(',')? + 1;
+    let function = &info[function_start..];
+    let name_start = function_start + (function.len() - function.trim_start().len());
+
+    match error {
+        Error::ParserFailed {
+            expected,
+            found,
+            remaining,
+        } => {
+            let start = info.len().checked_sub(*remaining)?;
+            Some(Label {
+                start,
+                length: info[start..].chars().next().map_or(0, char::len_utf8),
+                hint: expected.map_or_else(|| format!("unexpected {found}"), ToString::to_string),
+            })
+        }
+        Error::UnknownFunction(name) => Some(Label {
+            start: name_start,
+            length: name.len(),
+            hint: match function_schema::closest_function(name) {
+                Some(closest) => format!("unknown function `{name}`, did you mean `{closest}`?"),
+                None => format!("unknown function `{name}`"),
+            },
+        }),
+        Error::UnknownArgument { argument, hint, .. } => Some(Label {
+            start: function_start + argument_name_start(function, argument)?,
+            length: argument.len(),
+            hint: hint.clone(),
+        }),
+        Error::FunctionStringParser(function_string_parser::Error::MissingArgument {
+            function,
+            argument,
+        }) => Some(Label {
+            start: name_start,
+            length: function.len(),
+            hint: format!("requires `{argument}`"),
+        }),
+        Error::FunctionStringParser(function_string_parser::Error::IncorrectArgumentType {
+            argument,
+            expected,
+            got,
+            ..
+        }) => value_label(
+            function,
+            function_start,
+            argument,
+            format!("expected {expected}, found {got}"),
+        ),
+        Error::InvalidArgumentValue {
+            argument, expected, ..
+        } => value_label(
+            function,
+            function_start,
+            argument,
+            format!("expected {expected}"),
+        ),
+        _ => None,
+    }
+}
+
+fn value_label(
+    function: &str,
+    function_start: usize,
+    argument: &str,
+    hint: String,
+) -> Option<Label> {
+    let name_end = argument_name_start(function, argument)? + argument.len();
+    let after_name = &function[name_end..];
+    let value = after_name.trim_start().strip_prefix('=')?.trim_start();
+    Some(Label {
+        start: function_start + function.len() - value.len(),
+        length: function_string_parser::value_length(value)?,
+        hint,
+    })
+}
+
+/// Where the argument `name` is given in `function`: the name must follow
+/// the opening `(` or a `,` and be followed by `=`, so a mention of it inside
+/// another argument's value isn't mistaken for it.
+fn argument_name_start(function: &str, name: &str) -> Option<usize> {
+    function
+        .match_indices(name)
+        .map(|(start, _)| start)
+        .find(|start| {
+            let before = function[..*start].trim_end();
+            let after = function[start + name.len()..].trim_start();
+            (before.ends_with('(') || before.ends_with(',')) && after.starts_with('=')
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use crate::parsers::parse;
+
+    fn render(markdown: &str) -> String {
+        parse(markdown)
+            .expect_err("markdown to fail to parse")
+            .render("spec.md")
+    }
+
+    #[test]
+    fn points_at_an_unknown_function_with_a_suggestion() {
+        let markdown = indoc! {r#"
+            # Title
+
+            ```shell,scirpt(name="greet")
+            echo hello
+            ```
+        "#};
+
+        assert_eq!(
+            render(markdown),
+            indoc! {r#"
+                Unknown function: scirpt
+                 --> spec.md:3:1
+                  |
+                3 | shell,scirpt(name="greet")
+                  |       ^^^^^^ unknown function `scirpt`, did you mean `script`?"#}
+        );
+    }
+
+    #[test]
+    fn points_at_where_the_syntax_is_wrong_and_what_was_expected() {
+        let markdown = "```shell,script(name=\"greet\" expected_exit_code=1)\n```\n";
+
+        assert_eq!(
+            render(markdown),
+            indoc! {r#"
+                The parser failed: expected `,` or `)`, found `e`
+                 --> spec.md:1:1
+                  |
+                1 | shell,script(name="greet" expected_exit_code=1)
+                  |                           ^ expected `,` or `)`"#}
+        );
+    }
+
+    #[test]
+    fn points_at_the_end_of_an_unterminated_function() {
+        let markdown = "```shell,script(name=\"greet\"\n```\n";
+
+        assert_eq!(
+            render(markdown),
+            indoc! {r#"
+                The parser failed: expected `,` or `)`, found the end of the function
+                 --> spec.md:1:1
+                  |
+                1 | shell,script(name="greet"
+                  |                          ^ expected `,` or `)`"#}
+        );
+    }
+
+    #[test]
+    fn points_at_an_unknown_argument() {
+        let markdown = "```shell,script(name=\"greet\", expect_exit_code=1)\n```\n";
+
+        assert_eq!(
+            render(markdown),
+            indoc! {r#"
+                Unknown argument `expect_exit_code` for function script, did you mean `expected_exit_code`?
+                 --> spec.md:1:1
+                  |
+                1 | shell,script(name="greet", expect_exit_code=1)
+                  |                            ^^^^^^^^^^^^^^^^ did you mean `expected_exit_code`?"#}
+        );
+    }
+
+    #[test]
+    fn points_at_a_value_of_the_wrong_type() {
+        let markdown = "```shell,script(name=\"x\", expected_exit_code=\"zero\")\n```\n";
+
+        assert_eq!(
+            render(markdown),
+            indoc! {r#"
+                Function script requires argument expected_exit_code to be a integer, got string
+                 --> spec.md:1:1
+                  |
+                1 | shell,script(name="x", expected_exit_code="zero")
+                  |                                           ^^^^^^ expected integer, found string"#}
+        );
+    }
+
+    #[test]
+    fn points_at_the_function_missing_a_required_argument() {
+        let markdown = "Text\n\n```text,file()\n```\n";
+
+        assert_eq!(
+            render(markdown),
+            indoc! {"
+                Function file requires argument path
+                 --> spec.md:3:1
+                  |
+                3 | text,file()
+                  |      ^^^^ requires `path`"}
+        );
+    }
+
+    #[test]
+    fn gives_the_location_of_a_markdown_error() {
+        let markdown = "<!-- specdown: script() -->\n\nText\n";
+
+        assert_eq!(
+            render(markdown),
+            indoc! {"
+                The annotation '<!-- specdown: script() -->' must be directly followed by a fenced code block
+                 --> spec.md:1"}
+        );
+    }
+}
@@ -1,20 +1,38 @@
 use super::function_string_parser;
 use super::markdown;
 
-use nom::error::{ErrorKind, FromExternalError, ParseError};
+use nom::error::{ContextError, ErrorKind, FromExternalError, ParseError};
 
+/// The result of parsing a spec file.
 pub type Result<T> = std::result::Result<T, Error>;
 
+/// An error in a spec file's specdown functions.
+// Each variant is described by its error message.
+#[allow(missing_docs)]
 #[derive(Clone, Debug, Eq, thiserror::Error, PartialEq)]
 pub enum Error {
     #[error("{0}")]
     FunctionStringParser(#[from] function_string_parser::Error),
     #[error("{0}")]
     MarkdownParser(#[from] markdown::Error),
-    #[error("The parser failed: {0}")]
-    ParserFailed(String),
+    /// `remaining` is how much of the input was left when parsing failed,
+    /// which locates the failure in the info string.
+    #[error("The parser failed: {}", describe_failure(*.expected, .found))]
+    ParserFailed {
+        expected: Option<&'static str>,
+        found: String,
+        remaining: usize,
+    },
     #[error("Unknown function: {0}")]
     UnknownFunction(String),
+    #[error("Unknown argument `{argument}` for function {function}, {hint}")]
+    UnknownArgument {
+        function: String,
+        argument: String,
+        hint: String,
+    },
+    #[error("Console blocks must start with a command prefixed by '$ ', got: {0}")]
+    ConsoleOutputBeforeCommand(String),
     #[error("Argument {argument} for function {function} must be {expected}, got {got}")]
     InvalidArgumentValue {
         function: String,
@@ -22,19 +40,58 @@ pub enum Error {
         expected: String,
         got: String,
     },
+    /// An error in the function of the fenced code block at `line` and
+    /// `column`, whose info string is `info`.
+    #[error("{error}")]
+    InCodeBlock {
+        line: usize,
+        column: usize,
+        info: String,
+        error: Box<Error>,
+    },
+}
+
+fn describe_failure(expected: Option<&str>, found: &str) -> String {
+    match expected {
+        Some(expected) => format!("{expected}, found {found}"),
+        None => format!("unexpected {found}"),
+    }
 }
 
 impl ParseError<&str> for Error {
-    fn from_error_kind(input: &str, kind: ErrorKind) -> Self {
-        Self::ParserFailed(format!(
-            "Failed parsing function from '{input}' :: {kind:?}"
-        ))
+    fn from_error_kind(input: &str, _kind: ErrorKind) -> Self {
+        Self::ParserFailed {
+            expected: None,
+            found: input.chars().next().map_or_else(
+                || "the end of the function".to_string(),
+                |c| format!("`{c}`"),
+            ),
+            remaining: input.len(),
+        }
+    }
+
+    /// Keeps the innermost error, which is the closest to the mistake.
+    fn append(_input: &str, _kind: ErrorKind, other: Self) -> Self {
+        other
     }
+}
 
-    fn append(input: &str, kind: ErrorKind, other: Self) -> Self {
-        Self::ParserFailed(format!(
-            "Failed parsing function from '{input}' :: {kind:?} (follows: {other}"
-        ))
+impl ContextError<&str> for Error {
+    /// Records what was expected where parsing failed. The innermost context
+    /// is the most specific, so an outer one never replaces it.
+    fn add_context(_input: &str, context: &'static str, other: Self) -> Self {
+        match other {
+            Self::ParserFailed {
+                expected: None,
+                found,
+                remaining,
+            } => Self::ParserFailed {
+                expected: Some(context),
+                found,
+                remaining,
+            },
+            other => other,
+        }
     }
 }
 
@@ -62,8 +119,15 @@ mod tests {
     #[test]
     fn display_parser_failed() {
         assert_eq!(
-            format!("{}", Error::ParserFailed("reason".to_string())),
-            "The parser failed: reason"
+            format!(
+                "{}",
+                Error::ParserFailed {
+                    expected: Some("expected `)`"),
+                    found: "`;`".to_string(),
+                    remaining: 2
+                }
+            ),
+            "The parser failed: expected `)`, found `;`"
         );
     }
 
@@ -75,6 +139,21 @@ mod tests {
         );
     }
 
+    #[test]
+    fn display_unknown_argument() {
+        assert_eq!(
+            format!(
+                "{}",
+                Error::UnknownArgument {
+                    function: "funcy".to_string(),
+                    argument: "argy".to_string(),
+                    hint: "did you mean `arg`?".to_string()
+                }
+            ),
+            "Unknown argument `argy` for function funcy, did you mean `arg`?"
+        );
+    }
+
     #[test]
     fn display_missing_argument() {
         assert_eq!(
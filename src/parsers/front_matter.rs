@@ -0,0 +1,97 @@
+//! Front matter: a block of YAML between `---` lines, or of TOML between
+//! `+++` lines, at the very start of a spec file.
+
+use comrak::nodes::NodeValue;
+use comrak::{parse_document, Arena, Options};
+
+/// The language front matter is written in.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Format {
+    /// YAML, between `---` lines.
+    Yaml,
+    /// TOML, between `+++` lines.
+    Toml,
+}
+
+impl Format {
+    fn delimiter(self) -> &'static str {
+        match self {
+            Self::Yaml => "---",
+            Self::Toml => "+++",
+        }
+    }
+
+    /// The format of the front matter `markdown` starts with, if any.
+    fn of(markdown: &str) -> Option<Self> {
+        let first_line = markdown.lines().next()?.trim_end();
+        [Self::Yaml, Self::Toml]
+            .iter()
+            .copied()
+            .find(|format| first_line == format.delimiter())
+    }
+}
+
+/// The options spec files are parsed with, which enable comrak's front
+/// matter extension for the delimiter `markdown` starts with, so its front
+/// matter isn't read as Markdown.
+pub fn options(markdown: &str) -> Options<'static> {
+    let mut options = Options::default();
+    options.extension.front_matter_delimiter =
+        Format::of(markdown).map(|format| format.delimiter().to_string());
+    options
+}
+
+/// The front matter `markdown` starts with, without its delimiters, and the
+/// format it's in.
+pub fn front_matter(markdown: &str) -> Option<(Format, String)> {
+    let format = Format::of(markdown)?;
+    let arena = Arena::new();
+    let root = parse_document(&arena, markdown, &options(markdown));
+    let node = root.first_child()?;
+    let NodeValue::FrontMatter(ref text) = node.data.borrow().value else {
+        return None;
+    };
+
+    let (_, body) = text.split_once('\n')?;
+    let body = body.trim_end().strip_suffix(format.delimiter())?;
+    Some((format, body.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use super::{front_matter, Format};
+
+    #[test]
+    fn finds_yaml_front_matter() {
+        let markdown = indoc! {"
+            ---
+            shell_command: sh -c
+            ---
+
+            # Title
+        "};
+
+        assert_eq!(
+            front_matter(markdown),
+            Some((Format::Yaml, "shell_command: sh -c\n".to_string()))
+        );
+    }
+
+    #[test]
+    fn finds_toml_front_matter() {
+        let markdown = "+++\ntags = [\"slow\"]\n+++\n# Title\n";
+
+        assert_eq!(
+            front_matter(markdown),
+            Some((Format::Toml, "tags = [\"slow\"]\n".to_string()))
+        );
+    }
+
+    #[test]
+    fn only_finds_front_matter_at_the_start() {
+        assert_eq!(front_matter("# Title\n\n---\na: b\n---\n"), None);
+        assert_eq!(front_matter("---\nnever closed\n"), None);
+    }
+}
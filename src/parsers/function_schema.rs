@@ -0,0 +1,350 @@
+use crate::parsers::error::{Error, Result};
+use crate::parsers::function_string_parser::{self, ArgumentValue, Function};
+
+/// The type of value an argument takes.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum ArgumentType {
+    Integer,
+    String,
+    Token,
+    List,
+    Map,
+}
+
+impl ArgumentType {
+    /// The name used for the type in error messages, matching
+    /// `ArgumentValue::type_name`.
+    const fn name(self) -> &'static str {
+        match self {
+            Self::Integer => "integer",
+            Self::String => "string",
+            Self::Token => "token",
+            Self::List => "list",
+            Self::Map => "map",
+        }
+    }
+
+    /// Whether a value of this type can be given. A token can also be
+    /// `true` or `false`, which were tokens before they were booleans.
+    fn accepts(self, value: &ArgumentValue) -> bool {
+        self.name() == value.type_name()
+            || (self == Self::Token && matches!(value, ArgumentValue::Boolean(_)))
+    }
+}
+
+/// An argument a function accepts, and the types of value it can be given.
+#[derive(Debug)]
+struct Argument {
+    name: &'static str,
+    types: &'static [ArgumentType],
+    required: bool,
+}
+
+const fn optional(name: &'static str, types: &'static [ArgumentType]) -> Argument {
+    Argument {
+        name,
+        types,
+        required: false,
+    }
+}
+
+const fn required(name: &'static str, types: &'static [ArgumentType]) -> Argument {
+    Argument {
+        name,
+        types,
+        required: true,
+    }
+}
+
+const INTEGER: &[ArgumentType] = &[ArgumentType::Integer];
+const STRING: &[ArgumentType] = &[ArgumentType::String];
+const TOKEN: &[ArgumentType] = &[ArgumentType::Token];
+/// A list, or the older `;`-separated string form.
+const LIST: &[ArgumentType] = &[ArgumentType::List, ArgumentType::String];
+/// A map, or the older `;`-separated string of `name: value` pairs.
+const MAP: &[ArgumentType] = &[ArgumentType::Map, ArgumentType::String];
+
+const SCRIPT: &[Argument] = &[
+    optional("name", STRING),
+    optional("expected_exit_code", INTEGER),
+    optional("expected_output", TOKEN),
+    optional("interpreter", STRING),
+];
+
+const VERIFY: &[Argument] = &[
+    optional("script_name", STRING),
+    optional("stream", TOKEN),
+    optional("target_os", STRING),
+    optional("mock_command", STRING),
+];
+
+const FILE: &[Argument] = &[required("path", STRING)];
+
+const BACKGROUND: &[Argument] = &[
+    optional("name", STRING),
+    optional("ready_when", STRING),
+    optional("timeout_secs", INTEGER),
+];
+
+const RESPONSE: &[Argument] = &[
+    required("name", STRING),
+    optional("status", INTEGER),
+    optional("headers", MAP),
+    optional("content_type", STRING),
+    optional("delay", INTEGER),
+    optional("body", STRING),
+];
+
+const VERIFY_TREE: &[Argument] = &[
+    required("path", STRING),
+    optional("include", LIST),
+    optional("exclude", LIST),
+    optional("details", LIST),
+];
+
+const MOCK_COMMAND: &[Argument] = &[
+    required("name", STRING),
+    optional("args", STRING),
+    optional("stream", TOKEN),
+    optional("exit_code", INTEGER),
+];
+
+/// Every function specdown knows.
+pub const FUNCTIONS: &[&str] = &[
+    "script",
+    "session",
+    "verify",
+    "file",
+    "background",
+    "response",
+    "verify_tree",
+    "mock_command",
+    "skip",
+];
+
+/// The arguments each function accepts, or `None` for an unknown function.
+fn schema(function: &str) -> Option<&'static [Argument]> {
+    match function {
+        "script" | "session" => Some(SCRIPT),
+        "verify" => Some(VERIFY),
+        "file" => Some(FILE),
+        "background" => Some(BACKGROUND),
+        "response" => Some(RESPONSE),
+        "verify_tree" => Some(VERIFY_TREE),
+        "mock_command" => Some(MOCK_COMMAND),
+        "skip" => Some(&[]),
+        _ => None,
+    }
+}
+
+/// The names of the arguments `function` accepts, or `None` for an unknown
+/// function.
+pub fn argument_names(function: &str) -> Option<Vec<&'static str>> {
+    schema(function).map(|arguments| arguments.iter().map(|argument| argument.name).collect())
+}
+
+/// Checks `f`'s arguments against its function's schema: every argument
+/// must be one the function accepts, have the right type, and every
+/// required argument must be given. Like `deny_unknown_fields` in
+/// `specdown.toml`, an unknown argument is an error rather than ignored.
+///
+/// Unknown functions are left for the caller to report.
+pub fn validate(f: &Function) -> Result<()> {
+    let Some(arguments) = schema(&f.name) else {
+        return Ok(());
+    };
+
+    let mut given: Vec<&String> = f.arguments.keys().collect();
+    given.sort();
+    if let Some(unknown) = given
+        .into_iter()
+        .find(|name| !arguments.iter().any(|argument| argument.name == *name))
+    {
+        return Err(Error::UnknownArgument {
+            function: f.name.clone(),
+            argument: unknown.clone(),
+            hint: unknown_argument_hint(unknown, arguments),
+        });
+    }
+
+    arguments
+        .iter()
+        .try_for_each(|argument| check_argument(f, argument))
+}
+
+fn check_argument(f: &Function, argument: &Argument) -> Result<()> {
+    let Some(value) = f.arguments.get(argument.name) else {
+        return if argument.required {
+            Err(function_string_parser::Error::MissingArgument {
+                function: f.name.clone(),
+                argument: argument.name.to_string(),
+            }
+            .into())
+        } else {
+            Ok(())
+        };
+    };
+
+    if argument
+        .types
+        .iter()
+        .any(|argument_type| argument_type.accepts(value))
+    {
+        Ok(())
+    } else {
+        Err(function_string_parser::Error::IncorrectArgumentType {
+            function: f.name.clone(),
+            argument: argument.name.to_string(),
+            expected: argument
+                .types
+                .iter()
+                .map(|argument_type| argument_type.name())
+                .collect::<Vec<_>>()
+                .join(" or "),
+            got: value.type_name().to_string(),
+        }
+        .into())
+    }
+}
+
+/// The known function closest to the unknown function `name`, if any is
+/// close enough to be a typo.
+pub fn closest_function(name: &str) -> Option<&'static str> {
+    closest(name, FUNCTIONS.iter().copied())
+}
+
+fn closest(unknown: &str, names: impl Iterator<Item = &'static str>) -> Option<&'static str> {
+    names
+        .map(|name| (strsim::jaro_winkler(unknown, name), name))
+        .filter(|(similarity, _)| *similarity > 0.8)
+        .max_by(|(a, _), (b, _)| a.total_cmp(b))
+        .map(|(_, name)| name)
+}
+
+/// Suggests the closest accepted argument name, or lists them all when
+/// none is close.
+fn unknown_argument_hint(unknown: &str, arguments: &[Argument]) -> String {
+    let closest = closest(unknown, arguments.iter().map(|argument| argument.name));
+
+    match (closest, arguments) {
+        (Some(name), _) => format!("did you mean `{name}`?"),
+        (None, []) => "it takes no arguments".to_string(),
+        (None, _) => format!(
+            "expected one of {}",
+            arguments
+                .iter()
+                .map(|argument| format!("`{}`", argument.name))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{closest_function, validate};
+    use crate::parsers::error::Error;
+    use crate::parsers::function_string_parser::{self, Function};
+
+    fn function(input: &str) -> Function {
+        function_string_parser::parse::<Error>(input)
+            .expect("function to parse")
+            .1
+    }
+
+    fn validation_error(input: &str) -> String {
+        validate(&function(input))
+            .expect_err("validation to fail")
+            .to_string()
+    }
+
+    #[test]
+    fn accepts_known_arguments_of_the_right_type() {
+        assert_eq!(
+            validate(&function("script(name=\"x\", expected_exit_code=1)")),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn suggests_the_closest_argument_for_a_typo() {
+        assert_eq!(
+            validation_error("script(expect_exit_code=1)"),
+            "Unknown argument `expect_exit_code` for function script, did you mean `expected_exit_code`?"
+        );
+    }
+
+    #[test]
+    fn lists_the_accepted_arguments_when_none_is_close() {
+        assert_eq!(
+            validation_error("verify(colour=red)"),
+            "Unknown argument `colour` for function verify, expected one of `script_name`, `stream`, `target_os`, `mock_command`"
+        );
+    }
+
+    #[test]
+    fn rejects_arguments_to_a_function_which_takes_none() {
+        assert_eq!(
+            validation_error("skip(reason=\"flaky\")"),
+            "Unknown argument `reason` for function skip, it takes no arguments"
+        );
+    }
+
+    #[test]
+    fn rejects_arguments_of_the_wrong_type() {
+        assert_eq!(
+            validate(&function("script(expected_output=\"stdout\")")),
+            Err(Error::FunctionStringParser(
+                function_string_parser::Error::IncorrectArgumentType {
+                    function: "script".to_string(),
+                    argument: "expected_output".to_string(),
+                    expected: "token".to_string(),
+                    got: "string".to_string(),
+                }
+            ))
+        );
+    }
+
+    #[test]
+    fn accepts_any_of_an_arguments_types() {
+        assert_eq!(
+            validate(&function(
+                "verify_tree(path=\"out\", include=[\"*.rs\"], exclude=\"*.md\")"
+            )),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn accepts_a_boolean_where_a_token_is_expected() {
+        assert_eq!(validate(&function("verify(stream=true)")), Ok(()));
+    }
+
+    #[test]
+    fn lists_every_accepted_type_when_the_type_is_wrong() {
+        assert_eq!(
+            validation_error("response(name=\"users\", headers=1)"),
+            "Function response requires argument headers to be a map or string, got integer"
+        );
+    }
+
+    #[test]
+    fn rejects_missing_required_arguments() {
+        assert_eq!(
+            validate(&function("file()")),
+            Err(Error::FunctionStringParser(
+                function_string_parser::Error::MissingArgument {
+                    function: "file".to_string(),
+                    argument: "path".to_string(),
+                }
+            ))
+        );
+    }
+
+    #[test]
+    fn suggests_the_closest_function_for_a_typo() {
+        assert_eq!(closest_function("scirpt"), Some("script"));
+        assert_eq!(closest_function("verify_tre"), Some("verify_tree"));
+        assert_eq!(closest_function("banana"), None);
+    }
+}
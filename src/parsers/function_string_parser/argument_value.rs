@@ -1,8 +1,14 @@
-#[derive(Debug, Eq, PartialEq, Clone)]
+use std::convert::TryFrom;
+
+#[derive(Debug, PartialEq, Clone)]
 pub enum ArgumentValue {
     Integer(i32),
+    Float(f64),
+    Boolean(bool),
     String(String),
     Token(String),
+    List(Vec<ArgumentValue>),
+    Map(Vec<(String, ArgumentValue)>),
 }
 
 #[derive(Debug, Eq, PartialEq)]
@@ -12,37 +18,109 @@ pub struct IncorrectArgumentType {
 }
 
 impl ArgumentValue {
+    /// The name of the value's type, as used in error messages.
+    pub const fn type_name(&self) -> &'static str {
+        match self {
+            Self::Integer(_) => "integer",
+            Self::Float(_) => "float",
+            Self::Boolean(_) => "boolean",
+            Self::String(_) => "string",
+            Self::Token(_) => "token",
+            Self::List(_) => "list",
+            Self::Map(_) => "map",
+        }
+    }
+
     pub fn integer(&self) -> Result<i32, IncorrectArgumentType> {
         match self {
             Self::Integer(num) => Ok(*num),
-            Self::String(_) => Self::incorrect_argument_type_error("integer", "string"),
-            Self::Token(_) => Self::incorrect_argument_type_error("integer", "token"),
+            other => other.incorrect_argument_type_error("integer"),
+        }
+    }
+
+    /// An integer which isn't negative, such as a timeout.
+    pub fn non_negative_integer(&self) -> Result<u32, IncorrectArgumentType> {
+        match self {
+            Self::Integer(num) => u32::try_from(*num).map_err(|_| IncorrectArgumentType {
+                expected: "non-negative integer".to_string(),
+                got: "negative integer".to_string(),
+            }),
+            other => other.incorrect_argument_type_error("non-negative integer"),
+        }
+    }
+
+    /// A float, or an integer, which is taken as the same number.
+    pub fn float(&self) -> Result<f64, IncorrectArgumentType> {
+        match self {
+            Self::Float(num) => Ok(*num),
+            Self::Integer(num) => Ok(f64::from(*num)),
+            other => other.incorrect_argument_type_error("float"),
+        }
+    }
+
+    pub fn boolean(&self) -> Result<bool, IncorrectArgumentType> {
+        match self {
+            Self::Boolean(value) => Ok(*value),
+            other => other.incorrect_argument_type_error("boolean"),
         }
     }
 
     pub fn string(&self) -> Result<String, IncorrectArgumentType> {
         match self {
             Self::String(s) => Ok(s.clone()),
-            Self::Integer(_) => Self::incorrect_argument_type_error("string", "integer"),
-            Self::Token(_) => Self::incorrect_argument_type_error("string", "token"),
+            other => other.incorrect_argument_type_error("string"),
         }
     }
 
+    /// A bare word. `true` and `false` are parsed as booleans, but are still
+    /// accepted here as the words they were before booleans were added.
     pub fn token(&self) -> Result<String, IncorrectArgumentType> {
         match self {
             Self::Token(t) => Ok(t.clone()),
-            Self::Integer(_) => Self::incorrect_argument_type_error("token", "integer"),
-            Self::String(_) => Self::incorrect_argument_type_error("token", "string"),
+            Self::Boolean(value) => Ok(value.to_string()),
+            other => other.incorrect_argument_type_error("token"),
+        }
+    }
+
+    /// A list whose items are all strings or tokens.
+    pub fn string_list(&self) -> Result<Vec<String>, IncorrectArgumentType> {
+        match self {
+            Self::List(items) => items
+                .iter()
+                .map(|item| item.text("list of strings", "list containing"))
+                .collect(),
+            other => other.incorrect_argument_type_error("list of strings"),
+        }
+    }
+
+    /// A map whose values are all strings or tokens, in the order written.
+    pub fn string_map(&self) -> Result<Vec<(String, String)>, IncorrectArgumentType> {
+        match self {
+            Self::Map(entries) => entries
+                .iter()
+                .map(|(key, value)| {
+                    Ok((key.clone(), value.text("map of strings", "map containing")?))
+                })
+                .collect(),
+            other => other.incorrect_argument_type_error("map of strings"),
+        }
+    }
+
+    /// The text of a string or token inside a list or map.
+    fn text(&self, expected: &str, container: &str) -> Result<String, IncorrectArgumentType> {
+        match self {
+            Self::String(text) | Self::Token(text) => Ok(text.clone()),
+            other => Err(IncorrectArgumentType {
+                expected: expected.to_string(),
+                got: format!("{container} {}", other.type_name()),
+            }),
         }
     }
 
-    fn incorrect_argument_type_error<T>(
-        expected: &str,
-        got: &str,
-    ) -> Result<T, IncorrectArgumentType> {
+    fn incorrect_argument_type_error<T>(&self, expected: &str) -> Result<T, IncorrectArgumentType> {
         Err(IncorrectArgumentType {
             expected: expected.to_string(),
-            got: got.to_string(),
+            got: self.type_name().to_string(),
         })
     }
 }
@@ -149,4 +227,133 @@ mod test {
             );
         }
     }
+
+    mod non_negative_integer {
+        use super::{ArgumentValue, IncorrectArgumentType};
+
+        #[test]
+        fn returns_integer_when_value_is_not_negative() {
+            assert_eq!(Ok(0), ArgumentValue::Integer(0).non_negative_integer());
+        }
+
+        #[test]
+        fn returns_error_when_value_is_negative() {
+            assert_eq!(
+                Err(IncorrectArgumentType {
+                    expected: "non-negative integer".to_string(),
+                    got: "negative integer".to_string(),
+                }),
+                ArgumentValue::Integer(-1).non_negative_integer()
+            );
+        }
+    }
+
+    mod float {
+        use super::{ArgumentValue, IncorrectArgumentType};
+
+        #[test]
+        fn returns_float_when_value_is_a_float_or_an_integer() {
+            assert_eq!(Ok(-0.25), ArgumentValue::Float(-0.25).float());
+            assert_eq!(Ok(2.0), ArgumentValue::Integer(2).float());
+        }
+
+        #[test]
+        fn returns_error_when_value_is_a_string() {
+            assert_eq!(
+                Err(IncorrectArgumentType {
+                    expected: "float".to_string(),
+                    got: "string".to_string(),
+                }),
+                ArgumentValue::String("1.5".to_string()).float()
+            );
+        }
+    }
+
+    mod boolean {
+        use super::{ArgumentValue, IncorrectArgumentType};
+
+        #[test]
+        fn returns_bool_when_value_is_a_boolean() {
+            assert_eq!(Ok(true), ArgumentValue::Boolean(true).boolean());
+        }
+
+        #[test]
+        fn returns_error_when_value_is_a_token() {
+            assert_eq!(
+                Err(IncorrectArgumentType {
+                    expected: "boolean".to_string(),
+                    got: "token".to_string(),
+                }),
+                ArgumentValue::Token("yes".to_string()).boolean()
+            );
+        }
+    }
+
+    mod string_list {
+        use super::{ArgumentValue, IncorrectArgumentType};
+
+        #[test]
+        fn returns_strings_and_tokens_in_order() {
+            assert_eq!(
+                Ok(vec!["a.txt".to_string(), "size".to_string()]),
+                ArgumentValue::List(vec![
+                    ArgumentValue::String("a.txt".to_string()),
+                    ArgumentValue::Token("size".to_string()),
+                ])
+                .string_list()
+            );
+        }
+
+        #[test]
+        fn returns_error_naming_the_item_type_which_is_not_a_string() {
+            assert_eq!(
+                Err(IncorrectArgumentType {
+                    expected: "list of strings".to_string(),
+                    got: "list containing integer".to_string(),
+                }),
+                ArgumentValue::List(vec![ArgumentValue::Integer(1)]).string_list()
+            );
+        }
+
+        #[test]
+        fn returns_error_when_value_is_a_string() {
+            assert_eq!(
+                Err(IncorrectArgumentType {
+                    expected: "list of strings".to_string(),
+                    got: "string".to_string(),
+                }),
+                ArgumentValue::String("a.txt".to_string()).string_list()
+            );
+        }
+    }
+
+    mod string_map {
+        use super::{ArgumentValue, IncorrectArgumentType};
+
+        #[test]
+        fn returns_entries_in_order() {
+            assert_eq!(
+                Ok(vec![
+                    ("b".to_string(), "2".to_string()),
+                    ("a".to_string(), "1".to_string()),
+                ]),
+                ArgumentValue::Map(vec![
+                    ("b".to_string(), ArgumentValue::String("2".to_string())),
+                    ("a".to_string(), ArgumentValue::String("1".to_string())),
+                ])
+                .string_map()
+            );
+        }
+
+        #[test]
+        fn returns_error_naming_the_value_type_which_is_not_a_string() {
+            assert_eq!(
+                Err(IncorrectArgumentType {
+                    expected: "map of strings".to_string(),
+                    got: "map containing float".to_string(),
+                }),
+                ArgumentValue::Map(vec![("a".to_string(), ArgumentValue::Float(1.5))]).string_map()
+            );
+        }
+    }
 }
@@ -1,3 +1,6 @@
+/// An error in the arguments given to a specdown function.
+// Each variant is described by its error message.
+#[allow(missing_docs)]
 #[derive(Clone, Debug, Eq, thiserror::Error, PartialEq)]
 pub enum Error {
     #[error("Function {function} requires argument {argument}")]
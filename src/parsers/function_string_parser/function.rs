@@ -3,7 +3,7 @@ use std::collections::HashMap;
 use super::argument_value::{ArgumentValue, IncorrectArgumentType};
 use super::error::Error;
 
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Debug, PartialEq)]
 pub struct Function {
     pub name: String,
     pub arguments: HashMap<String, ArgumentValue>,
@@ -27,6 +27,39 @@ impl Function {
             .map_err(|err| self.incorrect_argument_type_error(name, err))
     }
 
+    pub fn get_non_negative_integer_argument(&self, name: &str) -> Result<u32, Error> {
+        self.get_required_argument(name)?
+            .non_negative_integer()
+            .map_err(|err| self.incorrect_argument_type_error(name, err))
+    }
+
+    // No function takes a boolean or float argument yet.
+    #[allow(dead_code)]
+    pub fn get_bool_argument(&self, name: &str) -> Result<bool, Error> {
+        self.get_required_argument(name)?
+            .boolean()
+            .map_err(|err| self.incorrect_argument_type_error(name, err))
+    }
+
+    #[allow(dead_code)]
+    pub fn get_float_argument(&self, name: &str) -> Result<f64, Error> {
+        self.get_required_argument(name)?
+            .float()
+            .map_err(|err| self.incorrect_argument_type_error(name, err))
+    }
+
+    pub fn get_list_argument(&self, name: &str) -> Result<Vec<String>, Error> {
+        self.get_required_argument(name)?
+            .string_list()
+            .map_err(|err| self.incorrect_argument_type_error(name, err))
+    }
+
+    pub fn get_map_argument(&self, name: &str) -> Result<Vec<(String, String)>, Error> {
+        self.get_required_argument(name)?
+            .string_map()
+            .map_err(|err| self.incorrect_argument_type_error(name, err))
+    }
+
     pub fn get_string_argument(&self, name: &str) -> Result<String, Error> {
         self.get_required_argument(name)?
             .string()
@@ -93,4 +126,87 @@ mod test {
             assert!(!f.has_argument("not-arg"));
         }
     }
+
+    mod typed_accessors {
+        use super::{ArgumentValue, Function};
+        use crate::parsers::function_string_parser::Error;
+        use maplit::hashmap;
+
+        #[test]
+        fn get_bool_argument_reports_the_argument_and_type() {
+            let f = Function::new(
+                "abc",
+                hashmap! { "flag".to_string() => ArgumentValue::Token("yes".to_string()) },
+            );
+
+            assert_eq!(
+                f.get_bool_argument("flag"),
+                Err(Error::IncorrectArgumentType {
+                    function: "abc".to_string(),
+                    argument: "flag".to_string(),
+                    expected: "boolean".to_string(),
+                    got: "token".to_string(),
+                })
+            );
+        }
+
+        #[test]
+        fn get_float_argument_reports_the_argument_and_type() {
+            let f = Function::new(
+                "abc",
+                hashmap! { "ratio".to_string() => ArgumentValue::Boolean(true) },
+            );
+
+            assert_eq!(
+                f.get_float_argument("ratio"),
+                Err(Error::IncorrectArgumentType {
+                    function: "abc".to_string(),
+                    argument: "ratio".to_string(),
+                    expected: "float".to_string(),
+                    got: "boolean".to_string(),
+                })
+            );
+        }
+
+        #[test]
+        fn get_non_negative_integer_argument_reports_a_negative_value() {
+            let f = Function::new(
+                "abc",
+                hashmap! { "timeout".to_string() => ArgumentValue::Integer(-1) },
+            );
+
+            assert_eq!(
+                f.get_non_negative_integer_argument("timeout"),
+                Err(Error::IncorrectArgumentType {
+                    function: "abc".to_string(),
+                    argument: "timeout".to_string(),
+                    expected: "non-negative integer".to_string(),
+                    got: "negative integer".to_string(),
+                })
+            );
+        }
+
+        #[test]
+        fn get_list_argument_reports_an_item_of_the_wrong_type() {
+            let f = Function::new(
+                "abc",
+                hashmap! {
+                    "items".to_string() => ArgumentValue::List(vec![
+                        ArgumentValue::String("a".to_string()),
+                        ArgumentValue::Boolean(true),
+                    ]),
+                },
+            );
+
+            assert_eq!(
+                f.get_list_argument("items"),
+                Err(Error::IncorrectArgumentType {
+                    function: "abc".to_string(),
+                    argument: "items".to_string(),
+                    expected: "list of strings".to_string(),
+                    got: "list containing boolean".to_string(),
+                })
+            );
+        }
+    }
 }
@@ -1,6 +1,7 @@
+pub use argument_value::ArgumentValue;
 pub use error::Error;
 pub use function::Function;
-pub use parser::parse;
+pub use parser::{parse, value_length};
 
 mod argument_value;
 mod error;
@@ -1,13 +1,13 @@
 use std::collections::HashMap;
 
-use nom::error::{ErrorKind, ParseError};
+use nom::error::{context, ContextError, ErrorKind, ParseError};
 use nom::{
     branch::alt,
-    bytes::streaming::{escaped, tag},
-    character::streaming::{alpha1, alphanumeric1, digit1, none_of, space0},
-    combinator::map,
+    bytes::complete::{escaped, tag, take_until, take_while},
+    character::complete::{alpha1, alphanumeric1, digit1, multispace0, none_of, space0},
+    combinator::{cut, map, opt, recognize},
     multi::{many0, separated_list0},
-    sequence::delimited,
+    sequence::{delimited, preceded, terminated},
     IResult, Parser,
 };
 
@@ -16,23 +16,52 @@ use super::function::Function;
 
 pub type Argument<'a> = (&'a str, ArgumentValue);
 
-pub fn parse<'a, E: ParseError<&'a str>>(input: &'a str) -> IResult<&'a str, Function, E> {
-    let p = (space0, alpha1, space0, argument_list);
+/// Parses a function. Once the start of an argument, value, string, list or
+/// map has been recognised, a mistake in the rest of it is a failure rather
+/// than a backtrack, so the error points at the mistake and says what was
+/// expected there.
+pub fn parse<'a, E: ParseError<&'a str> + ContextError<&'a str>>(
+    input: &'a str,
+) -> IResult<&'a str, Function, E> {
+    let p = (
+        space0,
+        context("expected a function name", identifier),
+        space0,
+        argument_list,
+    );
     map(p, |(_, name, _, arguments)| Function::new(name, arguments)).parse(input)
 }
 
-fn argument_list<'a, E: ParseError<&'a str>>(
+/// The length of the argument value at the start of `input`, if there is
+/// one, used to point at a value in an error.
+pub fn value_length(input: &str) -> Option<usize> {
+    argument_value::<nom::error::Error<&str>>(input)
+        .ok()
+        .map(|(remainder, _)| input.len() - remainder.len())
+}
+
+/// The arguments between parentheses. They may be spread over several lines,
+/// with a trailing comma, when the function is written in an annotation
+/// comment.
+fn argument_list<'a, E: ParseError<&'a str> + ContextError<&'a str>>(
     input: &'a str,
 ) -> IResult<&'a str, HashMap<String, ArgumentValue>, E> {
     let p = delimited(
-        (tag("("), space0),
-        separated_list0((space0, tag(","), space0), argument),
-        (space0, tag(")")),
+        (context("expected `(`", tag("(")), multispace0),
+        separated_list0(separator, argument),
+        cut(context(
+            "expected `,` or `)`",
+            (multispace0, opt(tag(",")), multispace0, tag(")")),
+        )),
     );
 
     map(p, |args| list_of_args_to_hash_map(&args)).parse(input)
 }
 
+fn separator<'a, E: ParseError<&'a str>>(input: &'a str) -> IResult<&'a str, &'a str, E> {
+    delimited(multispace0, tag(","), multispace0).parse(input)
+}
+
 fn list_of_args_to_hash_map(arguments: &[Argument<'_>]) -> HashMap<String, ArgumentValue> {
     arguments
         .iter()
@@ -40,46 +69,92 @@ fn list_of_args_to_hash_map(arguments: &[Argument<'_>]) -> HashMap<String, Argum
         .collect()
 }
 
-fn argument<'a, E: ParseError<&'a str>>(input: &'a str) -> IResult<&'a str, Argument<'a>, E> {
-    let p = (argument_name, (space0, tag("="), space0), argument_value);
-    map(p, |(name, _, value)| (name, value)).parse(input)
+fn argument<'a, E: ParseError<&'a str> + ContextError<&'a str>>(
+    input: &'a str,
+) -> IResult<&'a str, Argument<'a>, E> {
+    let p = (
+        identifier,
+        cut((context("expected `=`", (space0, tag("="), space0)), value)),
+    );
+    map(p, |(name, (_, value))| (name, value)).parse(input)
 }
 
-fn argument_name<'a, E: ParseError<&'a str>>(input: &'a str) -> IResult<&'a str, &'a str, E> {
+/// A function or argument name: a letter followed by letters, digits or `_`.
+fn identifier<'a, E: ParseError<&'a str>>(input: &'a str) -> IResult<&'a str, &'a str, E> {
     let mut p = (alpha1, many0(alt((alphanumeric1, tag("_")))));
     let (remainder, (start, parts)) = p.parse(input)?;
     let length = start.len() + parts.iter().map(|p| (*p).len()).sum::<usize>();
     Ok((remainder, &input[0..length]))
 }
 
-fn argument_value<'a, E: ParseError<&'a str>>(
+/// A value which must be there, such as after an argument's `=`.
+fn value<'a, E: ParseError<&'a str> + ContextError<&'a str>>(
     input: &'a str,
 ) -> IResult<&'a str, ArgumentValue, E> {
-    alt((integer_value, string_value, token_value)).parse(input)
+    cut(context("expected a value", argument_value)).parse(input)
 }
 
-fn integer_value<'a, E: ParseError<&'a str>>(input: &'a str) -> IResult<&'a str, ArgumentValue, E> {
-    let (remainder, digits) = digit1::<&'a str, E>.parse(input)?;
+fn argument_value<'a, E: ParseError<&'a str> + ContextError<&'a str>>(
+    input: &'a str,
+) -> IResult<&'a str, ArgumentValue, E> {
+    alt((
+        number_value,
+        raw_string_value,
+        string_value,
+        list_value,
+        map_value,
+        token_value,
+    ))
+    .parse(input)
+}
 
-    match digits.parse::<i32>() {
-        Ok(value) => Ok((remainder, ArgumentValue::Integer(value))),
-        Err(_) => Err(nom::Err::Error(E::from_error_kind(
+/// An integer, or a float when it has a fractional part. Either may be
+/// negative.
+fn number_value<'a, E: ParseError<&'a str> + ContextError<&'a str>>(
+    input: &'a str,
+) -> IResult<&'a str, ArgumentValue, E> {
+    let mut p = recognize((opt(tag("-")), digit1, opt((tag("."), digit1))));
+    let (remainder, number) = p.parse(input)?;
+
+    let value = if number.contains('.') {
+        number.parse::<f64>().ok().map(ArgumentValue::Float)
+    } else {
+        number.parse::<i32>().ok().map(ArgumentValue::Integer)
+    };
+
+    match value {
+        Some(value) => Ok((remainder, value)),
+        None => Err(nom::Err::Failure(E::add_context(
             input,
-            ErrorKind::MapRes,
+            "expected a number in range",
+            E::from_error_kind(input, ErrorKind::MapRes),
         ))),
     }
 }
 
-fn string_value<'a, E: ParseError<&'a str>>(input: &'a str) -> IResult<&'a str, ArgumentValue, E> {
-    let parser = delimited(
-        tag("\""),
-        escaped(none_of("\\\""), '\\', alt((tag("\""), tag("\\")))),
+fn string_value<'a, E: ParseError<&'a str> + ContextError<&'a str>>(
+    input: &'a str,
+) -> IResult<&'a str, ArgumentValue, E> {
+    map(quoted_string, ArgumentValue::String).parse(input)
+}
+
+/// A double-quoted string, in which `\"`, `\\`, `\n` and `\t` are escapes.
+fn quoted_string<'a, E: ParseError<&'a str> + ContextError<&'a str>>(
+    input: &'a str,
+) -> IResult<&'a str, String, E> {
+    let contents = escaped(
+        none_of("\\\""),
+        '\\',
+        alt((tag("\""), tag("\\"), tag("n"), tag("t"))),
+    );
+    let parser = preceded(
         tag("\""),
+        cut(context(
+            "expected a closing `\"`",
+            terminated(opt(contents), tag("\"")),
+        )),
     );
-    map(parser, |s: &'a str| {
-        ArgumentValue::String(unescape_string(s))
-    })
-    .parse(input)
+    map(parser, |contents| unescape_string(contents.unwrap_or(""))).parse(input)
 }
 
 fn unescape_string(s: &str) -> String {
@@ -87,8 +162,11 @@ fn unescape_string(s: &str) -> String {
     let mut chars = s.chars();
     while let Some(c) = chars.next() {
         if c == '\\' {
-            if let Some(next) = chars.next() {
-                result.push(next);
+            match chars.next() {
+                Some('n') => result.push('\n'),
+                Some('t') => result.push('\t'),
+                Some(next) => result.push(next),
+                None => {}
             }
         } else {
             result.push(c);
@@ -97,9 +175,70 @@ fn unescape_string(s: &str) -> String {
     result
 }
 
+/// A raw string, `r"..."` or `r#"..."#`, taken literally: there are no
+/// escapes, and with `#`s it may contain quotes.
+fn raw_string_value<'a, E: ParseError<&'a str> + ContextError<&'a str>>(
+    input: &'a str,
+) -> IResult<&'a str, ArgumentValue, E> {
+    let (remainder, hashes) = preceded(tag("r"), take_while(|c| c == '#')).parse(input)?;
+    let (remainder, _) = tag("\"").parse(remainder)?;
+    let closing = format!("\"{hashes}");
+    let (remainder, literal) = cut(context(
+        "expected the raw string to be closed",
+        terminated(take_until(closing.as_str()), tag(closing.as_str())),
+    ))
+    .parse(remainder)?;
+    Ok((remainder, ArgumentValue::String(literal.to_string())))
+}
+
+/// A list of values, `[a, "b", 3]`.
+fn list_value<'a, E: ParseError<&'a str> + ContextError<&'a str>>(
+    input: &'a str,
+) -> IResult<&'a str, ArgumentValue, E> {
+    let p = delimited(
+        (tag("["), multispace0),
+        separated_list0(separator, argument_value),
+        cut(context(
+            "expected `,` or `]`",
+            (multispace0, opt(tag(",")), multispace0, tag("]")),
+        )),
+    );
+    map(p, ArgumentValue::List).parse(input)
+}
+
+/// A map of values, `{key: "value", "Quoted-Key": 1}`, kept in the order
+/// written.
+fn map_value<'a, E: ParseError<&'a str> + ContextError<&'a str>>(
+    input: &'a str,
+) -> IResult<&'a str, ArgumentValue, E> {
+    let key = alt((map(identifier, ToString::to_string), quoted_string));
+    let entry = map(
+        (
+            key,
+            cut((
+                context("expected `:`", (multispace0, tag(":"), multispace0)),
+                value,
+            )),
+        ),
+        |(key, (_, value))| (key, value),
+    );
+    let p = delimited(
+        (tag("{"), multispace0),
+        separated_list0(separator, entry),
+        cut(context(
+            "expected `,` or `}`",
+            (multispace0, opt(tag(",")), multispace0, tag("}")),
+        )),
+    );
+    map(p, ArgumentValue::Map).parse(input)
+}
+
+/// A bare word, or a boolean when it's `true` or `false`.
 fn token_value<'a, E: ParseError<&'a str>>(input: &'a str) -> IResult<&'a str, ArgumentValue, E> {
-    map(alpha1, |token: &'a str| {
-        ArgumentValue::Token(token.to_string())
+    map(alpha1, |token: &'a str| match token {
+        "true" => ArgumentValue::Boolean(true),
+        "false" => ArgumentValue::Boolean(false),
+        _ => ArgumentValue::Token(token.to_string()),
     })
     .parse(input)
 }
@@ -126,6 +265,20 @@ mod tests {
             );
         }
 
+        #[test]
+        fn succeeds_when_function_name_contains_underscores_and_digits() {
+            assert_eq!(
+                parse::<nom::error::Error<&str>>("verify_tree2()"),
+                Ok((
+                    "",
+                    Function {
+                        name: "verify_tree2".to_string(),
+                        arguments: HashMap::new(),
+                    }
+                ))
+            );
+        }
+
         #[test]
         fn succeeds_when_function_has_leading_whitespace() {
             assert_eq!(
@@ -419,8 +572,6 @@ mod tests {
 
             #[test]
             fn fails_when_there_is_no_closing_quote() {
-                // The streaming-based escaped-quote parser returns Incomplete
-                // for an unterminated string, which is still an error.
                 assert!(
                     argument_value::<nom::error::Error<&str>>("\"arg_value2").is_err(),
                     "an unterminated string should produce an error"
@@ -507,6 +658,102 @@ mod tests {
         }
     }
 
+    mod richer_values {
+        use super::{argument_list, argument_value, ArgumentValue};
+        use maplit::hashmap;
+
+        fn value(input: &str) -> ArgumentValue {
+            argument_value::<nom::error::Error<&str>>(input)
+                .expect("value to parse")
+                .1
+        }
+
+        #[test]
+        fn parses_negative_integers_and_floats() {
+            assert_eq!(value("-5)"), ArgumentValue::Integer(-5));
+            assert_eq!(value("1.5)"), ArgumentValue::Float(1.5));
+            assert_eq!(value("-0.25)"), ArgumentValue::Float(-0.25));
+        }
+
+        #[test]
+        fn parses_booleans_but_keeps_other_words_as_tokens() {
+            assert_eq!(value("true)"), ArgumentValue::Boolean(true));
+            assert_eq!(value("false)"), ArgumentValue::Boolean(false));
+            assert_eq!(
+                value("trueish)"),
+                ArgumentValue::Token("trueish".to_string())
+            );
+        }
+
+        #[test]
+        fn parses_lists_of_mixed_values() {
+            assert_eq!(
+                value("[a, \"b\", 3,] rest"),
+                ArgumentValue::List(vec![
+                    ArgumentValue::Token("a".to_string()),
+                    ArgumentValue::String("b".to_string()),
+                    ArgumentValue::Integer(3),
+                ])
+            );
+            assert_eq!(value("[])"), ArgumentValue::List(vec![]));
+        }
+
+        #[test]
+        fn parses_maps_in_the_order_written() {
+            assert_eq!(
+                value("{b: 1, \"Content-Type\": \"text/plain\", nested: [x]})"),
+                ArgumentValue::Map(vec![
+                    ("b".to_string(), ArgumentValue::Integer(1)),
+                    (
+                        "Content-Type".to_string(),
+                        ArgumentValue::String("text/plain".to_string())
+                    ),
+                    (
+                        "nested".to_string(),
+                        ArgumentValue::List(vec![ArgumentValue::Token("x".to_string())])
+                    ),
+                ])
+            );
+        }
+
+        #[test]
+        fn parses_raw_strings_literally() {
+            assert_eq!(
+                value(r#"r"C:\path\n")"#),
+                ArgumentValue::String(r"C:\path\n".to_string())
+            );
+            assert_eq!(
+                value("r#\"say \"hi\"\"#)"),
+                ArgumentValue::String(r#"say "hi""#.to_string())
+            );
+        }
+
+        #[test]
+        fn parses_newline_and_tab_escapes_in_strings() {
+            assert_eq!(
+                value(r#""line one\n\tline two")"#),
+                ArgumentValue::String("line one\n\tline two".to_string())
+            );
+        }
+
+        #[test]
+        fn allows_arguments_over_several_lines() {
+            assert_eq!(
+                argument_list::<nom::error::Error<&str>>("(\n  a=1,\n  b=[x,\n    y],\n)"),
+                Ok((
+                    "",
+                    hashmap! {
+                        "a".to_string() => ArgumentValue::Integer(1),
+                        "b".to_string() => ArgumentValue::List(vec![
+                            ArgumentValue::Token("x".to_string()),
+                            ArgumentValue::Token("y".to_string()),
+                        ]),
+                    }
+                ))
+            );
+        }
+    }
+
     mod quickcheck_properties {
         use std::convert::TryFrom;
 
@@ -1,27 +1,63 @@
 use comrak::arena_tree::Children;
 use comrak::nodes::{Ast, AstNode, NodeCodeBlock, NodeValue};
-use comrak::{parse_document, Arena, Options};
+use comrak::{parse_document, Arena};
 use std::cell::RefCell;
 
+use crate::parsers::front_matter;
+
+/// An error in the Markdown around a spec file's specdown functions.
+// Each variant is described by its error message.
+#[allow(missing_docs)]
 #[derive(Clone, Debug, Eq, thiserror::Error, PartialEq)]
 pub enum Error {
     #[error("RootMustBeDocument :: This error should never occur")]
     RootMustBeDocument,
+    #[error(
+        "The annotation '<!-- specdown: {function} -->' must be directly followed by a fenced code block"
+    )]
+    AnnotationWithoutCodeBlock { function: String, line: usize },
+    #[error("The code block after the annotation '<!-- specdown: {function} -->' already has a function in its info string")]
+    AnnotationConflictsWithInfoString { function: String, line: usize },
+}
+
+impl Error {
+    /// The line in the Markdown the error was found on.
+    #[must_use]
+    pub fn line(&self) -> usize {
+        match self {
+            Self::RootMustBeDocument => 1,
+            Self::AnnotationWithoutCodeBlock { line, .. }
+            | Self::AnnotationConflictsWithInfoString { line, .. } => *line,
+        }
+    }
 }
 
+/// A Markdown element specdown reads, with the line and column it starts on.
 #[derive(Debug, Eq, PartialEq)]
 pub enum Element {
-    FencedCodeBlock { info: String, literal: String },
+    FencedCodeBlock {
+        info: String,
+        literal: String,
+        line: usize,
+        column: usize,
+    },
 }
 
 pub fn parse(markdown: &str) -> Result<Vec<Element>, Error> {
     let arena = Arena::new();
-    let root = parse_document(&arena, markdown, &Options::default());
+    let root = parse_document(&arena, markdown, &front_matter::options(markdown));
     extract_elements(root)
 }
 
+/// Extracts every fenced code block in document order, including those
+/// nested in lists, blockquotes and other container blocks.
 fn extract_elements<'a>(root: &'a AstNode<'a>) -> Result<Vec<Element>, Error> {
-    Ok(get_root_children(root)?.filter_map(to_element).collect())
+    get_root_children(root)?;
+
+    root.descendants()
+        .map(to_element)
+        .collect::<Result<Vec<Option<Element>>, Error>>()
+        .map(|elements| elements.into_iter().flatten().collect())
 }
 
 fn get_root_children<'a>(root: &'a AstNode<'a>) -> Result<Children<'a, RefCell<Ast>>, Error> {
@@ -37,7 +73,7 @@ fn get_root_children<'a>(root: &'a AstNode<'a>) -> Result<Children<'a, RefCell<A
 /// rather than just the top-level block elements.
 pub fn find_links(markdown: &str) -> Result<Vec<String>, Error> {
     let arena = Arena::new();
-    let root = parse_document(&arena, markdown, &Options::default());
+    let root = parse_document(&arena, markdown, &front_matter::options(markdown));
     get_root_children(root)?;
 
     Ok(root
@@ -49,18 +85,71 @@ pub fn find_links(markdown: &str) -> Result<Vec<String>, Error> {
         .collect())
 }
 
-fn to_element<'a>(node: &'a AstNode<'a>) -> Option<Element> {
+fn to_element<'a>(node: &'a AstNode<'a>) -> Result<Option<Element>, Error> {
     match node.data.borrow().value.clone() {
-        NodeValue::CodeBlock(block) => Some(block)
-            .filter(|b| b.fenced)
-            .map(|b| to_fenced_code_block_element(&b)),
+        NodeValue::CodeBlock(block) if block.fenced => {
+            let annotation = node.previous_sibling().and_then(to_annotation);
+            let position = node.data.borrow().sourcepos.start;
+            to_fenced_code_block_element(&block, annotation, position.line, position.column)
+                .map(Some)
+        }
+        NodeValue::HtmlBlock(_) => match to_annotation(node) {
+            Some(function) if !node.next_sibling().is_some_and(is_fenced_code_block) => {
+                Err(Error::AnnotationWithoutCodeBlock {
+                    function,
+                    line: start_line(node),
+                })
+            }
+            _ => Ok(None),
+        },
+        _ => Ok(None),
+    }
+}
+
+/// Returns the function from a `<!-- specdown: function(...) -->` comment,
+/// which annotates the fenced code block directly after it, as an
+/// alternative to writing the function in the block's info string.
+fn to_annotation<'a>(node: &'a AstNode<'a>) -> Option<String> {
+    match &node.data.borrow().value {
+        NodeValue::HtmlBlock(html) => html
+            .literal
+            .trim()
+            .strip_prefix("<!--")
+            .and_then(|comment| comment.strip_suffix("-->"))
+            .and_then(|comment| comment.trim().strip_prefix("specdown:"))
+            .map(|function| function.trim().to_string()),
         _ => None,
     }
 }
 
-fn to_fenced_code_block_element(block: &NodeCodeBlock) -> Element {
+fn start_line<'a>(node: &'a AstNode<'a>) -> usize {
+    node.data.borrow().sourcepos.start.line
+}
+
+fn is_fenced_code_block<'a>(node: &'a AstNode<'a>) -> bool {
+    matches!(&node.data.borrow().value, NodeValue::CodeBlock(block) if block.fenced)
+}
+
+fn to_fenced_code_block_element(
+    block: &NodeCodeBlock,
+    annotation: Option<String>,
+    line: usize,
+    column: usize,
+) -> Result<Element, Error> {
     let (info, literal) = node_block_to_components(block);
-    Element::FencedCodeBlock { info, literal }
+    let info = match annotation {
+        Some(function) if info.contains(',') => {
+            return Err(Error::AnnotationConflictsWithInfoString { function, line })
+        }
+        Some(function) => format!("{},{function}", info.trim()),
+        None => info,
+    };
+    Ok(Element::FencedCodeBlock {
+        info,
+        literal,
+        line,
+        column,
+    })
 }
 
 fn node_block_to_components(block: &NodeCodeBlock) -> (String, String) {
@@ -69,7 +158,7 @@ fn node_block_to_components(block: &NodeCodeBlock) -> (String, String) {
 
 #[cfg(test)]
 mod tests {
-    use super::{parse, Element};
+    use super::{parse, Element, Error};
     use indoc::indoc;
 
     #[test]
@@ -104,15 +193,161 @@ mod tests {
                 Element::FencedCodeBlock {
                     info: "info1".to_string(),
                     literal: "literal1\n".to_string(),
+                    line: 3,
+                    column: 1,
                 },
                 Element::FencedCodeBlock {
                     info: "info2".to_string(),
                     literal: "literal2\n".to_string(),
+                    line: 9,
+                    column: 1,
                 },
             ])
         );
     }
 
+    #[test]
+    fn an_annotation_comment_adds_its_function_to_the_following_code_block() {
+        let markdown = indoc!(
+            r#"
+            <!-- specdown: script(name="example") -->
+            ```shell
+            echo hello
+            ```
+
+            <!-- a regular comment -->
+
+            ```text
+            not annotated
+            ```
+            "#
+        );
+
+        assert_eq!(
+            parse(markdown),
+            Ok(vec![
+                Element::FencedCodeBlock {
+                    info: "shell,script(name=\"example\")".to_string(),
+                    literal: "echo hello\n".to_string(),
+                    line: 2,
+                    column: 1,
+                },
+                Element::FencedCodeBlock {
+                    info: "text".to_string(),
+                    literal: "not annotated\n".to_string(),
+                    line: 8,
+                    column: 1,
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn an_annotation_comment_applies_to_a_code_block_without_a_language() {
+        let markdown = "<!-- specdown: verify(script_name=\"x\") -->\n\n```\nhello\n```\n";
+
+        assert_eq!(
+            parse(markdown),
+            Ok(vec![Element::FencedCodeBlock {
+                info: ",verify(script_name=\"x\")".to_string(),
+                literal: "hello\n".to_string(),
+                line: 3,
+                column: 1,
+            }])
+        );
+    }
+
+    #[test]
+    fn an_annotation_comment_must_be_followed_by_a_fenced_code_block() {
+        let markdown = "<!-- specdown: script() -->\n\nSome text\n";
+
+        assert_eq!(
+            parse(markdown),
+            Err(Error::AnnotationWithoutCodeBlock {
+                function: "script()".to_string(),
+                line: 1
+            })
+        );
+    }
+
+    #[test]
+    fn an_annotation_comment_cannot_be_combined_with_an_info_string_function() {
+        let markdown = "<!-- specdown: script() -->\n```shell,script()\necho\n```\n";
+
+        assert_eq!(
+            parse(markdown),
+            Err(Error::AnnotationConflictsWithInfoString {
+                function: "script()".to_string(),
+                line: 2
+            })
+        );
+    }
+
+    #[test]
+    fn fenced_blocks_nested_in_lists_blockquotes_and_details_are_returned_in_order() {
+        let markdown = indoc!(
+            "
+            1. First step:
+
+               ```info1
+               literal1
+               ```
+
+            2. Second step:
+               - nested
+
+                 ```info2
+                 literal2
+                 ```
+
+            > ```info3
+            > literal3
+            > ```
+
+            <details>
+            <summary>More</summary>
+
+            ```info4
+            literal4
+            ```
+
+            </details>
+            "
+        );
+
+        let infos: Vec<String> = parse(markdown)
+            .expect("markdown to parse")
+            .into_iter()
+            .map(|Element::FencedCodeBlock { info, .. }| info)
+            .collect();
+
+        assert_eq!(infos, vec!["info1", "info2", "info3", "info4"]);
+    }
+
+    #[test]
+    fn an_annotation_comment_applies_inside_a_list_item() {
+        let markdown = indoc!(
+            "
+            - Run it:
+
+              <!-- specdown: script() -->
+              ```shell
+              echo hello
+              ```
+            "
+        );
+
+        assert_eq!(
+            parse(markdown),
+            Ok(vec![Element::FencedCodeBlock {
+                info: "shell,script()".to_string(),
+                literal: "echo hello\n".to_string(),
+                line: 4,
+                column: 3,
+            }])
+        );
+    }
+
     #[test]
     fn it_does_not_return_an_element_when_a_code_block_is_not_fenced() {
         let markdown = "# Non-fenced\n    this code block is not fenced";
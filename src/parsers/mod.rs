@@ -1,40 +1,115 @@
+//! Parsing specdown functions out of Markdown.
+
 use crate::types::Action;
 
+use code_block_type::CodeBlockType;
+
 mod actions;
+mod check;
 mod code_block_info;
 mod code_block_type;
+mod console;
+mod diagnostic;
 mod error;
+mod front_matter;
+mod function_schema;
 mod function_string_parser;
 mod markdown;
 mod strip;
 
 use error::Result;
 
+pub use check::{check, Problem};
 pub use strip::strip;
 
 pub use error::Error;
+pub use function_string_parser::Error as FunctionError;
+pub use markdown::Error as MarkdownError;
+
+pub(crate) use front_matter::{front_matter, Format as FrontMatterFormat};
+pub(crate) use function_schema::{argument_names, FUNCTIONS};
 
+/// Parses the actions from every specdown function in `markdown`, in
+/// document order.
+///
+/// # Errors
+///
+/// Returns an error if a function can not be parsed. An error in a code
+/// block's function is an [`Error::InCodeBlock`], locating it in the file.
 pub fn parse(markdown: &str) -> Result<Vec<Action>> {
     markdown::parse(markdown)?
         .iter()
-        .map(to_action)
-        .collect::<Result<Vec<Option<Action>>>>()
+        .map(|element| to_actions(element).map_err(|error| in_code_block(element, error)))
+        .collect::<Result<Vec<Vec<Action>>>>()
         .map(|actions| actions.into_iter().flatten().collect())
 }
 
 /// Finds the URLs of every Markdown link in `markdown`, in document order.
+///
+/// # Errors
+///
+/// Returns an error if the Markdown can not be parsed.
 pub fn find_links(markdown: &str) -> Result<Vec<String>> {
     self::markdown::find_links(markdown).map_err(Into::into)
 }
 
-fn to_action(element: &markdown::Element) -> Result<Option<Action>> {
+/// The name and line of every named `script`, `session` and `background`
+/// block in `markdown`, in document order. Blocks which can't be parsed are
+/// skipped.
+pub(crate) fn script_definitions(markdown: &str) -> Vec<(String, usize)> {
+    let Ok(elements) = markdown::parse(markdown) else {
+        return vec![];
+    };
+
+    elements
+        .iter()
+        .filter_map(|element| {
+            let markdown::Element::FencedCodeBlock { line, .. } = element;
+            let actions = to_actions(element).ok()?;
+            let name = actions.into_iter().find_map(|action| match action {
+                Action::Script(script) | Action::Session(script) => script.script_name,
+                Action::Background(background) => background.script_name,
+                _ => None,
+            })?;
+            Some((name.0, *line))
+        })
+        .collect()
+}
+
+fn in_code_block(element: &markdown::Element, error: Error) -> Error {
+    let markdown::Element::FencedCodeBlock {
+        info, line, column, ..
+    } = element;
+    Error::InCodeBlock {
+        line: *line,
+        column: *column,
+        info: info.clone(),
+        error: Box::new(error),
+    }
+}
+
+fn to_actions(element: &markdown::Element) -> Result<Vec<Action>> {
     match element {
-        markdown::Element::FencedCodeBlock { info, literal } => {
+        markdown::Element::FencedCodeBlock { info, literal, .. } => {
             if !info.contains(',') {
-                return Ok(None);
+                return Ok(vec![]);
+            }
+            let code_block_info = code_block_info::parse(info)?;
+            match (&code_block_info.extra, code_block_info.language.trim()) {
+                // A `console,session()` block is a transcript holding one
+                // command (and its expected output) per `$ ` prompt.
+                (CodeBlockType::Session(_), "console") => Ok(console::parse(literal)?
+                    .into_iter()
+                    .map(Action::ConsoleCommand)
+                    .collect()),
+                _ => Ok(actions::create_action(
+                    &code_block_info.extra,
+                    &code_block_info.language,
+                    literal.clone(),
+                )
+                .into_iter()
+                .collect()),
             }
-            let code_block_type = code_block_info::parse(info)?.extra;
-            Ok(actions::create_action(&code_block_type, literal.clone()))
         }
     }
 }
@@ -44,6 +119,7 @@ mod tests {
     use indoc::indoc;
 
     use super::parse;
+    use crate::types::{Action, ConsoleCommandAction, ExitCode, ScriptCode, VerifyValue};
 
     #[test]
     fn a_code_block_whose_info_string_is_not_a_specdown_function_is_ignored() {
@@ -68,4 +144,32 @@ mod tests {
 
         assert_eq!(parse(markdown), Ok(vec![]));
     }
+
+    #[test]
+    fn a_console_session_block_creates_an_action_per_command() {
+        let markdown = indoc! {r"
+            ```console,session()
+            $ echo one
+            one
+            $ false
+            [exit 1]
+            ```
+        "};
+
+        assert_eq!(
+            parse(markdown),
+            Ok(vec![
+                Action::ConsoleCommand(ConsoleCommandAction {
+                    command: ScriptCode("echo one".to_string()),
+                    expected_output: VerifyValue("one\n".to_string()),
+                    expected_exit_code: ExitCode(0),
+                }),
+                Action::ConsoleCommand(ConsoleCommandAction {
+                    command: ScriptCode("false".to_string()),
+                    expected_output: VerifyValue(String::new()),
+                    expected_exit_code: ExitCode(1),
+                }),
+            ])
+        );
+    }
 }
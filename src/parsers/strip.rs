@@ -1,12 +1,29 @@
 use comrak::nodes::{AstNode, NodeValue};
-use comrak::{format_commonmark, parse_document, Arena, Options};
-
-use super::code_block_info;
-
-pub fn strip(markdown: &str) -> String {
+use comrak::{format_commonmark, parse_document, Arena};
+
+use super::{code_block_info, front_matter};
+
+/// Removes the specdown functions from every code block's info string,
+/// leaving only the language. The front matter, which only holds specdown's
+/// settings, is removed too unless `keep_front_matter` is set.
+///
+/// # Panics
+///
+/// Panics if the stripped document can not be formatted as `CommonMark`.
+#[must_use]
+pub fn strip(markdown: &str, keep_front_matter: bool) -> String {
     let arena = Arena::new();
 
-    let root = parse_document(&arena, markdown, &Options::default());
+    let options = front_matter::options(markdown);
+    let root = parse_document(&arena, markdown, &options);
+
+    if !keep_front_matter {
+        if let Some(node) = root.first_child() {
+            if matches!(node.data.borrow().value, NodeValue::FrontMatter(_)) {
+                node.detach();
+            }
+        }
+    }
 
     iter_nodes(root, &|node| {
         if let NodeValue::CodeBlock(ref mut block) = node.data.borrow_mut().value {
@@ -18,7 +35,7 @@ pub fn strip(markdown: &str) -> String {
     });
 
     let mut result = String::new();
-    format_commonmark(root, &Options::default(), &mut result).unwrap();
+    format_commonmark(root, &options, &mut result).unwrap();
     result
 }
 
@@ -62,7 +79,7 @@ mod tests {
                 "
             );
 
-            assert_eq!(strip(markdown), expected.to_string());
+            assert_eq!(strip(markdown, false), expected.to_string());
         }
 
         #[test]
@@ -75,12 +92,24 @@ mod tests {
                 ```
             "};
 
-            let result = std::panic::catch_unwind(|| strip(markdown));
+            let result = std::panic::catch_unwind(|| strip(markdown, false));
             assert!(
                 result.is_ok(),
                 "strip panicked on a code block without a specdown function: {:?}",
                 result
             );
         }
+
+        const WITH_FRONT_MATTER: &str = "---\nskip: Not ready\n---\n\n# Header\n";
+
+        #[test]
+        fn removes_the_front_matter() {
+            assert_eq!(strip(WITH_FRONT_MATTER, false), "# Header\n");
+        }
+
+        #[test]
+        fn keeps_the_front_matter_when_asked_to() {
+            assert_eq!(strip(WITH_FRONT_MATTER, true), WITH_FRONT_MATTER);
+        }
     }
 }
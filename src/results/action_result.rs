@@ -1,26 +1,46 @@
+use std::fmt::Write;
+
 use crate::types::{
-    BackgroundAction, CreateFileAction, ExitCode, MockName, OutputExpectation, ScriptAction,
-    ScriptName, VerifyAction,
+    BackgroundAction, ConsoleCommandAction, CreateFileAction, ExitCode, MockCommandAction,
+    MockName, OutputExpectation, ScriptAction, ScriptName, VerifyAction, VerifyMockCommandAction,
+    VerifyTreeAction,
 };
 
+/// Why a function failed.
 #[derive(Debug, Eq, PartialEq)]
 pub enum ActionError {
+    /// A script exited with a different code to the expected one.
     ExitCodeIsIncorrect(ScriptResult),
+    /// A script wrote to a stream its `expected_output` said it wouldn't.
     UnexpectedOutputIsPresent(ScriptResult),
+    /// A `verify` block did not match the script's output.
     OutputDoesNotMatch(VerifyResult),
+    /// A background script exited with a non-zero code.
     BackgroundExitedWithError(BackgroundStopResult),
+    /// A `response` block has no matching request block.
     UnpairedResponse(MockName),
+    /// A `verify_tree` block did not match the directory listing.
+    TreeDoesNotMatch(VerifyTreeResult),
+    /// A `console` command's output or exit code did not match the transcript.
+    ConsoleOutputDoesNotMatch(ConsoleCommandResult),
+    /// A `verify(mock_command=...)` block did not match the calls made.
+    CallsDoNotMatch(VerifyMockCommandResult),
 }
 
 trait ActionErrorProvider {
     fn error(&self) -> Option<ActionError>;
 }
 
+/// The outcome of a `script` block.
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct ScriptResult {
+    /// The script which was run.
     pub action: ScriptAction,
+    /// The script's exit code, or `None` if it was killed by a signal.
     pub exit_code: Option<ExitCode>,
+    /// Everything the script wrote to stdout.
     pub stdout: String,
+    /// Everything the script wrote to stderr.
     pub stderr: String,
 }
 
@@ -52,9 +72,13 @@ impl ActionErrorProvider for ScriptResult {
     }
 }
 
+/// The outcome of a `verify` block: `got` is the script output it was
+/// compared with.
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct VerifyResult {
+    /// The verification which was run.
     pub action: VerifyAction,
+    /// The script output which was checked.
     pub got: String,
 }
 
@@ -71,8 +95,90 @@ impl ActionErrorProvider for VerifyResult {
     }
 }
 
+/// The outcome of a `verify_tree` block: `got` is the listing that was
+/// actually produced from the directory.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct VerifyTreeResult {
+    /// The verification which was run.
+    pub action: VerifyTreeAction,
+    /// The directory listing which was produced.
+    pub got: String,
+}
+
+impl ActionErrorProvider for VerifyTreeResult {
+    fn error(&self) -> Option<ActionError> {
+        let normalize = |s: &str| s.replace('\r', "");
+        let expected = normalize(&String::from(self.action.expected_value.clone()));
+        if expected == normalize(&self.got) {
+            None
+        } else {
+            Some(ActionError::TreeDoesNotMatch(self.clone()))
+        }
+    }
+}
+
+/// The outcome of one command from a `console` block. `output` holds
+/// stdout and stderr interleaved, as they would appear in a terminal.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ConsoleCommandResult {
+    /// The command which was run.
+    pub action: ConsoleCommandAction,
+    /// Everything the command wrote to stdout and stderr.
+    pub output: String,
+    /// The command's exit code, or `None` if it was killed by a signal.
+    pub exit_code: Option<ExitCode>,
+}
+
+impl ConsoleCommandResult {
+    /// The transcript written in the spec for this command: its output,
+    /// followed by `[exit N]` when it should exit with a non-zero code.
+    #[must_use]
+    pub fn expected_transcript(&self) -> String {
+        to_transcript(
+            &String::from(self.action.expected_output.clone()),
+            Some(self.action.expected_exit_code),
+        )
+    }
+
+    /// The transcript the command actually produced, in the same form as
+    /// `expected_transcript`.
+    #[must_use]
+    pub fn got_transcript(&self) -> String {
+        to_transcript(&self.output, self.exit_code)
+    }
+}
+
+/// Builds a console transcript. Missing trailing newlines are added, so
+/// output without one still matches the line-based expected output.
+fn to_transcript(output: &str, exit_code: Option<ExitCode>) -> String {
+    let mut transcript = output.replace('\r', "");
+    if !transcript.is_empty() && !transcript.ends_with('\n') {
+        transcript.push('\n');
+    }
+    match exit_code {
+        Some(ExitCode(0)) => {}
+        Some(ExitCode(code)) => {
+            let _ = writeln!(transcript, "[exit {code}]");
+        }
+        None => transcript.push_str("[exit none]\n"),
+    }
+    transcript
+}
+
+impl ActionErrorProvider for ConsoleCommandResult {
+    fn error(&self) -> Option<ActionError> {
+        if self.expected_transcript() == self.got_transcript() {
+            None
+        } else {
+            Some(ActionError::ConsoleOutputDoesNotMatch(self.clone()))
+        }
+    }
+}
+
+/// The outcome of a `file` block.
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct CreateFileResult {
+    /// The file which was created.
     pub action: CreateFileAction,
 }
 
@@ -82,8 +188,45 @@ impl ActionErrorProvider for CreateFileResult {
     }
 }
 
+/// The outcome of a `mock_command` block.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MockCommandResult {
+    /// The command which was mocked.
+    pub action: MockCommandAction,
+}
+
+impl ActionErrorProvider for MockCommandResult {
+    fn error(&self) -> Option<ActionError> {
+        None
+    }
+}
+
+/// The outcome of a `verify(mock_command=...)` block: `got` is the calls
+/// which were recorded, one per line.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct VerifyMockCommandResult {
+    /// The verification which was run.
+    pub action: VerifyMockCommandAction,
+    /// The calls which were made.
+    pub got: String,
+}
+
+impl ActionErrorProvider for VerifyMockCommandResult {
+    fn error(&self) -> Option<ActionError> {
+        let normalize = |s: &str| s.replace('\r', "");
+        let expected = normalize(&String::from(self.action.expected_value.clone()));
+        if expected == normalize(&self.got) {
+            None
+        } else {
+            Some(ActionError::CallsDoNotMatch(self.clone()))
+        }
+    }
+}
+
+/// The outcome of starting a `background` block.
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct BackgroundStartResult {
+    /// The background script which was started.
     pub action: BackgroundAction,
 }
 
@@ -93,9 +236,12 @@ impl ActionErrorProvider for BackgroundStartResult {
     }
 }
 
+/// The outcome of stopping a `background` block at the end of a spec file.
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct BackgroundStopResult {
+    /// The name of the background script, if it has one.
     pub script_name: Option<ScriptName>,
+    /// How the background script ended.
     pub exit_status: BackgroundExitStatus,
 }
 
@@ -111,7 +257,7 @@ impl ActionErrorProvider for BackgroundStopResult {
 }
 
 /// How a background process ended.
-#[derive(Clone, Debug, Eq, PartialEq)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum BackgroundExitStatus {
     /// The process was still running and specdown killed it.
     Killed,
@@ -120,7 +266,7 @@ pub enum BackgroundExitStatus {
 }
 
 /// Whether a response block was successfully paired with a request block.
-#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
 pub enum ResponseStatus {
     /// The response was paired with a matching request block.
     #[default]
@@ -129,9 +275,12 @@ pub enum ResponseStatus {
     Unpaired,
 }
 
+/// The outcome of a `response` block.
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct ResponseResult {
+    /// The name of the mock endpoint.
     pub name: MockName,
+    /// Whether the response was paired with a request.
     pub status: ResponseStatus,
 }
 
@@ -144,21 +293,40 @@ impl ActionErrorProvider for ResponseResult {
     }
 }
 
+/// The outcome of running a single function from a spec file.
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum ActionResult {
+    /// A `script` (or `session`) block was run.
     Script(ScriptResult),
+    /// A `verify` block was checked.
     Verify(VerifyResult),
+    /// A `file` block was created.
     CreateFile(CreateFileResult),
+    /// A `background` block was started.
     BackgroundStart(BackgroundStartResult),
+    /// A `background` block was stopped.
     BackgroundStop(BackgroundStopResult),
+    /// A `response` block was registered.
     Response(ResponseResult),
+    /// A `verify_tree` block was checked.
+    VerifyTree(VerifyTreeResult),
+    /// A command from a `console` block was run.
+    ConsoleCommand(ConsoleCommandResult),
+    /// A `mock_command` block was set up.
+    MockCommand(MockCommandResult),
+    /// A `verify(mock_command=...)` block was checked.
+    VerifyMockCommand(VerifyMockCommandResult),
 }
 
 impl ActionResult {
+    /// Whether the function succeeded.
+    #[must_use]
     pub fn success(&self) -> bool {
         self.error().is_none()
     }
 
+    /// Why the function failed, or `None` if it succeeded.
+    #[must_use]
     pub fn error(&self) -> Option<ActionError> {
         self.as_error_provider().error()
     }
@@ -171,6 +339,10 @@ impl ActionResult {
             Self::BackgroundStart(result) => result,
             Self::BackgroundStop(result) => result,
             Self::Response(result) => result,
+            Self::VerifyTree(result) => result,
+            Self::MockCommand(result) => result,
+            Self::VerifyMockCommand(result) => result,
+            Self::ConsoleCommand(result) => result,
         }
     }
 }
@@ -190,7 +362,9 @@ mod tests {
 
         mod error {
             use super::{ActionError, ActionResult, ScriptResult};
-            use crate::types::{ExitCode, OutputExpectation, ScriptAction, ScriptCode, ScriptName};
+            use crate::types::{
+                ExitCode, Interpreter, OutputExpectation, ScriptAction, ScriptCode, ScriptName,
+            };
 
             #[test]
             fn returns_none_when_successful_script() {
@@ -200,6 +374,7 @@ mod tests {
                         script_code: ScriptCode("example code".to_string()),
                         expected_exit_code: None,
                         expected_output: OutputExpectation::Any,
+                        interpreter: Interpreter::Shell,
                     },
                     exit_code: None,
                     stdout: String::new(),
@@ -217,6 +392,7 @@ mod tests {
                         script_code: ScriptCode("example code".to_string()),
                         expected_exit_code: Some(ExitCode(1)),
                         expected_output: OutputExpectation::Any,
+                        interpreter: Interpreter::Shell,
                     },
                     exit_code: Some(ExitCode(1)),
                     stdout: String::new(),
@@ -234,6 +410,7 @@ mod tests {
                         script_code: ScriptCode("example code".to_string()),
                         expected_exit_code: Some(ExitCode(1)),
                         expected_output: OutputExpectation::Any,
+                        interpreter: Interpreter::Shell,
                     },
                     exit_code: Some(ExitCode(2)),
                     stdout: String::new(),
@@ -256,6 +433,7 @@ mod tests {
                         script_code: ScriptCode("example code".to_string()),
                         expected_exit_code: None,
                         expected_output: OutputExpectation::StdOut,
+                        interpreter: Interpreter::Shell,
                     },
                     exit_code: None,
                     stdout: String::new(),
@@ -278,6 +456,7 @@ mod tests {
                         script_code: ScriptCode("example code".to_string()),
                         expected_exit_code: None,
                         expected_output: OutputExpectation::StdErr,
+                        interpreter: Interpreter::Shell,
                     },
                     exit_code: None,
                     stdout: "unexpected output".to_string(),
@@ -300,6 +479,7 @@ mod tests {
                         script_code: ScriptCode("example code".to_string()),
                         expected_exit_code: None,
                         expected_output: OutputExpectation::None,
+                        interpreter: Interpreter::Shell,
                     },
                     exit_code: None,
                     stdout: "unexpected output".to_string(),
@@ -322,6 +502,7 @@ mod tests {
                         script_code: ScriptCode("example code".to_string()),
                         expected_exit_code: None,
                         expected_output: OutputExpectation::None,
+                        interpreter: Interpreter::Shell,
                     },
                     exit_code: None,
                     stdout: String::new(),
@@ -478,5 +659,86 @@ mod tests {
                 assert!(!result.success());
             }
         }
+
+        mod verify_tree {
+            use crate::results::action_result::{ActionError, ActionResult, VerifyTreeResult};
+            use crate::types::{FilePath, TreeDetails, VerifyTreeAction, VerifyValue};
+
+            fn verify_tree_result(expected: &str, got: &str) -> VerifyTreeResult {
+                VerifyTreeResult {
+                    action: VerifyTreeAction {
+                        path: FilePath("out".to_string()),
+                        include: vec![],
+                        exclude: vec![],
+                        details: TreeDetails::default(),
+                        expected_value: VerifyValue(expected.to_string()),
+                    },
+                    got: got.to_string(),
+                }
+            }
+
+            #[test]
+            fn returns_none_when_listing_matches() {
+                let result = ActionResult::VerifyTree(verify_tree_result("a.txt\n", "a.txt\n"));
+                assert_eq!(result.error(), None);
+            }
+
+            #[test]
+            fn returns_tree_does_not_match_when_listing_differs() {
+                let tree_result = verify_tree_result("a.txt\n", "b.txt\n");
+                let result = ActionResult::VerifyTree(tree_result.clone());
+                assert_eq!(
+                    result.error(),
+                    Some(ActionError::TreeDoesNotMatch(tree_result))
+                );
+            }
+        }
+
+        mod console_command {
+            use crate::results::action_result::{ActionError, ActionResult, ConsoleCommandResult};
+            use crate::types::{ConsoleCommandAction, ExitCode, ScriptCode, VerifyValue};
+
+            fn console_result(
+                expected: &str,
+                expected_exit_code: i32,
+                output: &str,
+                exit_code: i32,
+            ) -> ConsoleCommandResult {
+                ConsoleCommandResult {
+                    action: ConsoleCommandAction {
+                        command: ScriptCode("cmd".to_string()),
+                        expected_output: VerifyValue(expected.to_string()),
+                        expected_exit_code: ExitCode(expected_exit_code),
+                    },
+                    output: output.to_string(),
+                    exit_code: Some(ExitCode(exit_code)),
+                }
+            }
+
+            #[test]
+            fn returns_none_when_output_and_exit_code_match() {
+                let result = ActionResult::ConsoleCommand(console_result("a\n", 2, "a\n", 2));
+                assert_eq!(result.error(), None);
+            }
+
+            #[test]
+            fn ignores_a_missing_trailing_newline() {
+                let result = ActionResult::ConsoleCommand(console_result("a\n", 0, "a", 0));
+                assert_eq!(result.error(), None);
+            }
+
+            #[test]
+            fn returns_console_output_does_not_match_when_exit_code_differs() {
+                let console_result = console_result("a\n", 0, "a\n", 1);
+                let result = ActionResult::ConsoleCommand(console_result.clone());
+                assert_eq!(
+                    result.error(),
+                    Some(ActionError::ConsoleOutputDoesNotMatch(
+                        console_result.clone()
+                    ))
+                );
+                assert_eq!(console_result.got_transcript(), "a\n[exit 1]\n");
+            }
+        }
     }
 }
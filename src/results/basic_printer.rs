@@ -1,17 +1,26 @@
-use std::path::Path;
+//! The printer used by the `specdown` command line tool.
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
 use crossterm::style::Stylize;
 
 use super::diff_theme::DIFF_THEME;
+use super::mask::Mask;
 use crate::ansi::strip_ansi_escape_chars;
 use crate::runner::Error;
 use crate::runner::RunEvent;
-use crate::types::{ExitCode, OutputExpectation, Stream, VerifyAction};
+use crate::types::{
+    ExitCode, OutputExpectation, ScriptCode, Stream, VerifyAction, VerifyMockCommandAction,
+    VerifyTreeAction,
+};
 
 use super::action_result::ActionResult;
 use super::action_result::{
     ActionError, BackgroundExitStatus, BackgroundStartResult, BackgroundStopResult,
-    CreateFileResult, ResponseResult, ScriptResult, VerifyResult,
+    ConsoleCommandResult, CreateFileResult, MockCommandResult, ResponseResult, ScriptResult,
+    VerifyMockCommandResult, VerifyResult, VerifyTreeResult,
 };
 use super::printer::Printer;
 
@@ -20,34 +29,96 @@ struct Summary {
     pub number_failed: u32,
 }
 
+/// A function which has been run, and how long it took.
+struct Timing {
+    spec_file: PathBuf,
+    title: String,
+    duration: Duration,
+}
+
+/// Prints each result as a line with a tick or a cross, and a summary
+/// after each spec file.
 pub struct BasicPrinter {
     display_function: Box<dyn Fn(&str) + Send + Sync>,
     summary: Summary,
     colour: bool,
+    durations: Option<usize>,
+    spec_file: PathBuf,
+    timings: Vec<Timing>,
+    mask: Mask,
 }
 
 impl BasicPrinter {
+    /// Creates a printer writing to stdout, with or without ANSI colours.
+    #[must_use]
     pub fn new(colour: bool) -> Self {
+        Self::with_display(colour, |line: &str| println!("{line}"))
+    }
+
+    /// Creates a printer which passes each line of output to `display`
+    /// instead of writing it to stdout.
+    #[must_use]
+    pub fn with_display(colour: bool, display: impl Fn(&str) + Send + Sync + 'static) -> Self {
         Self {
-            display_function: Box::new(|line: &str| println!("{line}")),
+            display_function: Box::new(display),
             summary: Summary {
                 number_succeeded: 0,
                 number_failed: 0,
             },
             colour,
+            durations: None,
+            spec_file: PathBuf::new(),
+            timings: Vec::new(),
+            mask: Mask::default(),
         }
     }
+
+    /// Shows how long each spec file took in its summary, and once the run
+    /// finishes, lists the `count` slowest functions and the run's total
+    /// duration.
+    #[must_use]
+    pub fn with_durations(mut self, count: usize) -> Self {
+        self.durations = Some(count);
+        self
+    }
+
+    /// Replaces the secrets hidden by `mask` with `***` in everything
+    /// printed, including script output and diffs.
+    #[must_use]
+    pub fn with_mask(mut self, mask: Mask) -> Self {
+        self.mask = mask;
+        self
+    }
+}
+
+impl fmt::Debug for BasicPrinter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BasicPrinter")
+            .field("colour", &self.colour)
+            .field("durations", &self.durations)
+            .finish_non_exhaustive()
+    }
 }
 
 impl Printer for BasicPrinter {
     fn print(&mut self, event: &RunEvent) {
         match event {
             RunEvent::SpecFileStarted(path) => self.print_spec_file(path),
-            RunEvent::TestCompleted(result) => self.print_result(result),
-            RunEvent::SpecFileCompleted { .. } => self.print_summary(),
+            RunEvent::TestCompleted(result, duration) => {
+                self.record_timing(result, *duration);
+                self.print_result(result);
+            }
+            RunEvent::SpecFileCompleted { duration, .. } => self.print_summary(*duration),
+            RunEvent::SpecFileSkipped { path, reason } => self.print_skipped(path, reason),
             RunEvent::ErrorOccurred(error) => self.print_error(error),
         }
     }
+
+    fn finish(&mut self, duration: Duration) {
+        if self.durations.is_some() {
+            self.print_slowest(duration);
+        }
+    }
 }
 
 impl BasicPrinter {
@@ -56,9 +127,18 @@ impl BasicPrinter {
             number_succeeded: 0,
             number_failed: 0,
         };
+        self.spec_file = path.to_path_buf();
         self.display(&format!(
             "Running tests for {}:\n",
-            path.display().to_string().bold().blue()
+            self.mask.apply(&path.display().to_string()).bold().blue()
+        ));
+    }
+
+    fn print_skipped(&self, path: &Path, reason: &str) {
+        self.display(&format!(
+            "Skipping {}: {}\n",
+            self.mask.apply(&path.display().to_string()).bold().blue(),
+            self.mask.apply(reason)
         ));
     }
 
@@ -74,15 +154,57 @@ impl BasicPrinter {
         self.display_error_item(&error.to_string());
     }
 
-    fn print_summary(&self) {
+    fn print_summary(&self, duration: Duration) {
+        let took = if self.durations.is_some() {
+            format!(" in {}", format_duration(duration))
+        } else {
+            String::new()
+        };
         self.display(&format!(
-            "\n  {} functions run ({} succeeded / {} failed)\n",
+            "\n  {} functions run ({} succeeded / {} failed){took}\n",
             self.summary.number_failed + self.summary.number_succeeded,
             self.summary.number_succeeded,
             self.summary.number_failed
         ));
     }
 
+    fn record_timing(&mut self, result: &ActionResult, duration: Duration) {
+        if self.durations.is_some() {
+            self.timings.push(Timing {
+                spec_file: self.spec_file.clone(),
+                title: Self::action_title(result),
+                duration,
+            });
+        }
+    }
+
+    /// Lists the slowest functions run, slowest first, followed by how long
+    /// the whole run took.
+    fn print_slowest(&mut self, duration: Duration) {
+        let count = self.durations.unwrap_or_default().min(self.timings.len());
+        if count > 0 {
+            self.timings
+                .sort_by_key(|timing| std::cmp::Reverse(timing.duration));
+            let lines: Vec<String> = self.timings[..count]
+                .iter()
+                .map(|timing| {
+                    format!(
+                        "  {:>8}  {}: {}",
+                        format_duration(timing.duration),
+                        timing.spec_file.display(),
+                        timing.title
+                    )
+                })
+                .collect();
+            self.display(&format!(
+                "Slowest {count} of {} functions:\n\n{}\n",
+                self.timings.len(),
+                self.mask.apply(&lines.join("\n"))
+            ));
+        }
+        self.display(&format!("Finished in {}", format_duration(duration)));
+    }
+
     fn display_action(&mut self, result: &ActionResult) {
         let title = Self::action_title(result);
         let result_message = Self::action_result_message(result);
@@ -94,7 +216,7 @@ impl BasicPrinter {
         }
     }
 
-    fn action_title(result: &ActionResult) -> String {
+    pub(crate) fn action_title(result: &ActionResult) -> String {
         match result {
             ActionResult::Script(ScriptResult { action, .. }) => {
                 format!(
@@ -137,6 +259,23 @@ impl BasicPrinter {
             ActionResult::Response(ResponseResult { name, .. }) => {
                 format!("setting response for mock '{name}'")
             }
+            ActionResult::VerifyTree(VerifyTreeResult { action, .. }) => {
+                format!("verifying tree '{}'", String::from(action.path.clone()))
+            }
+            ActionResult::MockCommand(MockCommandResult { action }) => match &action.args {
+                Some(args) => format!("mocking command '{}' with arguments '{args}'", action.name),
+                None => format!("mocking command '{}'", action.name),
+            },
+            ActionResult::VerifyMockCommand(VerifyMockCommandResult { action, .. }) => {
+                format!("verifying calls to '{}'", action.name)
+            }
+            ActionResult::ConsoleCommand(ConsoleCommandResult { action, .. }) => {
+                let ScriptCode(command) = &action.command;
+                format!(
+                    "running command '{}'",
+                    command.lines().next().unwrap_or_default()
+                )
+            }
         }
     }
 
@@ -168,7 +307,12 @@ impl BasicPrinter {
                     }
                 )
             }
-            Some(ActionError::OutputDoesNotMatch(_)) => "failed".to_string(),
+            Some(
+                ActionError::OutputDoesNotMatch(_)
+                | ActionError::TreeDoesNotMatch(_)
+                | ActionError::ConsoleOutputDoesNotMatch(_)
+                | ActionError::CallsDoNotMatch(_),
+            ) => "failed".to_string(),
             Some(ActionError::BackgroundExitedWithError(result)) => match result.exit_status {
                 BackgroundExitStatus::Exited(code) => {
                     format!("failed (exited with code {})", i32::from(code))
@@ -198,42 +342,61 @@ impl BasicPrinter {
             ActionError::OutputDoesNotMatch(VerifyResult {
                 action: VerifyAction { expected_value, .. },
                 got,
+            })
+            | ActionError::TreeDoesNotMatch(VerifyTreeResult {
+                action: VerifyTreeAction { expected_value, .. },
+                got,
+            })
+            | ActionError::CallsDoNotMatch(VerifyMockCommandResult {
+                action: VerifyMockCommandAction { expected_value, .. },
+                got,
             }) => {
                 self.display_diff(&String::from(expected_value.clone()), got);
             }
+            ActionError::ConsoleOutputDoesNotMatch(result) => {
+                self.display_diff(&result.expected_transcript(), &result.got_transcript());
+            }
             ActionError::BackgroundExitedWithError(_) | ActionError::UnpairedResponse(_) => {}
         }
     }
 
     fn display_diff(&mut self, expected: &str, actual: &str) {
+        // Masked before diffing, as highlighting the differences could
+        // split a secret up.
+        let expected = self.mask.apply(expected);
+        let actual = self.mask.apply(actual);
         self.display(&format!(
             "===\n{}\n===",
-            termdiff::DrawDiff::new(expected, actual, &DIFF_THEME)
+            termdiff::DrawDiff::new(&expected, &actual, &DIFF_THEME)
         ));
     }
 
     fn disply_all_output(&mut self, stdout: &str, stderr: &str) {
         self.display(&format!(
-            "\n=== stdout:\n{stdout}\n\n=== stderr:\n{stderr}\n\n"
+            "\n=== stdout:\n{}\n\n=== stderr:\n{}\n\n",
+            self.mask.apply(stdout),
+            self.mask.apply(stderr)
         ));
     }
 
+    /// Prints `text`, which must already be masked: masking it once it's
+    /// coloured could miss a secret split up by escape codes, or match
+    /// inside one.
     fn display(&self, text: &str) {
         let display = &self.display_function;
-        let prepared_text = if self.colour {
-            text.to_string()
+        if self.colour {
+            display(text);
         } else {
-            strip_ansi_escape_chars(text)
-        };
-        display(&prepared_text);
+            display(&strip_ansi_escape_chars(text));
+        }
     }
 
     fn display_success_item(&self, text: &str) {
-        self.display_success(&format!("  \u{2713} {text}"));
+        self.display_success(&format!("  \u{2713} {}", self.mask.apply(text)));
     }
 
     fn display_error_item(&self, text: &str) {
-        self.display_error(&format!("  \u{2717} {text}"));
+        self.display_error(&format!("  \u{2717} {}", self.mask.apply(text)));
     }
 
     fn display_success(&self, text: &str) {
@@ -245,6 +408,10 @@ impl BasicPrinter {
     }
 }
 
+fn format_duration(duration: Duration) -> String {
+    format!("{:.2}s", duration.as_secs_f64())
+}
+
 const fn stream_to_string(stream: &Stream) -> &str {
     match stream {
         Stream::StdOut => "stdout",
@@ -257,8 +424,8 @@ mod tests {
     use super::*;
     use crate::results::action_result::{ActionError, ActionResult};
     use crate::types::{
-        CreateFileAction, ExitCode, FileContent, FilePath, OutputExpectation, ScriptAction,
-        ScriptCode, ScriptName, Source, Stream, VerifyAction, VerifyValue,
+        CreateFileAction, ExitCode, FileContent, FilePath, Interpreter, OutputExpectation,
+        ScriptAction, ScriptCode, ScriptName, Source, Stream, VerifyAction, VerifyValue,
     };
     use std::path::PathBuf;
     use std::sync::{Arc, Mutex};
@@ -279,6 +446,10 @@ mod tests {
                 number_failed: 0,
             },
             colour: false,
+            durations: None,
+            spec_file: PathBuf::new(),
+            timings: Vec::new(),
+            mask: Mask::default(),
         };
         (printer, captured)
     }
@@ -290,6 +461,7 @@ mod tests {
                 script_code: ScriptCode("echo hello".to_string()),
                 expected_exit_code: None,
                 expected_output: OutputExpectation::Any,
+                interpreter: Interpreter::Shell,
             },
             exit_code: Some(ExitCode(0)),
             stdout: "hello".to_string(),
@@ -304,6 +476,7 @@ mod tests {
                 script_code: ScriptCode("exit 1".to_string()),
                 expected_exit_code: Some(ExitCode(0)),
                 expected_output: OutputExpectation::Any,
+                interpreter: Interpreter::Shell,
             },
             exit_code: Some(ExitCode(1)),
             stdout: "out".to_string(),
@@ -318,6 +491,7 @@ mod tests {
                 script_code: ScriptCode("cmd".to_string()),
                 expected_exit_code: None,
                 expected_output: OutputExpectation::StdOut,
+                interpreter: Interpreter::Shell,
             },
             exit_code: None,
             stdout: String::new(),
@@ -367,6 +541,17 @@ mod tests {
 
     // ---- exit_code_to_string (covers mutant at line 173) ----
 
+    #[test]
+    fn print_skipped_displays_the_reason() {
+        let (mut printer, captured) = create_capture_printer();
+        printer.print(&RunEvent::SpecFileSkipped {
+            path: PathBuf::from("slow.md"),
+            reason: "Needs a database".to_string(),
+        });
+        let output = captured.lock().expect("capture mutex poisoned");
+        assert_eq!(*output, "Skipping slow.md: Needs a database\n\n");
+    }
+
     #[test]
     fn exit_code_to_string_returns_string_representation_for_some() {
         assert_eq!(
@@ -391,6 +576,7 @@ mod tests {
                 script_code: ScriptCode("x".to_string()),
                 expected_exit_code: Some(ExitCode(0)),
                 expected_output: OutputExpectation::Any,
+                interpreter: Interpreter::Shell,
             },
             exit_code: Some(ExitCode(1)),
             stdout: "my-stdout".to_string(),
@@ -410,6 +596,53 @@ mod tests {
         );
     }
 
+    #[test]
+    fn display_action_error_masks_secrets_in_stdout_stderr_and_diffs() {
+        let (printer, captured) = create_capture_printer();
+        let mut printer =
+            printer.with_mask(Mask::new(vec!["s3cret".to_string()], vec![]).expect("valid mask"));
+        printer.display_action_error(&ActionError::ExitCodeIsIncorrect(ScriptResult {
+            action: ScriptAction {
+                script_name: Some(ScriptName("s".to_string())),
+                script_code: ScriptCode("x".to_string()),
+                expected_exit_code: Some(ExitCode(0)),
+                expected_output: OutputExpectation::Any,
+                interpreter: Interpreter::Shell,
+            },
+            exit_code: Some(ExitCode(1)),
+            stdout: "token=s3cret".to_string(),
+            stderr: "bad token s3cret".to_string(),
+        }));
+        printer.display_diff("token=s3crex\n", "token=s3cret\n");
+
+        let output = captured.lock().expect("capture mutex poisoned");
+        assert!(
+            !output.contains("s3cret"),
+            "the secret should be masked, got: {:?}",
+            output
+        );
+        assert!(output.contains("token=***"), "got: {:?}", output);
+        assert!(output.contains("bad token ***"), "got: {:?}", output);
+    }
+
+    #[test]
+    fn masks_secrets_before_colouring_them() {
+        let (printer, captured) = create_capture_printer();
+        let mut printer = printer.with_mask(
+            Mask::new(Vec::<String>::new(), vec!["[0-9]+".to_string()]).expect("valid mask"),
+        );
+        printer.colour = true;
+
+        printer.display_error_item("token 12345");
+
+        let output = captured.lock().expect("capture mutex poisoned");
+        assert_eq!(
+            *output,
+            format!("{}\n", "  \u{2717} token ***".red()),
+            "the escape codes should be left alone"
+        );
+    }
+
     #[test]
     fn display_action_error_shows_stdout_stderr_for_unexpected_output() {
         let (mut printer, captured) = create_capture_printer();
@@ -419,6 +652,7 @@ mod tests {
                 script_code: ScriptCode("x".to_string()),
                 expected_exit_code: None,
                 expected_output: OutputExpectation::StdOut,
+                interpreter: Interpreter::Shell,
             },
             exit_code: None,
             stdout: "extra-out".to_string(),
@@ -580,6 +814,64 @@ mod tests {
         );
     }
 
+    #[test]
+    fn failed_verify_tree_shows_the_path_and_a_diff() {
+        use crate::types::{TreeDetails, VerifyTreeAction};
+
+        let (mut printer, captured) = create_capture_printer();
+        let result = ActionResult::VerifyTree(VerifyTreeResult {
+            action: VerifyTreeAction {
+                path: FilePath("out".to_string()),
+                include: vec![],
+                exclude: vec![],
+                details: TreeDetails::default(),
+                expected_value: VerifyValue("a.txt\n".to_string()),
+            },
+            got: "b.txt\n".to_string(),
+        });
+        printer.print(&RunEvent::TestCompleted(result, Duration::ZERO));
+        let output = captured.lock().expect("capture mutex poisoned");
+        assert!(
+            output.contains("verifying tree 'out' failed"),
+            "expected verify_tree failure title, got: {:?}",
+            output
+        );
+        assert!(
+            output.contains("==="),
+            "expected a diff for a failed verify_tree, got: {:?}",
+            output
+        );
+    }
+
+    #[test]
+    fn failed_console_command_shows_the_command_and_a_transcript_diff() {
+        use crate::results::ConsoleCommandResult;
+        use crate::types::ConsoleCommandAction;
+
+        let (mut printer, captured) = create_capture_printer();
+        let result = ActionResult::ConsoleCommand(ConsoleCommandResult {
+            action: ConsoleCommandAction {
+                command: ScriptCode("grep x\nmore".to_string()),
+                expected_output: VerifyValue("x\n".to_string()),
+                expected_exit_code: ExitCode(0),
+            },
+            output: String::new(),
+            exit_code: Some(ExitCode(1)),
+        });
+        printer.print(&RunEvent::TestCompleted(result, Duration::ZERO));
+        let output = captured.lock().expect("capture mutex poisoned");
+        assert!(
+            output.contains("running command 'grep x' failed"),
+            "expected console command failure title, got: {:?}",
+            output
+        );
+        assert!(
+            output.contains("[exit 1]"),
+            "expected the exit code in the transcript diff, got: {:?}",
+            output
+        );
+    }
+
     // ---- display_action (covers mutant at line 84) ----
 
     #[test]
@@ -639,13 +931,16 @@ mod tests {
     #[test]
     fn print_test_completed_increments_success_count() {
         let (mut printer, _captured) = create_capture_printer();
-        let event = RunEvent::TestCompleted(successful_script_result());
+        let event = RunEvent::TestCompleted(successful_script_result(), Duration::ZERO);
         printer.print(&event);
         // Summary should show 1 succeeded, 0 failed
         // We verify by triggering the summary print
         let (mut printer2, captured2) = create_capture_printer();
         printer2.count_action(&successful_script_result());
-        let summary_event = RunEvent::SpecFileCompleted { success: true };
+        let summary_event = RunEvent::SpecFileCompleted {
+            success: true,
+            duration: Duration::ZERO,
+        };
         printer2.print(&summary_event);
         let output = captured2.lock().expect("capture mutex poisoned");
         assert!(
@@ -663,7 +958,7 @@ mod tests {
     #[test]
     fn print_test_completed_with_failure_shows_error_details() {
         let (mut printer, captured) = create_capture_printer();
-        let event = RunEvent::TestCompleted(failed_exit_code_result());
+        let event = RunEvent::TestCompleted(failed_exit_code_result(), Duration::ZERO);
         printer.print(&event);
         let output = captured.lock().expect("capture mutex poisoned");
         // The result is a failure, so it should display error details with stdout/stderr
@@ -682,7 +977,7 @@ mod tests {
     #[test]
     fn print_test_completed_with_verify_failure_shows_diff() {
         let (mut printer, captured) = create_capture_printer();
-        let event = RunEvent::TestCompleted(failed_verify_result());
+        let event = RunEvent::TestCompleted(failed_verify_result(), Duration::ZERO);
         printer.print(&event);
         let output = captured.lock().expect("capture mutex poisoned");
         assert!(
@@ -698,7 +993,10 @@ mod tests {
         // Count one success and one failure
         printer.count_action(&successful_script_result());
         printer.count_action(&failed_exit_code_result());
-        let event = RunEvent::SpecFileCompleted { success: false };
+        let event = RunEvent::SpecFileCompleted {
+            success: false,
+            duration: Duration::ZERO,
+        };
         printer.print(&event);
         let output = captured.lock().expect("capture mutex poisoned");
         assert!(
@@ -721,7 +1019,7 @@ mod tests {
     #[test]
     fn print_unexpected_output_failure_shows_output_sections() {
         let (mut printer, captured) = create_capture_printer();
-        let event = RunEvent::TestCompleted(failed_unexpected_output_result());
+        let event = RunEvent::TestCompleted(failed_unexpected_output_result(), Duration::ZERO);
         printer.print(&event);
         let output = captured.lock().expect("capture mutex poisoned");
         assert!(
@@ -750,8 +1048,12 @@ mod tests {
                 number_failed: 0,
             },
             colour: false, // no colour → ANSI should be stripped
+            durations: None,
+            spec_file: PathBuf::new(),
+            timings: Vec::new(),
+            mask: Mask::default(),
         };
-        let event = RunEvent::TestCompleted(successful_script_result());
+        let event = RunEvent::TestCompleted(successful_script_result(), Duration::ZERO);
         printer.print(&event);
         let output = captured.lock().expect("capture mutex poisoned");
         // In no-colour mode, crossterm's .green() / .red() / .blue() ANSI codes
@@ -763,6 +1065,81 @@ mod tests {
         );
     }
 
+    #[test]
+    fn durations_list_the_slowest_functions_once_finished() {
+        let (printer, captured) = create_capture_printer();
+        let mut printer = printer.with_durations(2);
+        printer.print(&RunEvent::SpecFileStarted(PathBuf::from("slow.md")));
+        printer.print(&RunEvent::TestCompleted(
+            successful_script_result(),
+            Duration::from_millis(250),
+        ));
+        printer.print(&RunEvent::TestCompleted(
+            failed_verify_result(),
+            Duration::from_millis(10),
+        ));
+        printer.print(&RunEvent::TestCompleted(
+            failed_exit_code_result(),
+            Duration::from_millis(1500),
+        ));
+        printer.print(&RunEvent::SpecFileCompleted {
+            success: false,
+            duration: Duration::from_millis(1760),
+        });
+        captured.lock().expect("capture mutex poisoned").clear();
+
+        printer.finish(Duration::from_secs(2));
+
+        let output = captured.lock().expect("capture mutex poisoned");
+        assert_eq!(
+            *output,
+            "Slowest 2 of 3 functions:\n\n\
+             \x20    1.50s  slow.md: running script 'bad_script'\n\
+             \x20    0.25s  slow.md: running script 'my_script'\n\n\
+             Finished in 2.00s\n"
+        );
+    }
+
+    #[test]
+    fn durations_add_the_time_taken_to_the_summary() {
+        let (printer, captured) = create_capture_printer();
+        let mut printer = printer.with_durations(1);
+
+        printer.print(&RunEvent::SpecFileCompleted {
+            success: true,
+            duration: Duration::from_millis(1234),
+        });
+
+        let output = captured.lock().expect("capture mutex poisoned");
+        assert!(
+            output.contains("(0 succeeded / 0 failed) in 1.23s"),
+            "summary should include the spec file's duration, got: {:?}",
+            output
+        );
+    }
+
+    #[test]
+    fn durations_are_not_shown_unless_asked_for() {
+        let (mut printer, captured) = create_capture_printer();
+        printer.print(&RunEvent::TestCompleted(
+            successful_script_result(),
+            Duration::from_secs(1),
+        ));
+        printer.print(&RunEvent::SpecFileCompleted {
+            success: true,
+            duration: Duration::from_secs(1),
+        });
+
+        printer.finish(Duration::from_secs(1));
+
+        let output = captured.lock().expect("capture mutex poisoned");
+        assert!(
+            !output.contains("1.00s") && !output.contains("Finished"),
+            "durations should not be shown, got: {:?}",
+            output
+        );
+    }
+
     #[test]
     fn unnamed_script_shows_unnamed_in_title() {
         let result = ActionResult::Script(ScriptResult {
@@ -771,6 +1148,7 @@ mod tests {
                 script_code: ScriptCode("echo".to_string()),
                 expected_exit_code: None,
                 expected_output: OutputExpectation::Any,
+                interpreter: Interpreter::Shell,
             },
             exit_code: Some(ExitCode(0)),
             stdout: String::new(),
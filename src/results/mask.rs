@@ -0,0 +1,114 @@
+//! Hiding secrets in the output specdown prints and records.
+
+use regex::Regex;
+
+use crate::runner::Error;
+
+/// What secrets are replaced with.
+const MASKED: &str = "***";
+
+/// Replaces secrets with `***` in the text it is applied to: the values of
+/// environment variables such as API tokens, and anything matching a regular
+/// expression.
+#[derive(Clone, Debug, Default)]
+pub struct Mask {
+    patterns: Vec<Regex>,
+}
+
+impl Mask {
+    /// Creates a mask hiding each of `values`, exactly as given, and every
+    /// match of the regular expressions in `patterns`. Empty values are left
+    /// out, as masking them would mask nothing but spoil the output.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidMask`] if one of `patterns` isn't a valid
+    /// regular expression, or a value is too long to search for.
+    pub fn new<V, P>(values: V, patterns: P) -> Result<Self, Error>
+    where
+        V: IntoIterator<Item = String>,
+        P: IntoIterator<Item = String>,
+    {
+        // Longer values first, so that a value containing another is masked
+        // whole.
+        let mut values: Vec<String> = values.into_iter().filter(|v| !v.is_empty()).collect();
+        values.sort_by_key(|value| std::cmp::Reverse(value.len()));
+        values.dedup();
+
+        let mut mask = Self::default();
+        for value in values {
+            // Reported masked, as the error shouldn't give the secret away.
+            let regex = Regex::new(&regex::escape(&value)).map_err(|err| Error::InvalidMask {
+                pattern: MASKED.to_string(),
+                message: err.to_string(),
+            })?;
+            mask.patterns.push(regex);
+        }
+        for pattern in patterns {
+            let regex = Regex::new(&pattern).map_err(|err| Error::InvalidMask {
+                pattern: pattern.clone(),
+                message: err.to_string(),
+            })?;
+            mask.patterns.push(regex);
+        }
+        Ok(mask)
+    }
+
+    /// `text` with every secret in it replaced with `***`.
+    #[must_use]
+    pub fn apply(&self, text: &str) -> String {
+        self.patterns
+            .iter()
+            .fold(text.to_string(), |text, pattern| {
+                pattern.replace_all(&text, MASKED).into_owned()
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Mask;
+    use crate::runner::Error;
+
+    fn strings(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| (*value).to_string()).collect()
+    }
+
+    #[test]
+    fn masks_values_exactly_as_given() {
+        let mask = Mask::new(strings(&["s3cr.t"]), vec![]).expect("valid mask");
+
+        assert_eq!("token=*** s3crxt", mask.apply("token=s3cr.t s3crxt"));
+    }
+
+    #[test]
+    fn masks_a_value_containing_another_whole() {
+        let mask = Mask::new(strings(&["abc", "abcdef"]), vec![]).expect("valid mask");
+
+        assert_eq!("*** ***", mask.apply("abcdef abc"));
+    }
+
+    #[test]
+    fn masks_matches_of_patterns() {
+        let mask = Mask::new(vec![], strings(&["ghp_[A-Za-z0-9]+"])).expect("valid mask");
+
+        assert_eq!(
+            "Authorization: ***",
+            mask.apply("Authorization: ghp_abc123")
+        );
+    }
+
+    #[test]
+    fn ignores_empty_values() {
+        let mask = Mask::new(strings(&[""]), vec![]).expect("valid mask");
+
+        assert_eq!("unchanged", mask.apply("unchanged"));
+    }
+
+    #[test]
+    fn errors_on_an_invalid_pattern() {
+        let result = Mask::new(vec![], strings(&["ghp_[a-z"]));
+
+        assert!(matches!(result, Err(Error::InvalidMask { pattern, .. }) if pattern == "ghp_[a-z"));
+    }
+}
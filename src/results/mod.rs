@@ -1,10 +1,15 @@
+//! The results of running spec file functions, and how they are printed.
+
 pub use action_result::{
-    ActionResult, BackgroundExitStatus, BackgroundStartResult, BackgroundStopResult,
-    CreateFileResult, ResponseResult, ResponseStatus, ScriptResult, VerifyResult,
+    ActionError, ActionResult, BackgroundExitStatus, BackgroundStartResult, BackgroundStopResult,
+    ConsoleCommandResult, CreateFileResult, MockCommandResult, ResponseResult, ResponseStatus,
+    ScriptResult, VerifyMockCommandResult, VerifyResult, VerifyTreeResult,
 };
+pub use mask::Mask;
 pub use printer::Printer;
 
 mod action_result;
 pub mod basic_printer;
 mod diff_theme;
+mod mask;
 mod printer;
@@ -1,5 +1,13 @@
+use std::time::Duration;
+
 use crate::runner::RunEvent;
 
+/// Reports the events of a run as they happen.
 pub trait Printer: Send {
+    /// Reports a single event.
     fn print(&mut self, event: &RunEvent);
+
+    /// Reports that every spec file has been run, taking `duration` in
+    /// total. Does nothing unless overridden.
+    fn finish(&mut self, _duration: Duration) {}
 }
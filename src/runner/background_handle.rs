@@ -10,9 +10,9 @@ use std::fmt::Debug;
 /// A handle to a background process that can be stopped and waited on.
 ///
 /// Implementations include:
-/// - [`ShellBackgroundHandle`](crate::runner::shell_executor::ShellBackgroundHandle)
+/// - `ShellBackgroundHandle`
 ///   wrapping `std::process::Child`
-/// - [`ContainerBackgroundHandle`](crate::runner::container_executor::ContainerBackgroundHandle)
+/// - `ContainerBackgroundHandle`
 ///   wrapping a Docker container managed via the socket API
 ///   (only available with the `container` feature)
 pub trait BackgroundHandle: Debug + Send {
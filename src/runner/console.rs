@@ -0,0 +1,26 @@
+use crate::results::{ActionResult, ConsoleCommandResult};
+use crate::types::{ConsoleCommandAction, ExitCode, ScriptCode};
+
+use super::error::Error;
+use super::executor::{Executor, Output};
+
+/// Runs one `console` command with stderr redirected into stdout, so the
+/// output is interleaved the way it would be in a terminal. The braces run
+/// it in the current shell, keeping any changes it makes to the session.
+pub fn run(action: &ConsoleCommandAction, executor: &dyn Executor) -> Result<ActionResult, Error> {
+    let ScriptCode(command) = &action.command;
+
+    executor
+        .execute(&ScriptCode(format!("{{ {command}\n}} 2>&1")))
+        .map(
+            |Output {
+                 stdout, exit_code, ..
+             }| {
+                ActionResult::ConsoleCommand(ConsoleCommandResult {
+                    action: action.clone(),
+                    output: stdout,
+                    exit_code: exit_code.map(ExitCode),
+                })
+            },
+        )
+}
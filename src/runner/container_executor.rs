@@ -38,7 +38,7 @@ use bollard::models::HostConfig;
 use bollard::Docker;
 use futures_util::StreamExt;
 
-use crate::types::ScriptCode;
+use crate::types::{Interpreter, ScriptCode};
 
 use super::background_handle::BackgroundHandle;
 use super::executor::Output;
@@ -86,6 +86,8 @@ pub struct ContainerExecutor {
     /// Label used to derive a unique container name (typically the
     /// spec file path). Incorporated into the container name as a hash.
     label: String,
+    /// Interpreter commands keyed by code block language.
+    interpreters: HashMap<String, String>,
 }
 
 impl ContainerExecutor {
@@ -156,9 +158,18 @@ impl ContainerExecutor {
             docker,
             container_id: Mutex::new(None),
             label: label.to_string(),
+            interpreters: HashMap::new(),
         })
     }
 
+    /// Sets the interpreter commands (e.g. `python3 -c`) used inside the
+    /// container for script blocks whose code block language matches the key.
+    #[must_use]
+    pub fn with_interpreters(mut self, interpreters: &[(String, String)]) -> Self {
+        self.interpreters = interpreters.iter().cloned().collect();
+        self
+    }
+
     /// Build the environment variable list for the container, applying the
     /// same semantics as `ShellExecutor`: extra paths are prepended to
     /// `PATH`, and `unset_env` variables are removed.
@@ -193,7 +204,11 @@ impl ContainerExecutor {
     /// Splits the shell command (e.g. `"bash -c"`) into individual words,
     /// then appends the script code as the final argument.
     fn exec_command(&self, code: &str) -> Vec<String> {
-        let mut words = shell_words::split(&self.shell_command).unwrap_or_default();
+        Self::exec_command_with(&self.shell_command, code)
+    }
+
+    fn exec_command_with(shell_command: &str, code: &str) -> Vec<String> {
+        let mut words = shell_words::split(shell_command).unwrap_or_default();
         if words.is_empty() {
             words = vec!["bash".to_string(), "-c".to_string()];
         }
@@ -331,15 +346,12 @@ impl ContainerExecutor {
             .expect("container_id mutex poisoned") = Some(container_id.clone());
         Ok(container_id)
     }
-}
 
-impl Executor for ContainerExecutor {
-    fn execute(&self, script: &ScriptCode) -> Result<Output, Error> {
-        let ScriptCode(code_string) = script;
+    /// Runs `cmd` in the persistent container and captures its output.
+    fn execute_command(&self, cmd: Vec<String>) -> Result<Output, Error> {
         let container_id = self.ensure_container()?;
 
         let docker = self.docker.clone();
-        let cmd = self.exec_command(code_string);
         let env = self.container_env();
         let working_dir = self.working_dir.clone();
 
@@ -415,6 +427,30 @@ impl Executor for ContainerExecutor {
             })
         })
     }
+}
+
+impl Executor for ContainerExecutor {
+    fn execute(&self, script: &ScriptCode) -> Result<Output, Error> {
+        let ScriptCode(code_string) = script;
+        self.execute_command(self.exec_command(code_string))
+    }
+
+    fn execute_with_interpreter(
+        &self,
+        interpreter: &Interpreter,
+        script: &ScriptCode,
+    ) -> Result<Output, Error> {
+        let interpreter_command = match interpreter {
+            Interpreter::Shell => None,
+            Interpreter::Language(language) => self.interpreters.get(language).map(String::as_str),
+            Interpreter::Command(command) => Some(command.as_str()),
+        };
+        let ScriptCode(code_string) = script;
+        match interpreter_command {
+            Some(command) => self.execute_command(Self::exec_command_with(command, code_string)),
+            None => self.execute(script),
+        }
+    }
 
     fn spawn(&self, script: &ScriptCode) -> Result<Box<dyn BackgroundHandle>, Error> {
         let ScriptCode(code_string) = script;
@@ -542,6 +578,12 @@ impl Executor for ContainerExecutor {
             .map(|(k, v)| (k.clone(), v.clone()))
             .collect();
 
+        let interpreters: Vec<(String, String)> = self
+            .interpreters
+            .iter()
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect();
+
         ContainerExecutor::new::<String>(
             &self.image,
             &self.shell_command,
@@ -551,6 +593,7 @@ impl Executor for ContainerExecutor {
             &self.binds,
             label,
         )
+        .map(|e| e.with_interpreters(&interpreters))
         .map_or_else(
             |err| {
                 // If we can't create a new executor, create a dummy that
@@ -2,22 +2,68 @@ use std::path::PathBuf;
 
 use crate::parsers;
 
+/// An error which stopped a function, or a whole run, from completing.
+// Each variant is described by its error message.
+#[allow(missing_docs)]
 #[derive(Debug, Eq, thiserror::Error, PartialEq, Clone)]
 pub enum Error {
+    #[error("Failed to read spec file '{path}': {message}")]
+    SpecFileUnreadable { path: String, message: String },
     #[error("Failed to follow link to '{path}': {message}")]
     LinkedFileUnreadable { path: String, message: String },
-    #[error("{0}")]
-    RunFailed(#[from] parsers::Error),
+    /// A spec file which could not be parsed, reported as a diagnostic
+    /// pointing at the mistake.
+    #[error("{}", error.render(path))]
+    RunFailed {
+        path: String,
+        error: Box<parsers::Error>,
+    },
+    #[error("No spec files found matching '{pattern}'")]
+    NoSpecFilesFound { pattern: String },
+    #[error("Invalid glob pattern '{pattern}': {message}")]
+    InvalidGlob { pattern: String, message: String },
+    #[error("Failed to read the front matter of '{path}': {message}")]
+    FrontMatterInvalid { path: String, message: String },
+    #[error("Failed to read env file '{path}': {message}")]
+    EnvFileUnreadable { path: String, message: String },
+    #[error("Invalid mask pattern '{pattern}': {message}")]
+    InvalidMask { pattern: String, message: String },
+    #[error("--hermetic and --pass-env are not supported with the container executor, which never passes on specdown's environment")]
+    HermeticNotSupported,
+    #[error("Failed to create the home directory '{path}': {message}")]
+    HomeUnwritable { path: String, message: String },
     #[error("Failed to load config file '{}': {message}", path.display())]
     ConfigFileLoadFailed { path: PathBuf, message: String },
     #[error("Failed to run command: {command} (Error: {message})")]
     CommandFailed { command: String, message: String },
     #[error("Failed to verify the output of '{missing_script_name}': No script with that name has been executed yet.")]
     ScriptOutputMissing { missing_script_name: String },
+    #[error("Failed to list the files under '{path}': {message}")]
+    TreeUnreadable { path: String, message: String },
+    #[error("Failed to list the files under '{path}': The path is outside the workspace")]
+    TreeOutsideWorkspace { path: String },
+    #[error("Mocking commands is not supported with this executor")]
+    MockCommandsNotSupported,
+    #[error("Failed to create the directory for mocked commands: {message}")]
+    StubsDirUnwritable { message: String },
+    #[error("Failed to write the stub for mock command '{name}': {message}")]
+    StubUnwritable { name: String, message: String },
+    #[error("Failed to read the calls made to mock command '{name}': {message}")]
+    CallsUnreadable { name: String, message: String },
     #[error("Invalid shell command provided: {command} (Error: {message})")]
     BadShellCommand { command: String, message: String },
+    #[error(
+        "Running scripts with the interpreter '{command}' is not supported with this executor"
+    )]
+    InterpreterNotSupported { command: String },
     #[error("Background scripts are not supported with this executor")]
     BackgroundNotSupported,
+    #[error("Shell sessions are not supported with this executor")]
+    SessionNotSupported,
+    #[error("Failed to run the shell session: {message}")]
+    SessionFailed { message: String },
+    #[error("The shell session has already exited, so no more scripts can be run in it")]
+    SessionEnded,
     #[error("The mock server has not been started")]
     MockServerNotStarted,
     #[error("Failed to spawn background process: {message}")]
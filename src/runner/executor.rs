@@ -1,11 +1,18 @@
-use crate::types::ScriptCode;
+use std::path::Path;
+
+use crate::types::{Interpreter, ScriptCode};
 
 use super::background_handle::BackgroundHandle;
 use super::Error;
 
+/// What a script printed and how it exited.
+#[derive(Debug)]
 pub struct Output {
+    /// Everything the script wrote to stdout.
     pub stdout: String,
+    /// Everything the script wrote to stderr.
     pub stderr: String,
+    /// The script's exit code, or `None` if it was killed by a signal.
     pub exit_code: Option<i32>,
 }
 
@@ -23,14 +30,83 @@ impl From<std::process::Output> for Output {
     }
 }
 
+/// Runs the scripts in spec files.
+///
+/// Implement this to run scripts somewhere other than a local shell; only
+/// `execute()` is required.
 pub trait Executor: Send + Sync {
+    /// Runs `script` to completion and returns its output.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the script could not be run at all. A script
+    /// which runs and fails is reported through its exit code instead.
     fn execute(&self, script: &ScriptCode) -> Result<Output, Error>;
 
+    /// Executes `script` with the given interpreter instead of the shell
+    /// command.
+    ///
+    /// The default implementation runs `Shell` and `Language` scripts with
+    /// `execute()` (i.e. no languages have interpreters configured) and
+    /// rejects explicit `script(interpreter=...)` commands. Executors that
+    /// support interpreters should override this.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the script could not be run with the interpreter.
+    fn execute_with_interpreter(
+        &self,
+        interpreter: &Interpreter,
+        script: &ScriptCode,
+    ) -> Result<Output, Error> {
+        match interpreter {
+            Interpreter::Shell | Interpreter::Language(_) => self.execute(script),
+            Interpreter::Command(command) => Err(Error::InterpreterNotSupported {
+                command: command.clone(),
+            }),
+        }
+    }
+
+    /// Starts a persistent session in which every `execute()` call shares
+    /// the same shell state (working directory, exported variables,
+    /// functions and aliases).
+    ///
+    /// Each spec file run in session mode gets its own session, which ends
+    /// when the returned executor is dropped. The default implementation
+    /// returns `SessionNotSupported`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the session could not be started.
+    fn start_session(&self) -> Result<Box<dyn Executor>, Error> {
+        Err(Error::SessionNotSupported)
+    }
+
+    /// Starts `script` without waiting for it to finish, for `background`
+    /// blocks.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the script could not be started. The default
+    /// implementation returns `BackgroundNotSupported`.
     fn spawn(&self, script: &ScriptCode) -> Result<Box<dyn BackgroundHandle>, Error> {
         let _ = script;
         Err(Error::BackgroundNotSupported)
     }
 
+    /// Returns an executor which runs scripts with `stubs_dir` at the start
+    /// of `PATH`, so the stubs written there for `mock_command` blocks are
+    /// run instead of the real commands.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the executor can't run the stubs. The default
+    /// implementation returns `MockCommandsNotSupported`.
+    fn with_mock_commands(&self, stubs_dir: &Path) -> Result<Box<dyn Executor>, Error> {
+        let _ = stubs_dir;
+        Err(Error::MockCommandsNotSupported)
+    }
+
     /// Create a boxed clone of this executor.
     ///
     /// Used when running spec files in parallel (`--jobs > 1`): each
@@ -0,0 +1,293 @@
+//! Stubbing commands for `mock_command` blocks.
+//!
+//! Each mocked command gets a `/bin/sh` stub in a temporary directory, which
+//! the spec file's scripts have at the start of their `PATH`. The stub appends each call it gets to
+//! a `<name>.calls` file next to it, then answers with the output of the
+//! last `mock_command` block whose `args` pattern matches.
+
+use std::fmt::Write;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::Path;
+
+use tempfile::TempDir;
+
+use crate::results::{ActionResult, MockCommandResult, VerifyMockCommandResult};
+use crate::types::{MockCommandAction, Stream, VerifyMockCommandAction};
+
+use super::Error;
+
+/// Records the call as the arguments would be written in a shell, quoting
+/// any which aren't made up of only safe characters.
+const RECORD_CALL: &str = r#"call=$(basename "$0")
+for arg in "$@"; do
+  case "$arg" in
+    '' | *[!A-Za-z0-9_./:=@%+,-]*)
+      call="$call '$(printf '%s' "$arg" | sed "s/'/'\\\\''/g")'"
+      ;;
+    *)
+      call="$call $arg"
+      ;;
+  esac
+done
+"#;
+
+/// Creates the directory a spec file's stubs are written to, which is
+/// removed when it is dropped.
+pub fn create_stubs_dir() -> Result<TempDir, Error> {
+    tempfile::Builder::new()
+        .prefix("specdown-mock-commands-")
+        .tempdir()
+        .map_err(|err| Error::StubsDirUnwritable {
+            message: err.to_string(),
+        })
+}
+
+/// Writes the stub for `action`'s command to `stubs_dir`, answering with
+/// every mock of that command in `mocks`, the later ones first.
+pub fn run(
+    action: &MockCommandAction,
+    mocks: &[MockCommandAction],
+    stubs_dir: &Path,
+) -> Result<ActionResult, Error> {
+    let stub_path = stubs_dir.join(&action.name);
+    let mocks: Vec<&MockCommandAction> = mocks
+        .iter()
+        .filter(|mock| mock.name == action.name)
+        .collect();
+
+    let write_stub = || -> std::io::Result<()> {
+        let calls_path = stubs_dir.join(format!("{}.calls", action.name));
+        fs::write(&stub_path, stub(&mocks, &calls_path))?;
+        make_executable(&stub_path)
+    };
+    write_stub().map_err(|err| Error::StubUnwritable {
+        name: action.name.clone(),
+        message: err.to_string(),
+    })?;
+
+    Ok(ActionResult::MockCommand(MockCommandResult {
+        action: action.clone(),
+    }))
+}
+
+/// Checks the calls recorded in `stubs_dir` by the stub for `action`'s
+/// command, which are empty when it hasn't been called.
+pub fn verify(action: &VerifyMockCommandAction, stubs_dir: &Path) -> Result<ActionResult, Error> {
+    let calls_path = stubs_dir.join(format!("{}.calls", action.name));
+    let got = match fs::read_to_string(calls_path) {
+        Ok(calls) => calls,
+        Err(err) if err.kind() == ErrorKind::NotFound => String::new(),
+        Err(err) => {
+            return Err(Error::CallsUnreadable {
+                name: action.name.clone(),
+                message: err.to_string(),
+            })
+        }
+    };
+
+    Ok(ActionResult::VerifyMockCommand(VerifyMockCommandResult {
+        action: action.clone(),
+        got,
+    }))
+}
+
+fn stub(mocks: &[&MockCommandAction], calls_path: &Path) -> String {
+    let mut script = String::from("#!/bin/sh\n");
+    script.push_str(RECORD_CALL);
+    let _ = writeln!(
+        script,
+        "printf '%s\\n' \"$call\" >> {}",
+        quote(&calls_path.to_string_lossy())
+    );
+
+    script.push_str("case \"$*\" in\n");
+    for mock in mocks.iter().rev() {
+        let pattern = mock
+            .args
+            .as_deref()
+            .map_or_else(|| "*".to_string(), to_pattern);
+        let redirect = match mock.stream {
+            Stream::StdOut => "",
+            Stream::StdErr => " >&2",
+        };
+        let _ = write!(
+            script,
+            "  {pattern})\n    printf '%s' {}{redirect}\n    exit {}\n    ;;\n",
+            quote(&mock.output),
+            i32::from(mock.exit_code)
+        );
+    }
+    script.push_str("esac\n");
+    script.push_str("printf 'specdown: no mock_command matches: %s\\n' \"$call\" >&2\nexit 127\n");
+    script
+}
+
+/// Quotes `value` for the shell, so it is taken literally.
+fn quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+/// Turns an `args` pattern into a `case` pattern: `*`, `?` and bracket
+/// expressions keep their meaning, everything else is matched literally.
+fn to_pattern(args: &str) -> String {
+    let mut pattern = String::new();
+    let mut literal = String::new();
+    let mut rest = args;
+
+    while let Some(c) = rest.chars().next() {
+        let special = match c {
+            '*' | '?' => Some(c.len_utf8()),
+            '[' => rest[1..].find(']').map(|end| end + 2),
+            _ => None,
+        };
+        if let Some(length) = special {
+            if !literal.is_empty() {
+                pattern.push_str(&quote(&literal));
+                literal.clear();
+            }
+            pattern.push_str(&rest[..length]);
+            rest = &rest[length..];
+        } else {
+            literal.push(c);
+            rest = &rest[c.len_utf8()..];
+        }
+    }
+    if !literal.is_empty() || pattern.is_empty() {
+        pattern.push_str(&quote(&literal));
+    }
+    pattern
+}
+
+#[cfg(not(windows))]
+fn make_executable(path: &Path) -> std::io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    fs::set_permissions(path, fs::Permissions::from_mode(0o755))
+}
+
+/// Windows can't run the `/bin/sh` stubs, so no executor supports mocking
+/// commands there and this is never reached.
+#[cfg(windows)]
+fn make_executable(_path: &Path) -> std::io::Result<()> {
+    Err(std::io::Error::new(
+        ErrorKind::Unsupported,
+        "stubs can't be run on Windows",
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::to_pattern;
+
+    #[test]
+    fn to_pattern_quotes_everything_but_wildcards() {
+        assert_eq!(to_pattern("log -n 1 *"), "'log -n 1 '*");
+        assert_eq!(to_pattern("get pod?"), "'get pod'?");
+        assert_eq!(to_pattern("it's $HOME"), r"'it'\''s $HOME'");
+    }
+
+    #[test]
+    fn to_pattern_keeps_bracket_expressions() {
+        assert_eq!(to_pattern("-[vq] status"), "'-'[vq]' status'");
+        assert_eq!(to_pattern("a[b"), "'a[b'");
+    }
+
+    #[test]
+    fn to_pattern_matches_no_arguments_when_empty() {
+        assert_eq!(to_pattern(""), "''");
+    }
+
+    #[cfg(not(windows))]
+    mod stub {
+        use std::path::Path;
+        use std::process::Command;
+
+        use crate::results::{ActionResult, VerifyMockCommandResult};
+        use crate::types::{
+            ExitCode, MockCommandAction, Stream, VerifyMockCommandAction, VerifyValue,
+        };
+
+        use super::super::{run, verify};
+
+        fn mock(
+            args: Option<&str>,
+            stream: Stream,
+            exit_code: i32,
+            output: &str,
+        ) -> MockCommandAction {
+            MockCommandAction {
+                name: "git".to_string(),
+                args: args.map(ToString::to_string),
+                stream,
+                exit_code: ExitCode(exit_code),
+                output: output.to_string(),
+            }
+        }
+
+        fn call(stubs_dir: &Path, args: &[&str]) -> std::process::Output {
+            Command::new(stubs_dir.join("git"))
+                .args(args)
+                .output()
+                .expect("stub to run")
+        }
+
+        #[test]
+        fn answers_with_the_last_matching_mock_and_records_each_call() {
+            let dir = tempfile::tempdir().expect("temp dir");
+            let mocks = vec![
+                mock(None, Stream::StdOut, 0, "git version 2.40.0\n"),
+                mock(
+                    Some("status *"),
+                    Stream::StdErr,
+                    128,
+                    "fatal: not a git repository\n",
+                ),
+            ];
+            run(&mocks[1], &mocks, dir.path()).expect("stub to be written");
+
+            let output = call(dir.path(), &["--version"]);
+            assert_eq!(output.status.code(), Some(0));
+            assert_eq!(
+                String::from_utf8_lossy(&output.stdout),
+                "git version 2.40.0\n"
+            );
+
+            let output = call(dir.path(), &["status", "--short"]);
+            assert_eq!(output.status.code(), Some(128));
+            assert_eq!(
+                String::from_utf8_lossy(&output.stderr),
+                "fatal: not a git repository\n"
+            );
+
+            call(dir.path(), &["commit", "-m", "It's done"]);
+
+            let action = VerifyMockCommandAction {
+                name: "git".to_string(),
+                expected_value: VerifyValue(String::new()),
+            };
+            match verify(&action, dir.path()) {
+                Ok(ActionResult::VerifyMockCommand(VerifyMockCommandResult { got, .. })) => {
+                    assert_eq!(
+                        got,
+                        "git --version\ngit status --short\ngit commit -m 'It'\\''s done'\n"
+                    );
+                }
+                other => panic!("expected the calls to be read, got: {:?}", other),
+            }
+        }
+
+        #[test]
+        fn fails_when_no_mock_matches() {
+            let dir = tempfile::tempdir().expect("temp dir");
+            let mocks = vec![mock(Some("status"), Stream::StdOut, 0, "")];
+            run(&mocks[0], &mocks, dir.path()).expect("stub to be written");
+
+            let output = call(dir.path(), &["push"]);
+            assert_eq!(output.status.code(), Some(127));
+            assert_eq!(
+                String::from_utf8_lossy(&output.stderr),
+                "specdown: no mock_command matches: git push\n"
+            );
+        }
+    }
+}
@@ -1,94 +1,180 @@
+//! Running the actions parsed from a spec file.
+
+pub use background_handle::BackgroundHandle;
 pub use error::Error;
 pub use executor::Executor;
-#[allow(unused_imports)]
 pub use executor::Output;
 pub use run_event::RunEvent;
-pub use runnable_action::to_runnable;
-pub use state::State;
+use runnable_action::to_runnable;
+pub use state::{ScriptOutput, State};
 
 use std::path::Path;
+use std::time::Instant;
+
+use tempfile::TempDir;
 
 use crate::results::{ActionResult, ResponseResult, ResponseStatus};
-use crate::types::Action;
+use crate::types::{Action, MockCommandAction};
 
 mod background;
 mod background_handle;
+mod console;
 #[cfg(feature = "container")]
 pub(crate) mod container_executor;
 mod error;
 mod executor;
 mod file;
+mod mock_command;
 mod run_event;
 mod runnable_action;
 mod script;
 pub mod shell_executor;
+mod shell_session;
 mod state;
 mod verify;
+mod verify_tree;
 
+/// Runs the actions of a single spec file, in order.
+#[allow(missing_debug_implementations)]
 pub struct Runner<'a> {
     executor: &'a dyn Executor,
     working_dir: &'a Path,
     state: &'a mut State,
+    session: bool,
     background_processes: Vec<background::BackgroundProcess>,
+    mock_commands: Vec<MockCommandAction>,
+    stubs_dir: Option<TempDir>,
 }
 
 impl<'a> Runner<'a> {
+    /// Creates a runner which executes scripts with `executor` and creates
+    /// files in `working_dir`, recording results in `state`.
     pub fn create(executor: &'a dyn Executor, working_dir: &'a Path, state: &'a mut State) -> Self {
         Runner {
             executor,
             working_dir,
             state,
+            session: false,
             background_processes: Vec::new(),
+            mock_commands: Vec::new(),
+            stubs_dir: None,
         }
     }
 
+    /// Runs every script in one persistent shell session, started from the
+    /// executor when `run()` is called.
+    #[must_use]
+    pub fn with_session(mut self, session: bool) -> Self {
+        self.session = session;
+        self
+    }
+
+    /// Runs `actions` and returns an event for each of them, followed by
+    /// the results of stopping any background scripts they started. Each
+    /// result is timed from the moment its action starts until it finishes.
+    ///
+    /// When the actions mock commands, their stubs are written to a
+    /// temporary directory of this run's own, which is removed once the
+    /// actions have run.
     pub fn run(&mut self, actions: &[Action]) -> Vec<RunEvent> {
+        let mocked = if actions.iter().any(|action| {
+            matches!(
+                action,
+                Action::MockCommand(_) | Action::VerifyMockCommand(_)
+            )
+        }) {
+            match self.mock_commands_executor() {
+                Ok(executor) => Some(executor),
+                Err(error) => return vec![RunEvent::ErrorOccurred(error)],
+            }
+        } else {
+            None
+        };
+        let executor = mocked.as_deref().unwrap_or(self.executor);
+
+        let session = if self.session {
+            match executor.start_session() {
+                Ok(session) => Some(session),
+                Err(error) => return vec![RunEvent::ErrorOccurred(error)],
+            }
+        } else {
+            None
+        };
+        let executor = session.as_deref().unwrap_or(executor);
+
         let mut events: Vec<RunEvent> = actions
             .iter()
-            .map(|action| self.run_action(action))
+            .map(|action| self.run_action(action, executor))
             .collect();
 
         // Stop all background processes
         for bg in self.background_processes.drain(..) {
+            let started = Instant::now();
             let result = background::stop(bg);
             self.state.add_result(&result);
-            events.push(RunEvent::TestCompleted(result));
+            events.push(RunEvent::TestCompleted(result, started.elapsed()));
         }
 
+        self.mock_commands.clear();
+        self.stubs_dir = None;
+
         events
     }
 
-    fn run_action(&mut self, action: &Action) -> RunEvent {
-        match action {
+    /// Creates the directory for this run's stubs, and an executor which
+    /// has it at the start of `PATH`.
+    fn mock_commands_executor(&mut self) -> Result<Box<dyn Executor>, Error> {
+        let stubs_dir = mock_command::create_stubs_dir()?;
+        let executor = self.executor.with_mock_commands(stubs_dir.path())?;
+        self.stubs_dir = Some(stubs_dir);
+        Ok(executor)
+    }
+
+    fn stubs_dir(&self) -> Result<&Path, Error> {
+        self.stubs_dir
+            .as_ref()
+            .map(TempDir::path)
+            .ok_or(Error::MockCommandsNotSupported)
+    }
+
+    fn run_action(&mut self, action: &Action, executor: &dyn Executor) -> RunEvent {
+        let started = Instant::now();
+        let result = match action {
             Action::Background(bg_action) => {
-                match background::start(bg_action, self.executor, self.working_dir) {
-                    Ok((result, bg_process)) => {
-                        self.state.add_result(&result);
+                background::start(bg_action, executor, self.working_dir).map(
+                    |(result, bg_process)| {
                         self.background_processes.push(bg_process);
-                        RunEvent::TestCompleted(result)
-                    }
-                    Err(error) => RunEvent::ErrorOccurred(error),
-                }
+                        result
+                    },
+                )
+            }
+            Action::MockCommand(mock_action) => {
+                self.mock_commands.push(mock_action.clone());
+                self.stubs_dir().and_then(|stubs_dir| {
+                    mock_command::run(mock_action, &self.mock_commands, stubs_dir)
+                })
             }
+            Action::VerifyMockCommand(verify_action) => self
+                .stubs_dir()
+                .and_then(|stubs_dir| mock_command::verify(verify_action, stubs_dir)),
             Action::Response(resp_action) => {
                 // Response actions require a mock server which is not yet
                 // implemented. Return an unpaired result so the error is
                 // visible in test output.
-                let result = ActionResult::Response(ResponseResult {
+                Ok(ActionResult::Response(ResponseResult {
                     name: resp_action.name.clone(),
                     status: ResponseStatus::Unpaired,
-                });
+                }))
+            }
+            _ => to_runnable(action).run(self.state, executor, self.working_dir),
+        };
+
+        match result {
+            Ok(result) => {
                 self.state.add_result(&result);
-                RunEvent::TestCompleted(result)
+                RunEvent::TestCompleted(result, started.elapsed())
             }
-            _ => to_runnable(action)
-                .run(self.state, self.executor, self.working_dir)
-                .map(|result| {
-                    self.state.add_result(&result);
-                    RunEvent::TestCompleted(result)
-                })
-                .or_else::<Error, _>(|error| Ok(RunEvent::ErrorOccurred(error)))
-                .unwrap(),
+            Err(error) => RunEvent::ErrorOccurred(error),
         }
     }
 }
@@ -98,8 +184,8 @@ mod tests {
     use super::*;
     use crate::runner::executor::Output;
     use crate::types::{
-        CreateFileAction, FileContent, FilePath, OutputExpectation, ScriptAction, ScriptCode,
-        ScriptName,
+        CreateFileAction, FileContent, FilePath, Interpreter, OutputExpectation, ScriptAction,
+        ScriptCode, ScriptName,
     };
     use std::sync::Mutex;
 
@@ -147,6 +233,7 @@ mod tests {
                 script_code: ScriptCode("echo hello".to_string()),
                 expected_exit_code: None,
                 expected_output: OutputExpectation::Any,
+                interpreter: Interpreter::Shell,
             }),
             Action::CreateFile(CreateFileAction {
                 file_path: FilePath("test.txt".to_string()),
@@ -172,7 +259,7 @@ mod tests {
 
         // 3. The events contain the correct variants
         match &events[0] {
-            RunEvent::TestCompleted(result) => {
+            RunEvent::TestCompleted(result, _) => {
                 // The script action should produce a successful Script result
                 assert!(result.success(), "script action should succeed");
             }
@@ -181,7 +268,7 @@ mod tests {
         }
 
         match &events[1] {
-            RunEvent::TestCompleted(result) => {
+            RunEvent::TestCompleted(result, _) => {
                 // The create file action should produce a successful CreateFile result
                 assert!(result.success(), "create file action should succeed");
             }
@@ -204,6 +291,7 @@ mod tests {
             script_code: ScriptCode("bad_cmd".to_string()),
             expected_exit_code: None,
             expected_output: OutputExpectation::Any,
+            interpreter: Interpreter::Shell,
         })];
 
         let events = runner.run(&actions);
@@ -221,7 +309,7 @@ mod tests {
                     error
                 );
             }
-            RunEvent::TestCompleted(_) => panic!("expected error event, got TestCompleted"),
+            RunEvent::TestCompleted(..) => panic!("expected error event, got TestCompleted"),
             _ => panic!("unexpected event type"),
         }
     }
@@ -250,6 +338,7 @@ mod tests {
             script_code: ScriptCode("echo".to_string()),
             expected_exit_code: None,
             expected_output: OutputExpectation::Any,
+            interpreter: Interpreter::Shell,
         })];
 
         runner.run(&actions);
@@ -260,4 +349,113 @@ mod tests {
             "state should be success after a passing script"
         );
     }
+
+    fn mock_date() -> Action {
+        Action::MockCommand(MockCommandAction {
+            name: "date".to_string(),
+            args: None,
+            stream: crate::types::Stream::StdOut,
+            exit_code: crate::types::ExitCode(0),
+            output: "MOCKED\n".to_string(),
+        })
+    }
+
+    #[test]
+    fn run_returns_an_error_when_the_executor_cannot_mock_commands() {
+        let mock = MockExecutor::with_success(Some(0), "", "");
+        let mut state = State::new();
+        let mut runner = Runner::create(&mock, Path::new("."), &mut state);
+
+        let events = runner.run(&[mock_date()]);
+
+        match &events[..] {
+            [RunEvent::ErrorOccurred(Error::MockCommandsNotSupported)] => {}
+            _ => panic!(
+                "expected a MockCommandsNotSupported error, got: {:?}",
+                events
+            ),
+        }
+    }
+
+    #[cfg(not(windows))]
+    #[test]
+    fn run_mocks_commands_outside_the_working_dir_until_the_run_ends() {
+        use crate::runner::shell_executor::ShellExecutor;
+
+        let working_dir = tempfile::tempdir().expect("failed to create temp dir");
+        let shell = ShellExecutor::new::<std::path::PathBuf>("sh -c", &[], &[], &[])
+            .expect("shell to be created")
+            .with_working_dir(working_dir.path().to_path_buf());
+        let mut state = State::new();
+        let mut runner = Runner::create(&shell, working_dir.path(), &mut state);
+
+        let events = runner.run(&[
+            mock_date(),
+            Action::Script(ScriptAction {
+                script_name: Some(ScriptName("date".to_string())),
+                script_code: ScriptCode("date; dirname \"$(command -v date)\"".to_string()),
+                expected_exit_code: Some(crate::types::ExitCode(0)),
+                expected_output: OutputExpectation::Any,
+                interpreter: Interpreter::Shell,
+            }),
+        ]);
+
+        let stubs_dir = match &events[..] {
+            [RunEvent::TestCompleted(_, _), RunEvent::TestCompleted(ActionResult::Script(result), _)] =>
+            {
+                let mut lines = result.stdout.lines();
+                assert_eq!(lines.next(), Some("MOCKED"));
+                std::path::PathBuf::from(lines.next().expect("the stub's directory"))
+            }
+            _ => panic!(
+                "expected the mock and script to complete, got: {:?}",
+                events
+            ),
+        };
+        assert!(!stubs_dir.starts_with(working_dir.path()));
+        assert!(!stubs_dir.exists(), "stubs should be removed after the run");
+        assert_eq!(
+            std::fs::read_dir(working_dir.path())
+                .expect("working dir to be readable")
+                .count(),
+            0
+        );
+    }
+
+    /// An executor which takes a while to run each script.
+    struct SlowExecutor;
+
+    impl Executor for SlowExecutor {
+        fn execute(&self, _script: &ScriptCode) -> Result<Output, Error> {
+            std::thread::sleep(std::time::Duration::from_millis(20));
+            Ok(Output {
+                stdout: String::new(),
+                stderr: String::new(),
+                exit_code: Some(0),
+            })
+        }
+    }
+
+    #[test]
+    fn run_times_each_action() {
+        let mut state = State::new();
+        let mut runner = Runner::create(&SlowExecutor, Path::new("."), &mut state);
+
+        let events = runner.run(&[Action::Script(ScriptAction {
+            script_name: Some(ScriptName("slow".to_string())),
+            script_code: ScriptCode("sleep".to_string()),
+            expected_exit_code: None,
+            expected_output: OutputExpectation::Any,
+            interpreter: Interpreter::Shell,
+        })]);
+
+        match &events[..] {
+            [RunEvent::TestCompleted(_, duration)] => assert!(
+                *duration >= std::time::Duration::from_millis(20),
+                "the script's duration should include the time it ran for, got: {:?}",
+                duration
+            ),
+            _ => panic!("expected a single TestCompleted event, got: {:?}", events),
+        }
+    }
 }
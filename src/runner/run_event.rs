@@ -1,12 +1,32 @@
 use std::path::PathBuf;
+use std::time::Duration;
 
 use super::Error;
 use crate::results::ActionResult;
 
-#[derive(Clone)]
+/// Something which happened while running spec files, as reported to a
+/// [`Printer`](crate::results::Printer).
+#[derive(Clone, Debug)]
 pub enum RunEvent {
+    /// A spec file is about to be run.
     SpecFileStarted(PathBuf),
-    TestCompleted(ActionResult),
-    SpecFileCompleted { success: bool },
+    /// A function in the current spec file has been run, taking the given
+    /// wall-clock time.
+    TestCompleted(ActionResult, Duration),
+    /// Every function in the current spec file has been run.
+    SpecFileCompleted {
+        /// Whether every function succeeded.
+        success: bool,
+        /// How long the spec file took to read, parse and run.
+        duration: Duration,
+    },
+    /// A spec file wasn't run, for the reason given in its front matter.
+    SpecFileSkipped {
+        /// The spec file's path.
+        path: PathBuf,
+        /// Why it wasn't run.
+        reason: String,
+    },
+    /// An error stopped a function, or the whole spec file, from running.
     ErrorOccurred(Error),
 }
@@ -2,22 +2,27 @@ use std::path::Path;
 
 use crate::results::ActionResult;
 use crate::types::{
-    Action, BackgroundAction, CreateFileAction, ResponseAction, ScriptAction, VerifyAction,
+    Action, BackgroundAction, ConsoleCommandAction, CreateFileAction, MockCommandAction,
+    ResponseAction, ScriptAction, VerifyAction, VerifyMockCommandAction, VerifyTreeAction,
 };
 
-use super::{error, file, script, verify, Error, Executor, State};
+use super::{console, error, file, script, verify, verify_tree, Error, Executor, State};
 
-pub fn to_runnable(action: &Action) -> &dyn RunnableAction {
+pub(super) fn to_runnable(action: &Action) -> &dyn RunnableAction {
     match action {
-        Action::Script(a) => a,
+        Action::Script(a) | Action::Session(a) => a,
         Action::Verify(a) => a,
         Action::CreateFile(a) => a,
         Action::Background(a) => a,
         Action::Response(a) => a,
+        Action::VerifyTree(a) => a,
+        Action::ConsoleCommand(a) => a,
+        Action::MockCommand(a) => a,
+        Action::VerifyMockCommand(a) => a,
     }
 }
 
-pub trait RunnableAction {
+pub(super) trait RunnableAction {
     fn run(
         &self,
         state: &State,
@@ -37,6 +42,17 @@ impl RunnableAction for ScriptAction {
     }
 }
 
+impl RunnableAction for ConsoleCommandAction {
+    fn run(
+        &self,
+        _state: &State,
+        executor: &dyn Executor,
+        _working_dir: &Path,
+    ) -> Result<ActionResult, Error> {
+        console::run(self, executor)
+    }
+}
+
 impl RunnableAction for VerifyAction {
     fn run(
         &self,
@@ -59,6 +75,42 @@ impl RunnableAction for CreateFileAction {
     }
 }
 
+impl RunnableAction for VerifyTreeAction {
+    fn run(
+        &self,
+        _state: &State,
+        _executor: &dyn Executor,
+        working_dir: &Path,
+    ) -> Result<ActionResult, Error> {
+        verify_tree::run(self, working_dir)
+    }
+}
+
+impl RunnableAction for MockCommandAction {
+    fn run(
+        &self,
+        _state: &State,
+        _executor: &dyn Executor,
+        _working_dir: &Path,
+    ) -> Result<ActionResult, Error> {
+        // The stubs live in a directory the Runner creates for each spec
+        // file, so it handles mock_command actions itself.
+        Err(Error::MockCommandsNotSupported)
+    }
+}
+
+impl RunnableAction for VerifyMockCommandAction {
+    fn run(
+        &self,
+        _state: &State,
+        _executor: &dyn Executor,
+        _working_dir: &Path,
+    ) -> Result<ActionResult, Error> {
+        // Handled by the Runner, which knows where the calls are recorded.
+        Err(Error::MockCommandsNotSupported)
+    }
+}
+
 impl RunnableAction for BackgroundAction {
     fn run(
         &self,
@@ -1,19 +1,23 @@
+//! Running scripts with a local shell.
+
 use std::collections::HashMap;
 use std::process::Command;
 
 use shell_words::ParseError;
 
-use crate::types::ScriptCode;
+use crate::types::{Interpreter, ScriptCode};
 
 use super::background_handle::BackgroundHandle;
 use super::executor::Output;
+use super::shell_session::ShellSession;
 use super::{Error, Executor};
 use std::env;
 use std::env::JoinPathsError;
 use std::ffi::{OsStr, OsString};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
-#[derive(Debug, Eq, PartialEq)]
+/// Runs scripts with a local shell command such as `bash -c`.
+#[derive(Debug, Clone, Eq, PartialEq)]
 pub struct ShellExecutor {
     command: String,
     args: Vec<String>,
@@ -21,9 +25,19 @@ pub struct ShellExecutor {
     unset_env: Vec<String>,
     paths: Vec<PathBuf>,
     working_dir: Option<PathBuf>,
+    interpreters: HashMap<String, String>,
+    clean_env: bool,
+    stubs_dir: Option<PathBuf>,
 }
 
 impl ShellExecutor {
+    /// Creates an executor which runs scripts with `shell_command`, with
+    /// `env` set, `unset_env` removed and `paths` prepended to `PATH`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `BadShellCommand` if `shell_command` is empty or can not be
+    /// split into words.
     pub fn new<P>(
         shell_command: &str,
         env: &[(String, String)],
@@ -56,6 +70,9 @@ impl ShellExecutor {
             unset_env: unset_env.to_vec(),
             paths: paths.iter().map(PathBuf::from).collect(),
             working_dir: None,
+            interpreters: HashMap::new(),
+            clean_env: false,
+            stubs_dir: None,
         }
     }
 
@@ -68,6 +85,34 @@ impl ShellExecutor {
         self
     }
 
+    /// Sets the interpreter commands (e.g. `python3 -c`) used for script
+    /// blocks whose code block language matches the key. Languages without
+    /// an entry keep running with the shell command.
+    #[must_use]
+    pub fn with_interpreters(mut self, interpreters: &[(String, String)]) -> Self {
+        self.interpreters = interpreters.iter().cloned().collect();
+        self
+    }
+
+    /// Starts commands with only the environment given to `new()` and
+    /// `PATH`, rather than inheriting the current process's environment.
+    #[must_use]
+    pub fn with_clean_env(mut self) -> Self {
+        self.clean_env = true;
+        self
+    }
+
+    pub(super) fn interpreter_command<'a>(
+        &'a self,
+        interpreter: &'a Interpreter,
+    ) -> Option<&'a str> {
+        match interpreter {
+            Interpreter::Shell => None,
+            Interpreter::Language(language) => self.interpreters.get(language).map(String::as_str),
+            Interpreter::Command(command) => Some(command),
+        }
+    }
+
     fn parse_error_to_error(shell_command: &str, err: ParseError) -> Error {
         Error::BadShellCommand {
             command: shell_command.to_string(),
@@ -86,8 +131,11 @@ impl ShellExecutor {
         }
     }
 
+    /// The `PATH` scripts are run with: the stubs for mocked commands
+    /// first, then the added paths, then the current `PATH`.
     fn path_env_var(&self) -> Result<OsString, JoinPathsError> {
-        let mut paths: Vec<PathBuf> = self.paths.clone();
+        let mut paths: Vec<PathBuf> = self.stubs_dir.iter().cloned().collect();
+        paths.extend(self.paths.iter().cloned());
 
         if let Ok(current_path) = env::var("PATH") {
             let mut s = env::split_paths(&current_path).collect();
@@ -97,14 +145,36 @@ impl ShellExecutor {
         env::join_paths(paths)
     }
 
-    pub fn build_command(&self, code: &str) -> Command {
+    fn build_command(&self, code: &str) -> Command {
+        self.build_command_with(&self.command, &self.args, code)
+    }
+
+    fn build_command_with(&self, program: &str, args: &[String], code: &str) -> Command {
+        let mut command = self.base_command(program, args);
+        command.arg(code);
+        command
+    }
+
+    /// Builds the command for a persistent session: the shell command
+    /// without its trailing `-c`, so the shell reads its commands from stdin.
+    pub(super) fn session_command(&self) -> Command {
+        let args = match self.args.split_last() {
+            Some((last, rest)) if last == "-c" => rest,
+            _ => &self.args[..],
+        };
+        self.base_command(&self.command, args)
+    }
+
+    fn base_command(&self, program: &str, args: &[String]) -> Command {
         let path = self.path_env_var();
 
-        let mut command = Command::new(&self.command);
+        let mut command = Command::new(program);
+        if self.clean_env {
+            command.env_clear();
+        }
 
         command
-            .args(&self.args)
-            .arg(code)
+            .args(args)
             .envs(&self.env)
             .env("PATH", path.expect("Failed to construct PATH"));
 
@@ -136,6 +206,34 @@ impl Executor for ShellExecutor {
             })
     }
 
+    fn execute_with_interpreter(
+        &self,
+        interpreter: &Interpreter,
+        script: &ScriptCode,
+    ) -> Result<Output, Error> {
+        let Some(interpreter_command) = self.interpreter_command(interpreter) else {
+            return self.execute(script);
+        };
+        let ScriptCode(code_string) = script;
+
+        let words = shell_words::split(interpreter_command)
+            .map_err(|err| Self::parse_error_to_error(interpreter_command, err))
+            .and_then(|words| Self::check_is_not_empty(interpreter_command, &words))?;
+        let (program, args) = words.split_at(1);
+
+        self.build_command_with(&program[0], args, code_string)
+            .output()
+            .map(Output::from)
+            .map_err(|err| Error::CommandFailed {
+                command: interpreter_command.to_string(),
+                message: err.to_string(),
+            })
+    }
+
+    fn start_session(&self) -> Result<Box<dyn Executor>, Error> {
+        ShellSession::start(self.clone()).map(|session| Box::new(session) as Box<dyn Executor>)
+    }
+
     fn spawn(&self, script: &ScriptCode) -> Result<Box<dyn BackgroundHandle>, Error> {
         let ScriptCode(code_string) = script;
 
@@ -162,49 +260,32 @@ impl Executor for ShellExecutor {
             })
     }
 
+    #[cfg(not(windows))]
+    fn with_mock_commands(&self, stubs_dir: &Path) -> Result<Box<dyn Executor>, Error> {
+        let mut shell = self.clone();
+        shell.stubs_dir = Some(stubs_dir.to_path_buf());
+        Ok(Box::new(shell))
+    }
+
     fn clone_box(&self, _label: &str) -> Box<dyn Executor> {
         // ShellExecutor is stateless (each execute() spawns a fresh process),
-        // so cloning just creates a new instance with the same configuration.
-        // The label is not used since the shell executor has no persistent state
-        // to namespace.
-        let env: Vec<(String, String)> = self
-            .env
-            .iter()
-            .map(|(k, v)| (k.clone(), v.clone()))
-            .collect();
-        let paths: Vec<String> = self
-            .paths
-            .iter()
-            .map(|p| p.to_string_lossy().to_string())
-            .collect();
-        let shell_cmd = if self.args.is_empty() {
-            self.command.clone()
-        } else {
-            format!("{} {}", self.command, self.args.join(" "))
-        };
-
-        let working_dir = self.working_dir.clone();
-        ShellExecutor::new::<String>(&shell_cmd, &env, &self.unset_env, &paths).map_or_else(
-            |err| Box::new(super::executor::FailedExecutor(err)) as Box<dyn Executor>,
-            |e| {
-                let e = match working_dir {
-                    Some(dir) => e.with_working_dir(dir),
-                    None => e,
-                };
-                Box::new(e) as Box<dyn Executor>
-            },
-        )
+        // so a copy of its configuration runs scripts the same way. The label
+        // is not used since the shell executor has no persistent state to
+        // namespace.
+        Box::new(self.clone())
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{Error, Executor, ScriptCode, ShellExecutor};
+    use super::{Error, Executor, Interpreter, ScriptCode, ShellExecutor};
 
     mod shell {
         use super::{Error, Executor, ScriptCode, ShellExecutor};
         #[cfg(not(windows))]
         use std::env;
+        #[cfg(not(windows))]
+        use std::path::Path;
         use std::path::PathBuf;
 
         #[cfg(not(windows))]
@@ -313,6 +394,29 @@ mod tests {
             assert_eq!("hello\n", output.stdout);
         }
 
+        #[cfg(not(windows))]
+        #[test]
+        fn with_clean_env_only_sets_the_given_environment_and_path() {
+            env::set_var("NOT_PASSED_ON", "value");
+
+            let shell = ShellExecutor::new::<PathBuf>(
+                "bash -c",
+                &[("MESSAGE".to_string(), "hello".to_string())],
+                &[],
+                &[],
+            )
+            .expect("shell to be created")
+            .with_clean_env();
+
+            let output = shell
+                .execute(&ScriptCode(
+                    "echo \"$MESSAGE:$NOT_PASSED_ON:${PATH:+path}\"".to_string(),
+                ))
+                .expect("success");
+
+            assert_eq!("hello::path\n", output.stdout);
+        }
+
         #[cfg(not(windows))]
         #[test]
         fn with_unset_environment_variable() {
@@ -364,6 +468,45 @@ mod tests {
             assert_eq!(output.stdout.trim(), expected.to_str().unwrap());
         }
 
+        #[cfg(not(windows))]
+        #[test]
+        fn with_mock_commands_puts_the_stubs_first_on_the_path() {
+            let shell = ShellExecutor::new("bash -c", &[], &[], &["my/bin"])
+                .expect("shell to be created")
+                .with_mock_commands(Path::new("/tmp/stubs"))
+                .expect("mock commands to be supported");
+
+            let output = shell
+                .execute(&ScriptCode("echo -n $PATH".to_string()))
+                .expect("success");
+
+            assert!(
+                output.stdout.starts_with("/tmp/stubs:my/bin:"),
+                "{:?}",
+                output.stdout
+            );
+        }
+
+        #[cfg(not(windows))]
+        #[test]
+        fn clone_box_propagates_clean_env() {
+            let shell = ShellExecutor::new::<PathBuf>(
+                "bash -c",
+                &[("MESSAGE".to_string(), "hello".to_string())],
+                &[],
+                &[],
+            )
+            .expect("shell to be created")
+            .with_clean_env();
+
+            let output = shell
+                .clone_box("clone")
+                .execute(&ScriptCode("echo \"$MESSAGE:${USER:-unset}\"".to_string()))
+                .expect("success");
+
+            assert_eq!(output.stdout, "hello:unset\n");
+        }
+
         #[cfg(not(windows))]
         #[test]
         fn clone_box_propagates_working_dir() {
@@ -391,4 +534,76 @@ mod tests {
             assert_eq!(shell.build_command("hello").get_current_dir(), None);
         }
     }
+
+    mod interpreters {
+        use super::{Error, Executor, Interpreter, ScriptCode, ShellExecutor};
+        use std::path::PathBuf;
+
+        fn shell_with_interpreters() -> ShellExecutor {
+            ShellExecutor::new::<PathBuf>("echo shell", &[], &[], &[])
+                .expect("shell to be created")
+                .with_interpreters(&[("python".to_string(), "echo python".to_string())])
+        }
+
+        #[test]
+        fn uses_the_interpreter_configured_for_the_language() {
+            let output = shell_with_interpreters()
+                .execute_with_interpreter(
+                    &Interpreter::Language("python".to_string()),
+                    &ScriptCode("code".to_string()),
+                )
+                .expect("success");
+            assert_eq!(output.stdout, "python code\n");
+        }
+
+        #[test]
+        fn falls_back_to_the_shell_for_an_unconfigured_language() {
+            let output = shell_with_interpreters()
+                .execute_with_interpreter(
+                    &Interpreter::Language("ruby".to_string()),
+                    &ScriptCode("code".to_string()),
+                )
+                .expect("success");
+            assert_eq!(output.stdout, "shell code\n");
+        }
+
+        #[test]
+        fn an_explicit_command_overrides_the_language() {
+            let output = shell_with_interpreters()
+                .execute_with_interpreter(
+                    &Interpreter::Command("echo explicit".to_string()),
+                    &ScriptCode("code".to_string()),
+                )
+                .expect("success");
+            assert_eq!(output.stdout, "explicit code\n");
+        }
+
+        #[test]
+        fn clone_box_propagates_interpreters() {
+            let cloned = shell_with_interpreters().clone_box("label");
+            let output = cloned
+                .execute_with_interpreter(
+                    &Interpreter::Language("python".to_string()),
+                    &ScriptCode("code".to_string()),
+                )
+                .expect("success");
+            assert_eq!(output.stdout, "python code\n");
+        }
+
+        #[test]
+        fn errors_on_an_empty_interpreter_command() {
+            assert_eq!(
+                shell_with_interpreters()
+                    .execute_with_interpreter(
+                        &Interpreter::Command(String::new()),
+                        &ScriptCode("code".to_string()),
+                    )
+                    .err(),
+                Some(Error::BadShellCommand {
+                    command: String::new(),
+                    message: "Command is empty".to_string(),
+                })
+            );
+        }
+    }
 }
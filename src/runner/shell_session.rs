@@ -0,0 +1,337 @@
+//! A persistent shell process shared by every script block of a spec file,
+//! used in session mode (`--session` or a `session()` block).
+//!
+//! Each block is written to a file which the shell sources with `.`, so
+//! `cd`, `export`, functions and aliases carry over to the next block. After
+//! sourcing, the shell prints a unique sentinel line (followed by `$?`) to
+//! stdout and to stderr; everything before the sentinels is that block's
+//! output.
+
+use std::fs;
+use std::io::{Read, Write};
+use std::process::{Child, ChildStdin, Stdio};
+use std::sync::mpsc::{self, Receiver};
+use std::sync::Mutex;
+use std::thread;
+
+use tempfile::TempDir;
+
+use crate::types::{Interpreter, ScriptCode};
+
+use super::background_handle::BackgroundHandle;
+use super::executor::Output;
+use super::shell_executor::ShellExecutor;
+use super::{Error, Executor};
+
+/// Allows aliases defined in one block to be used in the next (bash does
+/// not expand aliases in non-interactive shells by default).
+const SESSION_INIT: &str = "shopt -s expand_aliases 2>/dev/null || true\n";
+
+pub struct ShellSession {
+    shell: ShellExecutor,
+    script_dir: TempDir,
+    process: Mutex<SessionProcess>,
+}
+
+struct SessionProcess {
+    child: Child,
+    stdin: Option<ChildStdin>,
+    stdout: SessionStream,
+    stderr: SessionStream,
+    blocks_run: u64,
+    ended: bool,
+}
+
+impl ShellSession {
+    pub fn start(shell: ShellExecutor) -> Result<Self, Error> {
+        let script_dir = tempfile::tempdir().map_err(|err| session_failed(&err))?;
+
+        let mut child = shell
+            .session_command()
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|err| session_failed(&err))?;
+
+        let mut stdin = child.stdin.take().expect("session stdin is piped");
+        let stdout = SessionStream::spawn(child.stdout.take().expect("session stdout is piped"));
+        let stderr = SessionStream::spawn(child.stderr.take().expect("session stderr is piped"));
+
+        stdin
+            .write_all(SESSION_INIT.as_bytes())
+            .map_err(|err| session_failed(&err))?;
+
+        Ok(Self {
+            shell,
+            script_dir,
+            process: Mutex::new(SessionProcess {
+                child,
+                stdin: Some(stdin),
+                stdout,
+                stderr,
+                blocks_run: 0,
+                ended: false,
+            }),
+        })
+    }
+}
+
+impl Executor for ShellSession {
+    fn execute(&self, script: &ScriptCode) -> Result<Output, Error> {
+        let ScriptCode(code_string) = script;
+        let mut process = self.process.lock().expect("session mutex poisoned");
+
+        if process.ended {
+            return Err(Error::SessionEnded);
+        }
+
+        process.blocks_run += 1;
+        let sentinel = format!(
+            "__SPECDOWN_SESSION_{}_{}__",
+            std::process::id(),
+            process.blocks_run
+        );
+
+        let script_path = self.script_dir.path().join("block.sh");
+        fs::write(&script_path, code_string).map_err(|err| session_failed(&err))?;
+
+        let command = format!(
+            ". {} </dev/null\nprintf '\\n%s %s\\n' '{sentinel}' \"$?\"\nprintf '\\n%s\\n' '{sentinel}' >&2\n",
+            shell_words::quote(&script_path.to_string_lossy()),
+        );
+        let written = process.stdin.as_mut().map(|stdin| {
+            stdin
+                .write_all(command.as_bytes())
+                .and_then(|()| stdin.flush())
+        });
+
+        // A failed write means the shell has already exited; reading the
+        // streams below picks up whatever it printed before it did.
+        if !matches!(written, Some(Ok(()))) {
+            process.stdin = None;
+        }
+
+        match process.stdout.read_until(&sentinel) {
+            Ok((stdout, status)) => {
+                let (Ok((stderr, _)) | Err(stderr)) = process.stderr.read_until(&sentinel);
+                Ok(Output {
+                    stdout,
+                    stderr,
+                    exit_code: status.parse().ok(),
+                })
+            }
+            Err(stdout) => {
+                // The shell exited while running the block (e.g. `exit 1`).
+                let (Ok((stderr, _)) | Err(stderr)) = process.stderr.read_until(&sentinel);
+                process.ended = true;
+                process.stdin = None;
+                let exit_code = process.child.wait().ok().and_then(|status| status.code());
+                Ok(Output {
+                    stdout,
+                    stderr,
+                    exit_code,
+                })
+            }
+        }
+    }
+
+    fn execute_with_interpreter(
+        &self,
+        interpreter: &Interpreter,
+        script: &ScriptCode,
+    ) -> Result<Output, Error> {
+        // Scripts for other interpreters can't share the shell's state, so
+        // they run as separate processes, exactly as they do outside a session.
+        match self.shell.interpreter_command(interpreter) {
+            None => self.execute(script),
+            Some(_) => self.shell.execute_with_interpreter(interpreter, script),
+        }
+    }
+
+    fn start_session(&self) -> Result<Box<dyn Executor>, Error> {
+        self.shell.start_session()
+    }
+
+    fn spawn(&self, script: &ScriptCode) -> Result<Box<dyn BackgroundHandle>, Error> {
+        self.shell.spawn(script)
+    }
+
+    fn clone_box(&self, label: &str) -> Box<dyn Executor> {
+        self.shell.clone_box(label)
+    }
+}
+
+impl Drop for ShellSession {
+    fn drop(&mut self) {
+        if let Ok(process) = self.process.get_mut() {
+            process.stdin = None;
+            let _ = process.child.kill();
+            let _ = process.child.wait();
+        }
+    }
+}
+
+/// One of the session's output pipes, read on a background thread so that
+/// a full stderr pipe can never block the shell while stdout is being read.
+struct SessionStream {
+    receiver: Receiver<Vec<u8>>,
+    buffer: Vec<u8>,
+}
+
+impl SessionStream {
+    fn spawn<R: Read + Send + 'static>(mut reader: R) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            let mut chunk = [0; 4096];
+            while let Ok(count) = reader.read(&mut chunk) {
+                if count == 0 || sender.send(chunk[..count].to_vec()).is_err() {
+                    break;
+                }
+            }
+        });
+        Self {
+            receiver,
+            buffer: Vec::new(),
+        }
+    }
+
+    /// Reads up to the next line starting with `sentinel`, returning the
+    /// output written before it and the rest of the sentinel line. If the
+    /// stream closes first, returns everything that was read as an error.
+    fn read_until(&mut self, sentinel: &str) -> Result<(String, String), String> {
+        // The sentinel is always printed after a newline, which is not part
+        // of the block's output.
+        let needle = format!("\n{sentinel}");
+        loop {
+            if let Some(start) = find(&self.buffer, needle.as_bytes()) {
+                let rest_start = start + needle.len();
+                if let Some(length) = self.buffer[rest_start..].iter().position(|b| *b == b'\n') {
+                    let output = String::from_utf8_lossy(&self.buffer[..start]).into_owned();
+                    let rest =
+                        String::from_utf8_lossy(&self.buffer[rest_start..rest_start + length])
+                            .trim()
+                            .to_string();
+                    self.buffer.drain(..=rest_start + length);
+                    return Ok((output, rest));
+                }
+            }
+
+            if let Ok(chunk) = self.receiver.recv() {
+                self.buffer.extend(chunk);
+            } else {
+                let output = std::mem::take(&mut self.buffer);
+                return Err(String::from_utf8_lossy(&output).into_owned());
+            }
+        }
+    }
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+fn session_failed(err: &std::io::Error) -> Error {
+    Error::SessionFailed {
+        message: err.to_string(),
+    }
+}
+
+#[cfg(all(test, not(windows)))]
+mod tests {
+    use super::ShellSession;
+    use crate::runner::shell_executor::ShellExecutor;
+    use crate::runner::{Error, Executor};
+    use crate::types::{Interpreter, ScriptCode};
+    use std::path::PathBuf;
+
+    fn session() -> ShellSession {
+        let shell =
+            ShellExecutor::new::<PathBuf>("bash -c", &[], &[], &[]).expect("shell to be created");
+        ShellSession::start(shell).expect("session to start")
+    }
+
+    fn run(session: &ShellSession, code: &str) -> (String, String, Option<i32>) {
+        let output = session
+            .execute(&ScriptCode(code.to_string()))
+            .expect("script to run");
+        (output.stdout, output.stderr, output.exit_code)
+    }
+
+    #[test]
+    fn captures_stdout_stderr_and_exit_code_per_block() {
+        let session = session();
+
+        assert_eq!(
+            run(&session, "echo out\necho err >&2\nfalse"),
+            ("out\n".to_string(), "err\n".to_string(), Some(1))
+        );
+        assert_eq!(
+            run(&session, "printf 'no newline'"),
+            ("no newline".to_string(), String::new(), Some(0))
+        );
+    }
+
+    #[test]
+    fn keeps_shell_state_between_blocks() {
+        let session = session();
+
+        run(
+            &session,
+            "export GREETING=hello\ngreet() { echo \"$GREETING $1\"; }\nalias hi='greet alias'\ncd /",
+        );
+
+        assert_eq!(
+            run(&session, "greet world\nhi\npwd"),
+            (
+                "hello world\nhello alias\n/\n".to_string(),
+                String::new(),
+                Some(0)
+            )
+        );
+    }
+
+    #[test]
+    fn scripts_cannot_read_the_session_input() {
+        let session = session();
+
+        assert_eq!(
+            run(&session, "cat"),
+            (String::new(), String::new(), Some(0))
+        );
+        assert_eq!(run(&session, "echo next").0, "next\n");
+    }
+
+    #[test]
+    fn reports_the_exit_code_when_the_shell_exits_and_fails_afterwards() {
+        let session = session();
+
+        assert_eq!(
+            run(&session, "echo bye\nexit 3"),
+            ("bye\n".to_string(), String::new(), Some(3))
+        );
+        assert_eq!(
+            session.execute(&ScriptCode("echo again".to_string())).err(),
+            Some(Error::SessionEnded)
+        );
+    }
+
+    #[test]
+    fn runs_other_interpreters_outside_the_session() {
+        let shell = ShellExecutor::new::<PathBuf>("bash -c", &[], &[], &[])
+            .expect("shell to be created")
+            .with_interpreters(&[("text".to_string(), "echo".to_string())]);
+        let session = ShellSession::start(shell).expect("session to start");
+
+        let output = session
+            .execute_with_interpreter(
+                &Interpreter::Language("text".to_string()),
+                &ScriptCode("plain".to_string()),
+            )
+            .expect("script to run");
+
+        assert_eq!(output.stdout, "plain\n");
+    }
+}
@@ -2,18 +2,26 @@ use std::collections::HashMap;
 
 use crate::results::{ActionResult, ScriptResult};
 
+/// The results of the functions run so far in a spec file, which later
+/// `verify` blocks check against.
+#[derive(Debug)]
 pub struct State {
     last_script_result: Option<ScriptResult>,
     script_results: HashMap<String, ScriptResult>,
     is_success: bool,
 }
 
+/// Looks up the output of scripts which have already run.
 pub trait ScriptOutput {
+    /// The result of the script with the given name.
     fn get_result(&self, name: &str) -> Option<&ScriptResult>;
+    /// The result of the most recently run script.
     fn get_last_result(&self) -> Option<&ScriptResult>;
 }
 
 impl State {
+    /// Creates an empty, successful state.
+    #[must_use]
     pub fn new() -> Self {
         Self {
             last_script_result: None,
@@ -22,6 +30,7 @@ impl State {
         }
     }
 
+    /// Records a function's result.
     pub fn add_result(&mut self, action_result: &ActionResult) {
         if !(action_result.success()) {
             self.is_success = false;
@@ -39,11 +48,19 @@ impl State {
         }
     }
 
+    /// Whether every result recorded so far succeeded.
+    #[must_use]
     pub const fn is_success(&self) -> bool {
         self.is_success
     }
 }
 
+impl Default for State {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl ScriptOutput for State {
     fn get_result(&self, name: &str) -> Option<&ScriptResult> {
         self.script_results.get(name)
@@ -59,8 +76,8 @@ mod tests {
     use super::{ActionResult, ScriptOutput, State};
     use crate::results::{CreateFileResult, ScriptResult, VerifyResult};
     use crate::types::{
-        CreateFileAction, ExitCode, FileContent, FilePath, OutputExpectation, ScriptAction,
-        ScriptCode, ScriptName, Source, Stream, VerifyAction, VerifyValue,
+        CreateFileAction, ExitCode, FileContent, FilePath, Interpreter, OutputExpectation,
+        ScriptAction, ScriptCode, ScriptName, Source, Stream, VerifyAction, VerifyValue,
     };
 
     #[test]
@@ -76,6 +93,7 @@ mod tests {
             script_code: ScriptCode("script1".to_string()),
             expected_exit_code: None,
             expected_output: OutputExpectation::Any,
+            interpreter: Interpreter::Shell,
         };
         let script_result1 = ActionResult::Script(ScriptResult {
             action,
@@ -95,6 +113,7 @@ mod tests {
             script_code: ScriptCode("script1".to_string()),
             expected_exit_code: Some(ExitCode(1)),
             expected_output: OutputExpectation::Any,
+            interpreter: Interpreter::Shell,
         };
         let script_result1 = ActionResult::Script(ScriptResult {
             action,
@@ -127,6 +146,7 @@ mod tests {
                 script_code: ScriptCode("script1".to_string()),
                 expected_exit_code: None,
                 expected_output: OutputExpectation::Any,
+                interpreter: Interpreter::Shell,
             },
             exit_code: Some(ExitCode(0)),
             stdout: "stdout1".to_string(),
@@ -138,6 +158,7 @@ mod tests {
                 script_code: ScriptCode("script1".to_string()),
                 expected_exit_code: None,
                 expected_output: OutputExpectation::Any,
+                interpreter: Interpreter::Shell,
             },
             exit_code: Some(ExitCode(0)),
             stdout: "stdout2".to_string(),
@@ -230,6 +251,7 @@ mod tests {
             script_code: ScriptCode("script1".to_string()),
             expected_exit_code: None,
             expected_output: OutputExpectation::Any,
+            interpreter: Interpreter::Shell,
         };
         let script_result = ScriptResult {
             action,
@@ -249,6 +271,7 @@ mod tests {
             script_code: ScriptCode(String::new()),
             expected_exit_code: None,
             expected_output: OutputExpectation::Any,
+            interpreter: Interpreter::Shell,
         };
         let script_result = ScriptResult {
             action,
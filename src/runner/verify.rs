@@ -36,7 +36,7 @@ pub fn run(action: &VerifyAction, script_output: &dyn ScriptOutput) -> Result<Ac
 mod tests {
     use super::{run, ActionResult, Error, ScriptOutput};
     use crate::results::ScriptResult;
-    use crate::types::{OutputExpectation, ScriptAction, ScriptCode, ScriptName};
+    use crate::types::{Interpreter, OutputExpectation, ScriptAction, ScriptCode, ScriptName};
 
     struct MockScriptOutput {
         result: Option<ScriptResult>,
@@ -55,6 +55,7 @@ mod tests {
                         script_code: ScriptCode(String::new()),
                         expected_exit_code: None,
                         expected_output: OutputExpectation::Any,
+                        interpreter: Interpreter::Shell,
                     },
                     exit_code: None,
                     stdout: stdout.to_string(),
@@ -71,6 +72,7 @@ mod tests {
                         script_code: ScriptCode(String::new()),
                         expected_exit_code: None,
                         expected_output: OutputExpectation::Any,
+                        interpreter: Interpreter::Shell,
                     },
                     exit_code: None,
                     stdout: stdout.to_string(),
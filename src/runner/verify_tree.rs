@@ -0,0 +1,343 @@
+use std::fmt::Write;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use sha2::{Digest, Sha256};
+
+use crate::results::{ActionResult, VerifyTreeResult};
+use crate::types::{FilePath, TreeDetails, VerifyTreeAction};
+
+use super::Error;
+
+pub fn run(action: &VerifyTreeAction, working_dir: &Path) -> Result<ActionResult, Error> {
+    let VerifyTreeAction {
+        path: FilePath(path_string),
+        include,
+        exclude,
+        details,
+        ..
+    } = action;
+
+    if !is_inside(Path::new(path_string)) {
+        return Err(Error::TreeOutsideWorkspace {
+            path: path_string.clone(),
+        });
+    }
+
+    let root = working_dir.join(path_string);
+    let include = build_glob_set(include);
+    let exclude = build_glob_set(exclude);
+
+    let mut files = Vec::new();
+    collect_files(&root, &root, &mut files).map_err(|err| tree_unreadable(path_string, &err))?;
+    files.sort();
+
+    let mut got = String::new();
+    for relative in files
+        .iter()
+        .filter(|relative| include.is_empty() || include.is_match(relative))
+        .filter(|relative| !exclude.is_match(relative))
+    {
+        let line = describe_file(&root.join(relative), relative, *details)
+            .map_err(|err| tree_unreadable(path_string, &err))?;
+        got.push_str(&line);
+        got.push('\n');
+    }
+
+    Ok(ActionResult::VerifyTree(VerifyTreeResult {
+        action: action.clone(),
+        got,
+    }))
+}
+
+/// Builds a matcher from glob patterns that were already validated by the
+/// parser. `*` does not cross `/`; use `**` to match across directories.
+fn build_glob_set(patterns: &[String]) -> GlobSet {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        builder.add(
+            GlobBuilder::new(pattern)
+                .literal_separator(true)
+                .build()
+                .expect("glob patterns are validated when the spec is parsed"),
+        );
+    }
+    builder
+        .build()
+        .expect("glob patterns are validated when the spec is parsed")
+}
+
+/// Whether `path`, relative to the workspace, stays inside it. Only the
+/// components are checked, so `a/../b` is inside but `../b` and `/b` are not.
+fn is_inside(path: &Path) -> bool {
+    let mut depth = 0_usize;
+    for component in path.components() {
+        match component {
+            Component::Normal(_) => depth += 1,
+            Component::CurDir => {}
+            Component::ParentDir if depth > 0 => depth -= 1,
+            Component::ParentDir | Component::RootDir | Component::Prefix(_) => return false,
+        }
+    }
+    true
+}
+
+/// Recursively collects every file under `dir`, as `/`-separated paths
+/// relative to `root` so the listing is identical on every platform.
+/// Symlinks are listed rather than followed, so a link back up the tree
+/// can't make it recurse forever.
+fn collect_files(root: &Path, dir: &Path, files: &mut Vec<String>) -> std::io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        if entry.file_type()?.is_dir() {
+            collect_files(root, &path, files)?;
+        } else {
+            files.push(to_relative_string(root, &path));
+        }
+    }
+    Ok(())
+}
+
+fn to_relative_string(root: &Path, path: &Path) -> String {
+    path.strip_prefix(root)
+        .map_or_else(|_| path.to_path_buf(), PathBuf::from)
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+fn describe_file(path: &Path, relative: &str, details: TreeDetails) -> std::io::Result<String> {
+    let mut line = relative.to_string();
+
+    // A symlink to a directory is listed, but has no size, mode or hash.
+    if fs::metadata(path).is_ok_and(|metadata| metadata.is_dir()) {
+        return Ok(line);
+    }
+
+    if details.size {
+        let _ = write!(line, "  size={}", fs::metadata(path)?.len());
+    }
+
+    if details.mode {
+        let _ = write!(line, "  mode={}", file_mode(path)?);
+    }
+
+    if details.hash {
+        let digest = Sha256::digest(fs::read(path)?);
+        let _ = write!(line, "  sha256={digest:x}");
+    }
+
+    Ok(line)
+}
+
+#[cfg(not(windows))]
+fn file_mode(path: &Path) -> std::io::Result<String> {
+    use std::os::unix::fs::PermissionsExt;
+    Ok(format!(
+        "{:o}",
+        fs::metadata(path)?.permissions().mode() & 0o777
+    ))
+}
+
+/// Windows has no Unix permission bits, so the closest equivalent is shown:
+/// `444` for read-only files and `644` otherwise.
+#[cfg(windows)]
+fn file_mode(path: &Path) -> std::io::Result<String> {
+    let readonly = fs::metadata(path)?.permissions().readonly();
+    Ok(if readonly { "444" } else { "644" }.to_string())
+}
+
+fn tree_unreadable(path: &str, err: &std::io::Error) -> Error {
+    Error::TreeUnreadable {
+        path: path.to_string(),
+        message: err.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::run;
+    use crate::results::{ActionResult, VerifyTreeResult};
+    use crate::runner::Error;
+    use crate::types::{FilePath, TreeDetails, VerifyTreeAction, VerifyValue};
+    use std::fs;
+
+    fn action(include: &[&str], exclude: &[&str], details: TreeDetails) -> VerifyTreeAction {
+        VerifyTreeAction {
+            path: FilePath("out".to_string()),
+            include: include.iter().map(ToString::to_string).collect(),
+            exclude: exclude.iter().map(ToString::to_string).collect(),
+            details,
+            expected_value: VerifyValue(String::new()),
+        }
+    }
+
+    fn listing(result: Result<ActionResult, Error>) -> String {
+        match result {
+            Ok(ActionResult::VerifyTree(VerifyTreeResult { got, .. })) => got,
+            other => panic!("expected a verify_tree result, got {:?}", other),
+        }
+    }
+
+    fn fixture() -> tempfile::TempDir {
+        let dir = tempfile::tempdir().expect("failed to create temp dir");
+        fs::create_dir_all(dir.path().join("out/src/nested")).expect("failed to create dirs");
+        fs::write(dir.path().join("out/README.md"), "readme").expect("failed to write file");
+        fs::write(dir.path().join("out/src/main.rs"), "fn main() {}").expect("failed to write");
+        fs::write(dir.path().join("out/src/nested/lib.rs"), "").expect("failed to write file");
+        dir
+    }
+
+    #[test]
+    fn lists_every_file_sorted_with_forward_slashes() {
+        let dir = fixture();
+
+        let got = listing(run(&action(&[], &[], TreeDetails::default()), dir.path()));
+
+        assert_eq!(got, "README.md\nsrc/main.rs\nsrc/nested/lib.rs\n");
+    }
+
+    #[test]
+    fn applies_include_then_exclude_globs() {
+        let dir = fixture();
+
+        let got = listing(run(
+            &action(&["**/*.rs"], &["src/nested/**"], TreeDetails::default()),
+            dir.path(),
+        ));
+
+        assert_eq!(got, "src/main.rs\n");
+    }
+
+    #[test]
+    fn single_star_does_not_cross_directories() {
+        let dir = fixture();
+
+        let got = listing(run(
+            &action(&["*"], &[], TreeDetails::default()),
+            dir.path(),
+        ));
+
+        assert_eq!(got, "README.md\n");
+    }
+
+    #[test]
+    fn shows_size_and_hash_when_requested() {
+        let dir = fixture();
+
+        let got = listing(run(
+            &action(
+                &["README.md"],
+                &[],
+                TreeDetails {
+                    size: true,
+                    mode: false,
+                    hash: true,
+                },
+            ),
+            dir.path(),
+        ));
+
+        assert_eq!(
+            got,
+            "README.md  size=6  sha256=\
+             711a6108ba2ce6ca93dd47d6817f2361db10d8ab6eec89460b2dfc2c325efabe\n"
+        );
+    }
+
+    #[cfg(not(windows))]
+    #[test]
+    fn shows_the_permission_bits_when_requested() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = fixture();
+        fs::set_permissions(
+            dir.path().join("out/README.md"),
+            fs::Permissions::from_mode(0o755),
+        )
+        .expect("failed to set permissions");
+
+        let got = listing(run(
+            &action(
+                &["README.md"],
+                &[],
+                TreeDetails {
+                    size: false,
+                    mode: true,
+                    hash: false,
+                },
+            ),
+            dir.path(),
+        ));
+
+        assert_eq!(got, "README.md  mode=755\n");
+    }
+
+    #[cfg(not(windows))]
+    #[test]
+    fn lists_symlinks_without_following_them() {
+        let dir = fixture();
+        std::os::unix::fs::symlink("..", dir.path().join("out/src/up"))
+            .expect("failed to create symlink");
+
+        let got = listing(run(
+            &action(
+                &[],
+                &[],
+                TreeDetails {
+                    size: true,
+                    mode: false,
+                    hash: false,
+                },
+            ),
+            dir.path(),
+        ));
+
+        assert_eq!(
+            got,
+            "README.md  size=6\nsrc/main.rs  size=12\nsrc/nested/lib.rs  size=0\nsrc/up\n"
+        );
+    }
+
+    #[test]
+    fn errors_when_the_path_is_outside_the_workspace() {
+        let dir = fixture();
+
+        for path in &["../out", "out/../..", "/tmp"] {
+            let mut action = action(&[], &[], TreeDetails::default());
+            action.path = FilePath(path.to_string());
+
+            let result = run(&action, dir.path());
+
+            assert!(
+                matches!(result, Err(Error::TreeOutsideWorkspace { .. })),
+                "expected {:?} to be rejected, got {:?}",
+                path,
+                result
+            );
+        }
+    }
+
+    #[test]
+    fn allows_a_path_which_goes_up_and_back_down() {
+        let dir = fixture();
+        let mut action = action(&["README.md"], &[], TreeDetails::default());
+        action.path = FilePath("out/src/../.".to_string());
+
+        let got = listing(run(&action, dir.path()));
+
+        assert_eq!(got, "README.md\n");
+    }
+
+    #[test]
+    fn errors_when_the_directory_does_not_exist() {
+        let dir = tempfile::tempdir().expect("failed to create temp dir");
+
+        let result = run(&action(&[], &[], TreeDetails::default()), dir.path());
+
+        assert!(matches!(result, Err(Error::TreeUnreadable { .. })));
+    }
+}
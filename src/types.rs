@@ -1,14 +1,21 @@
+//! The actions parsed from spec files, and the values they hold.
+
 use std::convert::TryFrom;
 
-#[derive(Clone, Debug, Eq, PartialEq)]
+/// One of a script's output streams.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum Stream {
+    /// Standard output.
     StdOut,
+    /// Standard error.
     StdErr,
 }
 
+/// The name of an operating system, as used in `target_os` arguments.
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct TargetOs(pub String);
 
+/// The name given to a script with `script(name="...")`.
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct ScriptName(pub String);
 
@@ -26,15 +33,20 @@ impl From<&ScriptName> for String {
     }
 }
 
+/// The script output a `verify` block checks.
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct Source {
+    /// The script to check, or `None` for the most recently run script.
     pub name: Option<ScriptName>,
+    /// The stream to check.
     pub stream: Stream,
 }
 
+/// The code of a script.
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct ScriptCode(pub String);
 
+/// The expected output in a `verify` block.
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct VerifyValue(pub String);
 
@@ -45,6 +57,7 @@ impl From<VerifyValue> for String {
     }
 }
 
+/// A path relative to the working directory.
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct FilePath(pub String);
 
@@ -55,9 +68,11 @@ impl From<FilePath> for String {
     }
 }
 
+/// The contents of a `file` block.
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct FileContent(pub String);
 
+/// A process exit code.
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub struct ExitCode(pub i32);
 
@@ -75,46 +90,155 @@ impl From<ExitCode> for i32 {
     }
 }
 
-#[derive(Clone, Debug, Eq, PartialEq)]
+/// Which streams a script is allowed to write to, from its
+/// `expected_output` argument.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum OutputExpectation {
+    /// Either stream, or neither.
     Any,
+    /// Only stdout.
     StdOut,
+    /// Only stderr.
     StdErr,
+    /// Neither stream.
     None,
 }
 
+/// Which program a `script` block is run with.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub enum Interpreter {
+    /// Run with the configured shell command (the block has no language).
+    #[default]
+    Shell,
+    /// Run with the interpreter configured for this code block language in
+    /// `[run.interpreters]`, falling back to the shell command when the
+    /// language has no interpreter configured.
+    Language(String),
+    /// Run with this command, from a `script(interpreter="...")` override.
+    /// The script code is appended as the final argument.
+    Command(String),
+}
+
+/// A `script` block.
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct ScriptAction {
+    /// The name later `verify` blocks refer to the script by.
     pub script_name: Option<ScriptName>,
+    /// The code to run.
     pub script_code: ScriptCode,
+    /// The exit code the script must exit with, if any.
     pub expected_exit_code: Option<ExitCode>,
+    /// Which streams the script may write to.
     pub expected_output: OutputExpectation,
+    /// What the script is run with.
+    pub interpreter: Interpreter,
+}
+
+/// One `$ ` command from a `console` block, with the output (and exit code)
+/// that follows it in the transcript.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ConsoleCommandAction {
+    /// The command, with any continuation lines.
+    pub command: ScriptCode,
+    /// The output following the command in the transcript.
+    pub expected_output: VerifyValue,
+    /// The exit code from an `[exit N]` line, or 0.
+    pub expected_exit_code: ExitCode,
 }
 
+/// A `verify` block.
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct VerifyAction {
+    /// The script output to check.
     pub source: Source,
+    /// The output the script must have produced.
     pub expected_value: VerifyValue,
 }
 
 impl VerifyAction {
+    /// Returns a copy of this verification, checking the named script.
+    #[must_use]
     pub fn with_script_name(&self, script_name: Option<ScriptName>) -> Self {
         Self {
             source: Source {
                 name: script_name,
-                stream: self.source.stream.clone(),
+                stream: self.source.stream,
             },
             expected_value: self.expected_value.clone(),
         }
     }
 }
 
+/// A `file` block.
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct CreateFileAction {
+    /// Where the file is created.
     pub file_path: FilePath,
+    /// What is written to the file.
     pub file_content: FileContent,
 }
 
+/// Which extra details a `verify_tree` block lists next to each file path.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct TreeDetails {
+    /// Show the file size in bytes (`size=<n>`).
+    pub size: bool,
+    /// Show the Unix permission bits in octal (`mode=<nnn>`).
+    pub mode: bool,
+    /// Show the SHA-256 hash of the file contents (`sha256=<hex>`).
+    pub hash: bool,
+}
+
+/// A `verify_tree` block: lists the files under `path` (relative to the
+/// working directory) and compares the listing to `expected_value`.
+///
+/// `include` and `exclude` are glob patterns matched against each file's
+/// path relative to `path`. When `include` is empty every file is included;
+/// `exclude` is applied afterwards.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct VerifyTreeAction {
+    /// The directory to list.
+    pub path: FilePath,
+    /// Glob patterns of the files to list.
+    pub include: Vec<String>,
+    /// Glob patterns of the files to leave out.
+    pub exclude: Vec<String>,
+    /// The details listed next to each file.
+    pub details: TreeDetails,
+    /// The listing the directory must produce.
+    pub expected_value: VerifyValue,
+}
+
+/// A `mock_command` block: what a stub for the command `name` writes, and
+/// the code it exits with, when its arguments match `args`.
+///
+/// The stub is put in a directory at the start of `PATH`, so scripts run it
+/// instead of the real command, and records every call made to it.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MockCommandAction {
+    /// The name of the command to stub.
+    pub name: String,
+    /// A shell pattern the arguments, joined by spaces, must match, or
+    /// `None` to match any arguments.
+    pub args: Option<String>,
+    /// The stream `output` is written to.
+    pub stream: Stream,
+    /// The code the stub exits with.
+    pub exit_code: ExitCode,
+    /// What the stub writes.
+    pub output: String,
+}
+
+/// A `verify(mock_command=...)` block: checks the calls made to the stub for
+/// the command `name`, one per line, against `expected_value`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct VerifyMockCommandAction {
+    /// The name of the mocked command.
+    pub name: String,
+    /// The calls which must have been made.
+    pub expected_value: VerifyValue,
+}
+
 /// A readiness condition for a `background` block's `ready_when` argument.
 ///
 /// When set, the runner spawns the background script (non-blocking) and then
@@ -145,9 +269,12 @@ pub enum ReadyWhen {
 /// no explicit `timeout_secs` argument is provided.
 pub const DEFAULT_READY_WHEN_TIMEOUT_SECS: u32 = 30;
 
+/// A `background` block.
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct BackgroundAction {
+    /// The name of the background script.
     pub script_name: Option<ScriptName>,
+    /// The code to run.
     pub script_code: ScriptCode,
     /// When set, the runner polls this condition after spawning and blocks
     /// until it is satisfied (or `timeout_secs` elapses). When `None`, the
@@ -189,26 +316,32 @@ impl std::fmt::Display for MockName {
 pub struct StatusCode(pub u16);
 
 /// Error returned when a status code is outside the valid range.
-#[derive(Clone, Debug, Eq, PartialEq)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub struct StatusCodeError {
+    /// The value which was out of range.
     pub value: i32,
 }
 
 impl StatusCodeError {
+    /// The value which was out of range.
     #[must_use]
-    pub fn value(&self) -> i32 {
+    pub fn value(self) -> i32 {
         self.value
     }
 }
 
 impl StatusCode {
     /// Parse an integer into a `StatusCode`, validating the HTTP range 100..=599.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `value` is outside the range.
     pub fn parse(value: i32) -> Result<Self, StatusCodeError> {
-        if (100..=599).contains(&value) {
-            Ok(StatusCode(u16::try_from(value).expect("validated range")))
-        } else {
-            Err(StatusCodeError { value })
-        }
+        u16::try_from(value)
+            .ok()
+            .filter(|code| (100..=599).contains(code))
+            .map(StatusCode)
+            .ok_or(StatusCodeError { value })
     }
 }
 
@@ -221,7 +354,9 @@ impl Default for StatusCode {
 /// A response header. The name is stored lower-cased per HTTP convention.
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct ResponseHeader {
+    /// The header name, lower-cased.
     pub name: String,
+    /// The header value.
     pub value: String,
 }
 
@@ -231,14 +366,16 @@ pub struct ResponseHeader {
 pub struct DelayMillis(pub u32);
 
 /// Error returned when a delay value exceeds the maximum.
-#[derive(Clone, Debug, Eq, PartialEq)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub struct DelayMillisError {
+    /// The value which was out of range.
     pub value: i32,
 }
 
 impl DelayMillisError {
+    /// The value which was out of range.
     #[must_use]
-    pub fn value(&self) -> i32 {
+    pub fn value(self) -> i32 {
         self.value
     }
 }
@@ -248,13 +385,16 @@ pub const MAX_DELAY_MILLIS: i32 = 300_000;
 
 impl DelayMillis {
     /// Parse an integer into a `DelayMillis`, capping at `MAX_DELAY_MILLIS`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `value` is negative or above `MAX_DELAY_MILLIS`.
     pub fn parse(value: i32) -> Result<Self, DelayMillisError> {
-        if !(0..=MAX_DELAY_MILLIS).contains(&value) {
-            return Err(DelayMillisError { value });
-        }
-        Ok(DelayMillis(
-            u32::try_from(value).expect("validated non-negative"),
-        ))
+        u32::try_from(value)
+            .ok()
+            .filter(|_| value <= MAX_DELAY_MILLIS)
+            .map(DelayMillis)
+            .ok_or(DelayMillisError { value })
     }
 }
 
@@ -284,31 +424,58 @@ pub enum ResponseBody {
 /// A parsed `response` code block — the raw values before action decoding.
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct ResponseCodeBlock {
+    /// The name of the mock endpoint.
     pub name: MockName,
+    /// The response status code.
     pub status: StatusCode,
-    pub headers: Option<String>,
+    /// The `headers` argument, with names lower-cased.
+    pub headers: Vec<ResponseHeader>,
+    /// The `content_type` argument.
     pub content_type: Option<String>,
+    /// How long to wait before responding.
     pub delay: DelayMillis,
+    /// The response body.
     pub body: ResponseBody,
 }
 
 /// A decoded response action — headers expanded, body resolved.
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct ResponseAction {
+    /// The name of the mock endpoint.
     pub name: MockName,
+    /// The response status code.
     pub status: StatusCode,
+    /// The response headers.
     pub headers: Vec<ResponseHeader>,
+    /// How long to wait before responding.
     pub delay: DelayMillis,
+    /// The response body.
     pub body: ResponseBody,
 }
 
+/// A specdown function parsed from a spec file.
 #[derive(Debug, Eq, PartialEq)]
 pub enum Action {
+    /// A `script` block.
     Script(ScriptAction),
+    /// A `verify` block.
     Verify(VerifyAction),
+    /// A `file` block.
     CreateFile(CreateFileAction),
+    /// A `background` block.
     Background(BackgroundAction),
+    /// A `response` block.
     Response(ResponseAction),
+    /// A `verify_tree` block.
+    VerifyTree(VerifyTreeAction),
+    /// A `mock_command` block.
+    MockCommand(MockCommandAction),
+    /// A `verify(mock_command=...)` block.
+    VerifyMockCommand(VerifyMockCommandAction),
+    /// A script which puts the whole spec file into session mode.
+    Session(ScriptAction),
+    /// A command from a `console` block.
+    ConsoleCommand(ConsoleCommandAction),
 }
 
 #[cfg(test)]
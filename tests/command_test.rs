@@ -194,6 +194,100 @@ fn test_doc_completion() {
     assert_ok(&result);
 }
 
+#[cfg(not(windows))]
+#[test]
+fn test_doc_checking_specs() {
+    let result = specdown_run_with_path()
+        .arg("docs/cli/checking_specs.md")
+        .ok();
+
+    assert_ok(&result);
+}
+
+#[cfg(not(windows))]
+#[test]
+fn test_doc_durations() {
+    let result = specdown_run_with_path().arg("docs/cli/durations.md").ok();
+
+    assert_ok(&result);
+}
+
+#[cfg(not(windows))]
+#[test]
+fn test_doc_sharding() {
+    let result = specdown_run_with_path().arg("docs/cli/sharding.md").ok();
+
+    assert_ok(&result);
+}
+
+#[cfg(not(windows))]
+#[test]
+fn test_doc_rerunning_failed_specs() {
+    let result = specdown_run_with_path()
+        .arg("docs/cli/rerunning_failed_specs.md")
+        .ok();
+
+    assert_ok(&result);
+}
+
+#[cfg(not(windows))]
+#[test]
+fn test_doc_shuffling() {
+    let result = specdown_run_with_path().arg("docs/cli/shuffling.md").ok();
+
+    assert_ok(&result);
+}
+
+#[cfg(not(windows))]
+#[test]
+fn test_doc_front_matter() {
+    let result = specdown_run_with_path()
+        .arg("docs/cli/front_matter.md")
+        .ok();
+
+    assert_ok(&result);
+}
+
+#[cfg(not(windows))]
+#[test]
+fn test_doc_selecting_spec_files() {
+    let result = specdown_run_with_path()
+        .arg("docs/cli/selecting_spec_files.md")
+        .ok();
+
+    assert_ok(&result);
+}
+
+#[cfg(not(windows))]
+#[test]
+fn test_doc_masking_secrets() {
+    let result = specdown_run_with_path()
+        .arg("docs/cli/masking_secrets.md")
+        .ok();
+
+    assert_ok(&result);
+}
+
+#[cfg(not(windows))]
+#[test]
+fn test_doc_hermetic_environment() {
+    let result = specdown_run_with_path()
+        .arg("docs/cli/hermetic_environment.md")
+        .ok();
+
+    assert_ok(&result);
+}
+
+#[cfg(not(windows))]
+#[test]
+fn test_doc_language_server() {
+    let result = specdown_run_with_path()
+        .arg("docs/cli/language_server.md")
+        .ok();
+
+    assert_ok(&result);
+}
+
 #[cfg(not(windows))]
 #[test]
 fn test_doc_stripping_specs() {
@@ -213,6 +307,86 @@ fn test_doc_background_scripts() {
     assert_ok(&result);
 }
 
+#[cfg(not(windows))]
+#[test]
+fn test_doc_mocking_commands() {
+    let result = specdown_run_with_path()
+        .arg("docs/specs/mocking_commands.md")
+        .ok();
+
+    assert_ok(&result);
+}
+
+#[cfg(not(windows))]
+#[test]
+fn test_doc_verifying_directory_trees() {
+    let result = specdown_run_with_path()
+        .arg("docs/specs/verifying_directory_trees.md")
+        .ok();
+
+    assert_ok(&result);
+}
+
+#[cfg(not(windows))]
+#[test]
+fn test_doc_script_interpreters() {
+    let result = specdown_run_with_path()
+        .arg("docs/specs/script_interpreters.md")
+        .ok();
+
+    assert_ok(&result);
+}
+
+#[cfg(not(windows))]
+#[test]
+fn test_doc_shell_sessions() {
+    let result = specdown_run_with_path()
+        .arg("docs/specs/shell_sessions.md")
+        .ok();
+
+    assert_ok(&result);
+}
+
+#[cfg(not(windows))]
+#[test]
+fn test_doc_console_blocks() {
+    let result = specdown_run_with_path()
+        .arg("docs/specs/console_blocks.md")
+        .ok();
+
+    assert_ok(&result);
+}
+
+#[cfg(not(windows))]
+#[test]
+fn test_doc_html_comment_annotations() {
+    let result = specdown_run_with_path()
+        .arg("docs/specs/html_comment_annotations.md")
+        .ok();
+
+    assert_ok(&result);
+}
+
+#[cfg(not(windows))]
+#[test]
+fn test_doc_nested_code_blocks() {
+    let result = specdown_run_with_path()
+        .arg("docs/specs/nested_code_blocks.md")
+        .ok();
+
+    assert_ok(&result);
+}
+
+#[cfg(not(windows))]
+#[test]
+fn test_doc_argument_values() {
+    let result = specdown_run_with_path()
+        .arg("docs/specs/argument_values.md")
+        .ok();
+
+    assert_ok(&result);
+}
+
 #[test]
 fn test_doc_container_executor() {
     let result = specdown_run_with_path()
@@ -240,16 +414,19 @@ fn test_displays_error_when_required_args_are_missing() {
             Usage: {} [OPTIONS] <COMMAND>
 
             Commands:
+              check       Checks Markdown Specifications for problems without running them
               completion  Output completion for a shell of your choice
+              lsp         Runs a language server for Markdown Specifications over stdio
               run         Runs a given Markdown Specification
               strip       Outputs a version of the markdown with all specdown functions removed
               help        Print this message or the help of the given subcommand(s)
 
             Options:
-                  --no-colour      Disables coloured output
-                  --config <PATH>  Load settings from a specific config file instead of looking for `specdown.toml` in the current directory
-              -h, --help           Print help
-              -V, --version        Print version
+                  --no-colour       Disables coloured output
+                  --config <PATH>   Load settings from a specific config file instead of looking for `specdown.toml` in the current directory and those above it
+                  --profile <NAME>  Overlay the `[run]` settings with those of a `[profile.<NAME>]` table in the config file. Defaults to the `SPECDOWN_PROFILE` environment variable
+              -h, --help            Print help
+              -V, --version         Print version
             ",
             BINARY_NAME
         ));
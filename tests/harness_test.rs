@@ -0,0 +1,23 @@
+use std::env;
+use std::path::Path;
+
+use specdown::Specdown;
+
+fn main() {
+    // The specs run the `specdown` binary, so it needs to be on the PATH.
+    let bin_dir = Path::new(env!("CARGO_BIN_EXE_specdown"))
+        .parent()
+        .expect("the binary to be in a directory");
+    let path = env::var_os("PATH").unwrap_or_default();
+    let paths = std::iter::once(bin_dir.to_path_buf()).chain(env::split_paths(&path));
+    env::set_var("PATH", env::join_paths(paths).expect("PATH to be joinable"));
+
+    specdown::harness::run(
+        &[
+            "docs/specs/verifying_exit_codes.md",
+            "docs/specs/nested_code_blocks.md",
+            "docs/specs/console_blocks.md",
+        ],
+        |workspace: &Path| Specdown::new().working_dir(workspace),
+    );
+}
@@ -0,0 +1,153 @@
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use specdown::{
+    Action, ActionResult, Error, Executor, Output, ParseError, Printer, RunEvent, Runner,
+    ScriptCode, ScriptName, Specdown, State,
+};
+
+/// An executor which "runs" every script by echoing its code back.
+struct EchoExecutor;
+
+impl Executor for EchoExecutor {
+    fn execute(&self, script: &ScriptCode) -> Result<Output, Error> {
+        let ScriptCode(code) = script;
+        Ok(Output {
+            stdout: code.clone(),
+            stderr: String::new(),
+            exit_code: Some(0),
+        })
+    }
+}
+
+/// An executor which can't run anything.
+struct UnrunnableExecutor;
+
+impl Executor for UnrunnableExecutor {
+    fn execute(&self, script: &ScriptCode) -> Result<Output, Error> {
+        let ScriptCode(code) = script;
+        Err(Error::CommandFailed {
+            command: code.clone(),
+            message: "no shell".to_string(),
+        })
+    }
+}
+
+/// A printer which records the spec files it was told about.
+struct RecordingPrinter(Arc<Mutex<Vec<PathBuf>>>);
+
+impl Printer for RecordingPrinter {
+    fn print(&mut self, event: &RunEvent) {
+        if let RunEvent::SpecFileStarted(path) = event {
+            self.0
+                .lock()
+                .expect("printer mutex poisoned")
+                .push(path.clone());
+        }
+    }
+}
+
+fn write_spec(dir: &tempfile::TempDir, name: &str, contents: &str) -> PathBuf {
+    let path = dir.path().join(name);
+    fs::write(&path, contents).expect("failed to write spec file");
+    path
+}
+
+#[test]
+fn runs_spec_files_with_a_custom_executor_and_printer() {
+    let dir = tempfile::tempdir().expect("failed to create temp dir");
+    let passing = write_spec(
+        &dir,
+        "passing.md",
+        "```shell,script(name=\"greet\")\nhello\n```\n\n```text,verify(script_name=\"greet\")\nhello\n```\n",
+    );
+    let failing = write_spec(
+        &dir,
+        "failing.md",
+        "```shell,script(name=\"greet\")\nhello\n```\n\n```text,verify(script_name=\"greet\")\ngoodbye\n```\n",
+    );
+    let printed = Arc::new(Mutex::new(Vec::new()));
+
+    let report = Specdown::new()
+        .executor(EchoExecutor)
+        .printer(RecordingPrinter(Arc::clone(&printed)))
+        .working_dir(dir.path())
+        .run([&passing, &failing])
+        .expect("specs to run");
+
+    assert!(!report.success());
+    assert_eq!(report.spec_files.len(), 2);
+
+    assert_eq!(report.spec_files[0].path, passing);
+    assert!(report.spec_files[0].success());
+    assert_eq!(report.spec_files[0].results.len(), 2);
+    assert_eq!(report.spec_files[0].durations.len(), 2);
+
+    assert_eq!(report.spec_files[1].path, failing);
+    assert!(!report.spec_files[1].success());
+    let failures: Vec<&ActionResult> = report.spec_files[1].failures().collect();
+    assert!(matches!(failures[..], [ActionResult::Verify(_)]));
+
+    assert_eq!(
+        *printed.lock().expect("printer mutex poisoned"),
+        vec![passing, failing]
+    );
+}
+
+#[test]
+fn reports_spec_files_which_can_not_be_read() {
+    let dir = tempfile::tempdir().expect("failed to create temp dir");
+
+    let report = Specdown::new()
+        .executor(EchoExecutor)
+        .working_dir(dir.path())
+        .run([dir.path().join("missing.md")])
+        .expect("specs to run");
+
+    assert!(!report.success());
+    assert!(matches!(
+        report.spec_files[0].errors[..],
+        [Error::SpecFileUnreadable { .. }]
+    ));
+}
+
+#[test]
+fn returns_an_error_when_the_workspace_init_command_can_not_be_run() {
+    let dir = tempfile::tempdir().expect("failed to create temp dir");
+    let spec = write_spec(&dir, "spec.md", "# Spec\n");
+
+    let result = Specdown::new()
+        .executor(UnrunnableExecutor)
+        .workspace_init_command("make setup")
+        .working_dir(dir.path())
+        .run([spec]);
+
+    assert!(matches!(result, Err(Error::CommandFailed { .. })));
+}
+
+#[test]
+fn runs_parsed_actions_with_a_runner() {
+    let dir = tempfile::tempdir().expect("failed to create temp dir");
+    let actions = specdown::parse(
+        "```shell,script(name=\"greet\")\nhello\n```\n\n```text,verify(script_name=\"greet\")\nhello\n```\n",
+    )
+    .expect("markdown to parse");
+    assert!(matches!(
+        &actions[0],
+        Action::Script(script) if script.script_name == Some(ScriptName("greet".to_string()))
+    ));
+
+    let mut state = State::new();
+    let events = Runner::create(&EchoExecutor, dir.path(), &mut state).run(&actions);
+
+    assert_eq!(events.len(), 2);
+    assert!(state.is_success());
+}
+
+#[test]
+fn returns_a_parse_error_for_an_unknown_function() {
+    let result = specdown::parse("```shell,scrpt()\necho a\n```\n");
+
+    assert!(matches!(result, Err(ParseError::InCodeBlock { .. })));
+}